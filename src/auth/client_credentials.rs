@@ -1,14 +1,83 @@
+use crate::error::FireboltError;
+#[cfg(feature = "reqwest")]
+use crate::transport::ReqwestTransport;
+use crate::transport::{HttpRequest, HttpResponse, Method, Transport};
 use crate::version::user_agent;
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+pub const DEFAULT_AUDIENCE: &str = "https://api.firebolt.io";
+
 #[derive(Serialize)]
 struct AuthRequest {
     client_id: String,
     client_secret: String,
     grant_type: String,
     audience: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+}
+
+// Where and for what tokens are requested. Scopes are sent space-separated
+// as `scope` and left out of the request when there are none. Without
+// `token_url`, the URL is derived from the API endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct AuthOptions {
+    pub(crate) audience: String,
+    pub(crate) scopes: Vec<String>,
+    pub(crate) token_url: Option<String>,
+}
+
+impl Default for AuthOptions {
+    fn default() -> Self {
+        Self {
+            audience: DEFAULT_AUDIENCE.to_string(),
+            scopes: Vec::new(),
+            token_url: None,
+        }
+    }
+}
+
+impl AuthOptions {
+    pub(crate) fn validate(&self) -> Result<(), FireboltError> {
+        if let Some(token_url) = &self.token_url {
+            let valid = url::Url::parse(token_url).is_ok_and(|url| {
+                matches!(url.scheme(), "http" | "https")
+                    && url.host_str().is_some_and(|host| !host.is_empty())
+            });
+            if !valid {
+                return Err(FireboltError::Configuration(format!(
+                    "Invalid auth URL '{token_url}': it must be an http or https URL with a host"
+                )));
+            }
+        }
+        if self.audience.trim().is_empty() {
+            return Err(FireboltError::Configuration(
+                "The OAuth audience must not be empty".to_string(),
+            ));
+        }
+        if let Some(scope) = self
+            .scopes
+            .iter()
+            .find(|scope| scope.is_empty() || scope.contains(char::is_whitespace))
+        {
+            return Err(FireboltError::Configuration(format!(
+                "Invalid OAuth scope '{scope}': it must be non-empty and contain no whitespace"
+            )));
+        }
+        Ok(())
+    }
+
+    fn scope(&self) -> Option<String> {
+        (!self.scopes.is_empty()).then(|| self.scopes.join(" "))
+    }
+
+    pub(crate) fn token_url(&self, api_endpoint: &str) -> Result<String, String> {
+        match &self.token_url {
+            Some(token_url) => Ok(token_url.clone()),
+            None => validate_and_transform_endpoint(api_endpoint),
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -17,43 +86,178 @@ struct AuthResponse {
     expires_in: u64,
 }
 
+#[cfg(feature = "reqwest")]
 pub async fn authenticate(
     client_id: String,
     client_secret: String,
     api_endpoint: String,
 ) -> Result<(String, u64), String> {
-    let auth_url = validate_and_transform_endpoint(&api_endpoint)?;
+    authenticate_with_transport(
+        &ReqwestTransport::new(),
+        client_id,
+        client_secret,
+        api_endpoint,
+    )
+    .await
+}
 
-    let auth_request = AuthRequest {
+pub async fn authenticate_with_transport(
+    transport: &dyn Transport,
+    client_id: String,
+    client_secret: String,
+    api_endpoint: String,
+) -> Result<(String, u64), String> {
+    fetch_token(
+        transport,
         client_id,
         client_secret,
-        grant_type: "client_credentials".to_string(),
-        audience: "https://api.firebolt.io".to_string(),
-    };
+        api_endpoint,
+        &AuthOptions::default(),
+        &user_agent(),
+    )
+    .await
+    .map_err(error_message)
+}
 
-    let client = Client::new();
+pub(crate) async fn fetch_token(
+    transport: &dyn Transport,
+    client_id: String,
+    client_secret: String,
+    api_endpoint: String,
+    options: &AuthOptions,
+    user_agent: &str,
+) -> Result<(String, u64), FireboltError> {
+    cached_token(
+        transport,
+        client_id,
+        client_secret,
+        api_endpoint,
+        options,
+        user_agent,
+        None,
+    )
+    .await
+}
 
-    let response = client
-        .post(&auth_url)
-        .header("User-Agent", user_agent())
-        .json(&auth_request)
-        .send()
+// Replaces a token the server rejected. Concurrent refreshes of the same
+// token share a single request.
+pub(crate) async fn refresh_token(
+    transport: &dyn Transport,
+    client_id: String,
+    client_secret: String,
+    api_endpoint: String,
+    options: &AuthOptions,
+    user_agent: &str,
+    rejected: &str,
+) -> Result<(String, u64), FireboltError> {
+    cached_token(
+        transport,
+        client_id,
+        client_secret,
+        api_endpoint,
+        options,
+        user_agent,
+        Some(rejected),
+    )
+    .await
+}
+
+async fn cached_token(
+    transport: &dyn Transport,
+    client_id: String,
+    client_secret: String,
+    api_endpoint: String,
+    options: &AuthOptions,
+    user_agent: &str,
+    rejected: Option<&str>,
+) -> Result<(String, u64), FireboltError> {
+    let (id, secret, endpoint) = (
+        client_id.clone(),
+        client_secret.clone(),
+        api_endpoint.clone(),
+    );
+    super::token_cache::get_or_fetch(&id, &secret, &endpoint, options, rejected, || async move {
+        let operation = crate::telemetry::Operation::Authenticate {
+            api_endpoint: &api_endpoint,
+        };
+        crate::telemetry::instrument(
+            operation,
+            request_token(
+                transport,
+                client_id,
+                client_secret,
+                api_endpoint.clone(),
+                options,
+                user_agent,
+            ),
+        )
         .await
-        .map_err(|e| format!("Network error: {e}"))?;
+    })
+    .await
+}
+
+pub(crate) fn error_message(error: FireboltError) -> String {
+    match error {
+        FireboltError::Authentication(msg) => msg,
+        FireboltError::Network(msg) => format!("Network error: {msg}"),
+        other => other.to_string(),
+    }
+}
 
-    let status = response.status();
+async fn request_token(
+    transport: &dyn Transport,
+    client_id: String,
+    client_secret: String,
+    api_endpoint: String,
+    options: &AuthOptions,
+    user_agent: &str,
+) -> Result<(String, u64), FireboltError> {
+    let auth_url = options
+        .token_url(&api_endpoint)
+        .map_err(FireboltError::Authentication)?;
+
+    let auth_request = AuthRequest {
+        client_id,
+        client_secret,
+        grant_type: "client_credentials".to_string(),
+        audience: options.audience.clone(),
+        scope: options.scope(),
+    };
 
-    if status.is_success() {
-        handle_success_response(response).await
+    crate::telemetry::trace_event!(
+        debug,
+        url = %auth_url,
+        client_id = %auth_request.client_id,
+        "Requesting access token"
+    );
+
+    let body = serde_json::to_vec(&auth_request)
+        .map_err(|e| FireboltError::Authentication(format!("Failed to serialize request: {e}")))?;
+    let mut request = HttpRequest::new(Method::Post, auth_url)
+        .header("User-Agent", user_agent)
+        .header("Content-Type", "application/json")
+        .body(body)
+        .idempotent(true);
+    request.headers.extend(crate::telemetry::trace_headers());
+
+    let response = transport.send(request).await?;
+
+    crate::telemetry::record_status(response.status);
+    crate::telemetry::trace_event!(
+        debug,
+        status = response.status,
+        "Received access token response"
+    );
+
+    if response.is_success() {
+        handle_success_response(&response).map_err(FireboltError::Authentication)
     } else {
-        handle_error_response(response).await
+        handle_error_response(&response).map_err(FireboltError::Authentication)
     }
 }
 
-async fn handle_success_response(response: reqwest::Response) -> Result<(String, u64), String> {
-    let auth_response: AuthResponse = response
-        .json()
-        .await
+fn handle_success_response(response: &HttpResponse) -> Result<(String, u64), String> {
+    let auth_response: AuthResponse = serde_json::from_slice(&response.body)
         .map_err(|e| format!("Failed to parse response: {e}"))?;
 
     let current_time = SystemTime::now()
@@ -66,13 +270,8 @@ async fn handle_success_response(response: reqwest::Response) -> Result<(String,
     Ok((auth_response.access_token, expiration_timestamp))
 }
 
-async fn handle_error_response(response: reqwest::Response) -> Result<(String, u64), String> {
-    let response_text = response
-        .text()
-        .await
-        .map_err(|e| format!("Failed to read error response: {e}"))?;
-
-    Err(extract_error_message_from_json(&response_text))
+fn handle_error_response(response: &HttpResponse) -> Result<(String, u64), String> {
+    Err(extract_error_message_from_json(&response.text()))
 }
 
 fn extract_error_message_from_json(response_text: &str) -> String {
@@ -143,6 +342,7 @@ mod tests {
             client_secret: "test_secret".to_string(),
             grant_type: "client_credentials".to_string(),
             audience: "https://api.firebolt.io".to_string(),
+            scope: None,
         };
 
         let json = serde_json::to_string(&auth_request).unwrap();
@@ -150,6 +350,113 @@ mod tests {
         assert!(json.contains("\"client_secret\":\"test_secret\""));
         assert!(json.contains("\"grant_type\":\"client_credentials\""));
         assert!(json.contains("\"audience\":\"https://api.firebolt.io\""));
+        assert!(!json.contains("scope"));
+    }
+
+    #[test]
+    fn test_auth_options() {
+        let options = AuthOptions {
+            audience: "https://api.staging.firebolt.io".to_string(),
+            scopes: vec!["read".to_string(), "write".to_string()],
+            token_url: None,
+        };
+        assert!(options.validate().is_ok());
+        assert_eq!(options.scope().as_deref(), Some("read write"));
+        assert_eq!(AuthOptions::default().scope(), None);
+
+        for options in [
+            AuthOptions {
+                audience: " ".to_string(),
+                ..AuthOptions::default()
+            },
+            AuthOptions {
+                token_url: Some("id.internal/oauth/token".to_string()),
+                ..AuthOptions::default()
+            },
+            AuthOptions {
+                scopes: vec!["read write".to_string()],
+                ..AuthOptions::default()
+            },
+        ] {
+            assert!(matches!(
+                options.validate(),
+                Err(FireboltError::Configuration(_))
+            ));
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingTransport(std::sync::Mutex<Option<HttpRequest>>);
+
+    impl Transport for RecordingTransport {
+        fn send(
+            &self,
+            request: HttpRequest,
+        ) -> crate::transport::BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+            *self.0.lock().unwrap() = Some(request);
+            Box::pin(async {
+                Ok(HttpResponse::new(
+                    200,
+                    Vec::new(),
+                    br#"{"access_token": "token", "expires_in": 3600}"#.to_vec(),
+                ))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_token_sends_audience_and_scope() {
+        let transport = RecordingTransport::default();
+        let options = AuthOptions {
+            audience: "https://api.staging.firebolt.io".to_string(),
+            scopes: vec!["read".to_string(), "write".to_string()],
+            token_url: None,
+        };
+        request_token(
+            &transport,
+            "id".to_string(),
+            "secret".to_string(),
+            "api.staging.firebolt.io".to_string(),
+            &options,
+            "Rust SDK 1.0.0 Orders/2.1.0",
+        )
+        .await
+        .unwrap();
+
+        let request = transport.0.lock().unwrap().take().unwrap();
+        assert!(request.headers.contains(&(
+            "User-Agent".to_string(),
+            "Rust SDK 1.0.0 Orders/2.1.0".to_string()
+        )));
+        let body: serde_json::Value = serde_json::from_slice(&request.body.unwrap()).unwrap();
+        assert_eq!(body["audience"], "https://api.staging.firebolt.io");
+        assert_eq!(body["scope"], "read write");
+    }
+
+    #[tokio::test]
+    async fn test_request_token_uses_token_url() {
+        let transport = RecordingTransport::default();
+        let options = AuthOptions {
+            token_url: Some("https://id.firebolt.corp.internal/oauth/token".to_string()),
+            ..AuthOptions::default()
+        };
+        request_token(
+            &transport,
+            "id".to_string(),
+            "secret".to_string(),
+            "https://firebolt.corp.internal".to_string(),
+            &options,
+            &user_agent(),
+        )
+        .await
+        .unwrap();
+
+        let request = transport.0.lock().unwrap().take().unwrap();
+        assert_eq!(request.url, "https://id.firebolt.corp.internal/oauth/token");
+        assert!(options.validate().is_ok());
+        assert!(AuthOptions::default()
+            .token_url("https://firebolt.corp.internal")
+            .is_err());
     }
 
     #[test]
@@ -168,6 +475,7 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[cfg(feature = "reqwest")]
     #[tokio::test]
     async fn test_handle_success_response() {
         let json_response = r#"{"access_token": "test_token_123", "expires_in": 3600}"#;
@@ -181,14 +489,15 @@ mod tests {
             .create_async()
             .await;
 
-        let client = Client::new();
-        let response = client
-            .post(format!("{}/test", server.url()))
-            .send()
+        let response = ReqwestTransport::new()
+            .send(HttpRequest::new(
+                Method::Post,
+                format!("{}/test", server.url()),
+            ))
             .await
             .unwrap();
 
-        let result = handle_success_response(response).await;
+        let result = handle_success_response(&response);
 
         mock.assert_async().await;
 
@@ -206,6 +515,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "reqwest")]
     #[tokio::test]
     async fn test_handle_error_response_with_message() {
         let json_response = r#"{"message": "Invalid credentials"}"#;
@@ -219,14 +529,15 @@ mod tests {
             .create_async()
             .await;
 
-        let client = Client::new();
-        let response = client
-            .post(format!("{}/test", server.url()))
-            .send()
+        let response = ReqwestTransport::new()
+            .send(HttpRequest::new(
+                Method::Post,
+                format!("{}/test", server.url()),
+            ))
             .await
             .unwrap();
 
-        let result = handle_error_response(response).await;
+        let result = handle_error_response(&response);
 
         mock.assert_async().await;
 
@@ -238,6 +549,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "reqwest")]
     #[tokio::test]
     async fn test_handle_error_response_with_error_field() {
         let json_response = r#"{"error": "invalid_client"}"#;
@@ -251,14 +563,15 @@ mod tests {
             .create_async()
             .await;
 
-        let client = Client::new();
-        let response = client
-            .post(format!("{}/test", server.url()))
-            .send()
+        let response = ReqwestTransport::new()
+            .send(HttpRequest::new(
+                Method::Post,
+                format!("{}/test", server.url()),
+            ))
             .await
             .unwrap();
 
-        let result = handle_error_response(response).await;
+        let result = handle_error_response(&response);
 
         mock.assert_async().await;
 
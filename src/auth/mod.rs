@@ -1,3 +1,6 @@
 pub mod client_credentials;
+pub(crate) mod token_cache;
 
+#[cfg(feature = "reqwest")]
 pub use client_credentials::authenticate;
+pub use client_credentials::authenticate_with_transport;
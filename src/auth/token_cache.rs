@@ -0,0 +1,173 @@
+use super::client_credentials::AuthOptions;
+use crate::error::FireboltError;
+use secrecy::{ExposeSecret, SecretString};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Tokens this close to expiry are fetched again rather than handed out.
+pub(crate) const EXPIRY_MARGIN_SECS: u64 = 60;
+
+struct CachedToken {
+    client_secret: SecretString,
+    token: String,
+    expiration: u64,
+}
+
+type Slot = Arc<tokio::sync::Mutex<Option<CachedToken>>>;
+
+type Key = (String, String, AuthOptions);
+
+// Shared by every client in the process and keyed by (client_id, api_endpoint,
+// audience and scopes). Each key has its own async lock, so concurrent
+// clients wait for a single token request instead of all calling the
+// identity service.
+fn slots() -> &'static Mutex<HashMap<Key, Slot>> {
+    static SLOTS: OnceLock<Mutex<HashMap<Key, Slot>>> = OnceLock::new();
+    SLOTS.get_or_init(Default::default)
+}
+
+fn slot(client_id: &str, api_endpoint: &str, options: &AuthOptions) -> Slot {
+    let mut slots = slots().lock().unwrap_or_else(|e| e.into_inner());
+    slots
+        .entry((
+            client_id.to_string(),
+            api_endpoint.to_string(),
+            options.clone(),
+        ))
+        .or_default()
+        .clone()
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+// `rejected` is a token the server refused. Callers that hit 401 together
+// all pass the same one; the first fetches a replacement under the lock and
+// the rest find it cached when they get the lock.
+pub(crate) async fn get_or_fetch<F, Fut>(
+    client_id: &str,
+    client_secret: &str,
+    api_endpoint: &str,
+    options: &AuthOptions,
+    rejected: Option<&str>,
+    fetch: F,
+) -> Result<(String, u64), FireboltError>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<(String, u64), FireboltError>>,
+{
+    let slot = slot(client_id, api_endpoint, options);
+    let mut cached = slot.lock().await;
+
+    // A different secret for the same client ID must not receive the cached token.
+    if let Some(entry) = cached.as_ref() {
+        if entry.client_secret.expose_secret() == client_secret
+            && entry.expiration > now() + EXPIRY_MARGIN_SECS
+            && rejected != Some(entry.token.as_str())
+        {
+            return Ok((entry.token.clone(), entry.expiration));
+        }
+    }
+
+    let (token, expiration) = fetch().await?;
+    *cached = Some(CachedToken {
+        client_secret: SecretString::from(client_secret),
+        token: token.clone(),
+        expiration,
+    });
+    Ok((token, expiration))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_token_cache() {
+        let endpoint = format!("https://api.{}.firebolt.io", uuid::Uuid::new_v4());
+        let default = AuthOptions::default();
+        let fetches = AtomicUsize::new(0);
+        let fetch = |expires_in: u64| {
+            let count = fetches.fetch_add(1, Ordering::SeqCst);
+            async move { Ok((format!("token{count}"), now() + expires_in)) }
+        };
+
+        let first = get_or_fetch("id", "secret", &endpoint, &default, None, || fetch(3600))
+            .await
+            .unwrap();
+        let second = get_or_fetch("id", "secret", &endpoint, &default, None, || fetch(3600))
+            .await
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        // Tokens for another audience or scope are cached separately.
+        let scoped = AuthOptions {
+            scopes: vec!["read".to_string()],
+            ..AuthOptions::default()
+        };
+        let (token, _) = get_or_fetch("id", "secret", &endpoint, &scoped, None, || fetch(3600))
+            .await
+            .unwrap();
+        assert_eq!(token, "token1");
+        let (token, _) = get_or_fetch("id", "secret", &endpoint, &default, None, || fetch(3600))
+            .await
+            .unwrap();
+        assert_eq!(token, first.0);
+
+        let (token, _) = get_or_fetch("id", "other", &endpoint, &default, None, || fetch(3600))
+            .await
+            .unwrap();
+        assert_eq!(token, "token2");
+
+        let (token, _) = get_or_fetch("id", "other", &endpoint, &default, Some(&token), || {
+            fetch(30)
+        })
+        .await
+        .unwrap();
+        assert_eq!(token, "token3");
+
+        // Expires within the margin, so it is not reused.
+        let (token, _) = get_or_fetch("id", "other", &endpoint, &default, None, || fetch(3600))
+            .await
+            .unwrap();
+        assert_eq!(token, "token4");
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_refresh_fetches_once() {
+        let endpoint = format!("https://api.{}.firebolt.io", uuid::Uuid::new_v4());
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let refreshes = (0..8).map(|_| {
+            let endpoint = endpoint.clone();
+            let fetches = Arc::clone(&fetches);
+            tokio::spawn(async move {
+                get_or_fetch(
+                    "id",
+                    "secret",
+                    &endpoint,
+                    &AuthOptions::default(),
+                    Some("expired"),
+                    || async move {
+                        fetches.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                        Ok(("fresh".to_string(), now() + 3600))
+                    },
+                )
+                .await
+                .unwrap()
+            })
+        });
+        for refresh in refreshes.collect::<Vec<_>>() {
+            assert_eq!(refresh.await.unwrap().0, "fresh");
+        }
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,265 @@
+use crate::error::FireboltError;
+use crate::result::{ResultSet, Row};
+use crate::types::{Column, Type};
+use apache_avro::types::Value;
+use apache_avro::{Schema, Writer};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use std::collections::HashSet;
+use std::io::Write;
+
+const RECORD_NAME: &str = "Row";
+
+impl ResultSet {
+    // Writes an Avro object container file: the schema from `avro_schema`
+    // followed by one record per row.
+    pub fn write_avro<W: Write>(&self, writer: W) -> Result<(), FireboltError> {
+        write_avro_rows(writer, &self.columns, &self.rows)
+    }
+}
+
+pub fn write_avro_rows<'a, W: Write>(
+    writer: W,
+    columns: &[Column],
+    rows: impl IntoIterator<Item = &'a Row>,
+) -> Result<(), FireboltError> {
+    let schema = avro_schema(columns)?;
+    let mut writer = Writer::new(&schema, writer).map_err(avro_error)?;
+    for row in rows {
+        writer
+            .append_value(row_to_avro(columns, row)?)
+            .map_err(avro_error)?;
+    }
+    writer.flush().map_err(avro_error)?;
+    Ok(())
+}
+
+// A record named `Row` with one field per column. Column names that are not
+// valid Avro names have other characters replaced with `_`. Nullable columns
+// become a union with `null`. Arrays, structs, JSON, geography and intervals
+// are written as their JSON text, as in `to_dataframe`.
+pub fn avro_schema(columns: &[Column]) -> Result<Schema, FireboltError> {
+    let mut names = HashSet::new();
+    let fields = columns
+        .iter()
+        .map(|column| {
+            let name = field_name(&column.name);
+            if !names.insert(name.clone()) {
+                return Err(FireboltError::Serialization(format!(
+                    "Column {} maps to duplicate Avro field {name}",
+                    column.name
+                )));
+            }
+            let r#type = field_type(column);
+            Ok(if column.is_nullable {
+                serde_json::json!({"name": name, "type": ["null", r#type], "default": null})
+            } else {
+                serde_json::json!({"name": name, "type": r#type})
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let schema = serde_json::json!({"type": "record", "name": RECORD_NAME, "fields": fields});
+    Schema::parse(&schema).map_err(avro_error)
+}
+
+// The row as a record matching `avro_schema(columns)`, for encoding single
+// datums, e.g. with `apache_avro::to_avro_datum`.
+pub fn row_to_avro(columns: &[Column], row: &Row) -> Result<Value, FireboltError> {
+    let fields = columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            let value = row.get::<serde_json::Value>(index)?;
+            let value = match (value.is_null(), column.is_nullable) {
+                (true, true) => Value::Union(0, Box::new(Value::Null)),
+                (true, false) => {
+                    return Err(FireboltError::Serialization(format!(
+                        "NULL in non-nullable column {}",
+                        column.name
+                    )))
+                }
+                (false, true) => Value::Union(1, Box::new(field_value(column, index, row)?)),
+                (false, false) => field_value(column, index, row)?,
+            };
+            Ok((field_name(&column.name), value))
+        })
+        .collect::<Result<Vec<_>, FireboltError>>()?;
+    Ok(Value::Record(fields))
+}
+
+fn field_name(name: &str) -> String {
+    let mut field: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !field.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        field.insert(0, '_');
+    }
+    field
+}
+
+fn field_type(column: &Column) -> serde_json::Value {
+    match column.r#type {
+        Type::Int => "int".into(),
+        Type::Long => "long".into(),
+        Type::Float => "float".into(),
+        Type::Double => "double".into(),
+        Type::Boolean => "boolean".into(),
+        Type::Bytes => "bytes".into(),
+        Type::Decimal => serde_json::json!({
+            "type": "bytes",
+            "logicalType": "decimal",
+            "precision": column.precision.unwrap_or(38),
+            "scale": column.scale.unwrap_or(0),
+        }),
+        Type::Date => serde_json::json!({"type": "int", "logicalType": "date"}),
+        Type::Timestamp => {
+            serde_json::json!({"type": "long", "logicalType": "local-timestamp-micros"})
+        }
+        Type::TimestampTZ => {
+            serde_json::json!({"type": "long", "logicalType": "timestamp-micros"})
+        }
+        Type::Text | Type::Array | Type::Struct | Type::Geography | Type::Json | Type::Interval => {
+            "string".into()
+        }
+    }
+}
+
+fn field_value(column: &Column, index: usize, row: &Row) -> Result<Value, FireboltError> {
+    Ok(match column.r#type {
+        Type::Int => Value::Int(row.get(index)?),
+        Type::Long => Value::Long(row.get(index)?),
+        Type::Float => Value::Float(row.get(index)?),
+        Type::Double => Value::Double(row.get(index)?),
+        Type::Boolean => Value::Boolean(row.get(index)?),
+        Type::Bytes => Value::Bytes(row.get(index)?),
+        Type::Text => Value::String(row.get(index)?),
+        Type::Decimal => {
+            let mut value: rust_decimal::Decimal = row.get(index)?;
+            value.rescale(column.scale.unwrap_or(0).max(0) as u32);
+            Value::Decimal(value.mantissa().to_be_bytes().into())
+        }
+        Type::Date => {
+            let date: NaiveDate = row.get(index)?;
+            Value::Date((date - NaiveDate::default()).num_days() as i32)
+        }
+        Type::Timestamp => {
+            let timestamp: NaiveDateTime = row.get(index)?;
+            Value::LocalTimestampMicros(timestamp.and_utc().timestamp_micros())
+        }
+        Type::TimestampTZ => {
+            let timestamp: DateTime<Utc> = row.get(index)?;
+            Value::TimestampMicros(timestamp.timestamp_micros())
+        }
+        Type::Array | Type::Struct | Type::Geography | Type::Json | Type::Interval => {
+            match row.get::<serde_json::Value>(index)? {
+                serde_json::Value::String(text) => Value::String(text),
+                other => Value::String(other.to_string()),
+            }
+        }
+    })
+}
+
+fn avro_error(e: apache_avro::Error) -> FireboltError {
+    FireboltError::Serialization(format!("Avro encoding failed: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use apache_avro::Reader;
+
+    fn column(name: &str, r#type: Type, is_nullable: bool) -> Column {
+        Column {
+            name: name.to_string(),
+            r#type,
+            precision: None,
+            scale: None,
+            is_nullable,
+        }
+    }
+
+    #[test]
+    fn test_write_avro() {
+        let mut price = column("price", Type::Decimal, true);
+        price.precision = Some(10);
+        price.scale = Some(2);
+        let columns = vec![
+            column("id", Type::Long, false),
+            price,
+            column("count(*)", Type::Int, false),
+            column("day", Type::Date, true),
+            column("ts", Type::TimestampTZ, true),
+            column("tags", Type::Array, true),
+        ];
+        let rows = vec![
+            Row::new(
+                vec![
+                    serde_json::json!("30000000000"),
+                    serde_json::json!("12.5"),
+                    serde_json::json!(3),
+                    serde_json::json!("1970-01-02"),
+                    serde_json::json!("1970-01-01 00:00:01+00"),
+                    serde_json::json!([1, 2]),
+                ],
+                columns.clone(),
+            ),
+            Row::new(
+                vec![
+                    serde_json::json!(1),
+                    serde_json::Value::Null,
+                    serde_json::json!(0),
+                    serde_json::Value::Null,
+                    serde_json::Value::Null,
+                    serde_json::Value::Null,
+                ],
+                columns.clone(),
+            ),
+        ];
+        let result_set = ResultSet::new(columns, rows);
+
+        let mut output = Vec::new();
+        result_set.write_avro(&mut output).unwrap();
+        let records: Vec<Value> = Reader::new(&output[..])
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+
+        assert_eq!(
+            records[0],
+            Value::Record(vec![
+                ("id".to_string(), Value::Long(30000000000)),
+                (
+                    "price".to_string(),
+                    Value::Union(1, Box::new(Value::Decimal(1250i128.to_be_bytes().into())))
+                ),
+                ("count___".to_string(), Value::Int(3)),
+                ("day".to_string(), Value::Union(1, Box::new(Value::Date(1)))),
+                (
+                    "ts".to_string(),
+                    Value::Union(1, Box::new(Value::TimestampMicros(1_000_000)))
+                ),
+                (
+                    "tags".to_string(),
+                    Value::Union(1, Box::new(Value::String("[1,2]".to_string())))
+                ),
+            ])
+        );
+        let Value::Record(fields) = &records[1] else {
+            panic!("expected a record");
+        };
+        assert_eq!(fields[1].1, Value::Union(0, Box::new(Value::Null)));
+    }
+
+    #[test]
+    fn test_avro_schema_names() {
+        let columns = vec![
+            column("a b", Type::Text, false),
+            column("a-b", Type::Text, false),
+        ];
+        assert!(matches!(
+            avro_schema(&columns),
+            Err(FireboltError::Serialization(_))
+        ));
+        assert_eq!(field_name("1st"), "_1st");
+    }
+}
@@ -0,0 +1,59 @@
+use crate::client::FireboltClient;
+use crate::error::FireboltError;
+use crate::result::ResultSet;
+use futures_util::stream::{self, StreamExt};
+
+impl FireboltClient {
+    // Runs independent statements with at most `max_concurrency` in flight,
+    // over the client's shared connections. Each statement gets its own
+    // result, in the order given, and a failure does not stop the others. A
+    // concurrency of zero is treated as one.
+    pub async fn batch<I, S>(
+        &self,
+        statements: I,
+        max_concurrency: usize,
+    ) -> Vec<Result<ResultSet, FireboltError>>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        stream::iter(statements)
+            .map(|sql| async move { self.query(sql.as_ref()).await })
+            .buffered(max_concurrency.max(1))
+            .collect()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::FireboltError;
+    use crate::testing::{MockFirebolt, MockResponse};
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn test_batch() {
+        let mock = MockFirebolt::new();
+        let delay = Duration::from_millis(200);
+        mock.set_default_response(MockResponse::empty().with_delay(delay));
+        mock.on_query(
+            "REFRESH b",
+            MockResponse::error(400, "no such table").with_delay(delay),
+        );
+        let client = mock.connect().await.unwrap();
+
+        let started = Instant::now();
+        let results = client
+            .batch(["REFRESH a", "REFRESH b", "REFRESH c", "REFRESH d"], 2)
+            .await;
+        let elapsed = started.elapsed();
+
+        assert_eq!(results.len(), 4);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(FireboltError::Query(_))));
+        assert!(results[2].is_ok() && results[3].is_ok());
+        assert!(elapsed >= delay * 2 && elapsed < delay * 4, "{elapsed:?}");
+
+        assert!(client.batch(Vec::<String>::new(), 0).await.is_empty());
+    }
+}
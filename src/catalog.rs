@@ -0,0 +1,384 @@
+use crate::client::FireboltClient;
+use crate::error::FireboltError;
+use crate::sql::quote as quote_literal;
+use crate::types::Column;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TableInfo {
+    #[serde(rename = "table_schema")]
+    pub schema: String,
+    #[serde(rename = "table_name")]
+    pub name: String,
+    pub table_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ColumnInfo {
+    #[serde(rename = "column_name")]
+    pub name: String,
+    pub data_type: String,
+    #[serde(deserialize_with = "deserialize_yes_no")]
+    pub is_nullable: bool,
+    pub ordinal_position: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DatabaseInfo {
+    #[serde(rename = "catalog_name")]
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct IndexInfo {
+    pub table_name: String,
+    #[serde(rename = "index_name")]
+    pub name: String,
+    pub index_type: String,
+    #[serde(rename = "index_definition")]
+    pub definition: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ViewInfo {
+    #[serde(rename = "table_schema")]
+    pub schema: String,
+    #[serde(rename = "table_name")]
+    pub name: String,
+    #[serde(rename = "view_definition")]
+    pub definition: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RunningQueryInfo {
+    pub query_id: String,
+    pub query_label: Option<String>,
+    #[serde(default)]
+    pub user_name: Option<String>,
+    pub query_text: String,
+    pub status: String,
+    #[serde(rename = "duration_usec")]
+    pub duration_us: Option<i64>,
+}
+
+// Narrows `running_queries_matching` and `kill_queries`. An empty filter
+// matches every query running on the engine.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunningQueryFilter {
+    label: Option<String>,
+    user: Option<String>,
+}
+
+impl RunningQueryFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn with_user(mut self, user: impl Into<String>) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+
+    fn where_clause(&self) -> String {
+        let conditions: Vec<String> = [("query_label", &self.label), ("user_name", &self.user)]
+            .into_iter()
+            .filter_map(|(column, value)| {
+                value
+                    .as_deref()
+                    .map(|value| format!("{column} = {}", quote_literal(value)))
+            })
+            .collect();
+        if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        }
+    }
+}
+
+impl ColumnInfo {
+    pub fn to_column(&self) -> Result<Column, FireboltError> {
+        let (r#type, _, precision, scale) = crate::parser::parse_sql_type(&self.data_type)?;
+        Ok(Column {
+            name: self.name.clone(),
+            r#type,
+            precision,
+            scale,
+            is_nullable: self.is_nullable,
+        })
+    }
+}
+
+fn deserialize_yes_no<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    Ok(value.eq_ignore_ascii_case("yes"))
+}
+
+impl FireboltClient {
+    pub async fn tables(&self) -> Result<Vec<TableInfo>, FireboltError> {
+        let sql = "SELECT table_schema, table_name, table_type FROM information_schema.tables \
+                   WHERE table_schema <> 'information_schema' \
+                   ORDER BY table_schema, table_name";
+        self.query_as(sql).await
+    }
+
+    pub async fn databases(&self) -> Result<Vec<DatabaseInfo>, FireboltError> {
+        self.query_as(
+            "SELECT catalog_name, description FROM information_schema.catalogs \
+             ORDER BY catalog_name",
+        )
+        .await
+    }
+
+    pub async fn views(&self) -> Result<Vec<ViewInfo>, FireboltError> {
+        self.query_as(
+            "SELECT table_schema, table_name, view_definition FROM information_schema.views \
+             ORDER BY table_schema, table_name",
+        )
+        .await
+    }
+
+    pub async fn indexes(&self, table: Option<&str>) -> Result<Vec<IndexInfo>, FireboltError> {
+        let filter = table
+            .map(|table| format!(" WHERE table_name = {}", quote_literal(table)))
+            .unwrap_or_default();
+        self.query_as(&format!(
+            "SELECT table_name, index_name, index_type, index_definition \
+             FROM information_schema.indexes{filter} ORDER BY table_name, index_name"
+        ))
+        .await
+    }
+
+    pub async fn running_queries(&self) -> Result<Vec<RunningQueryInfo>, FireboltError> {
+        self.running_queries_matching(&RunningQueryFilter::new())
+            .await
+    }
+
+    pub async fn running_queries_matching(
+        &self,
+        filter: &RunningQueryFilter,
+    ) -> Result<Vec<RunningQueryInfo>, FireboltError> {
+        self.query_as(&format!(
+            "SELECT query_id, query_label, user_name, query_text, status, duration_usec \
+             FROM information_schema.engine_running_queries{}",
+            filter.where_clause()
+        ))
+        .await
+    }
+
+    // Cancels every running query that matches `filter` and returns their
+    // IDs. Queries that finish before they are cancelled are not an error.
+    pub async fn kill_queries(
+        &self,
+        filter: &RunningQueryFilter,
+    ) -> Result<Vec<String>, FireboltError> {
+        let running = self
+            .query(&format!(
+                "SELECT query_id FROM information_schema.engine_running_queries{}",
+                filter.where_clause()
+            ))
+            .await?;
+        let mut killed = Vec::with_capacity(running.rows.len());
+        for row in &running.rows {
+            let query_id: String = row.get("query_id")?;
+            self.cancel_query(&query_id).await?;
+            killed.push(query_id);
+        }
+        Ok(killed)
+    }
+
+    // Accepts either `table` or `schema.table`.
+    pub async fn describe_table(&self, table: &str) -> Result<Vec<ColumnInfo>, FireboltError> {
+        let filter = match table.split_once('.') {
+            Some((schema, name)) => format!(
+                "table_schema = {} AND table_name = {}",
+                quote_literal(schema),
+                quote_literal(name)
+            ),
+            None => format!("table_name = {}", quote_literal(table)),
+        };
+        let sql = format!(
+            "SELECT column_name, data_type, is_nullable, ordinal_position \
+             FROM information_schema.columns WHERE {filter} ORDER BY ordinal_position"
+        );
+
+        let columns: Vec<ColumnInfo> = self.query_as(&sql).await?;
+        if columns.is_empty() {
+            return Err(FireboltError::Configuration(format!(
+                "Table '{table}' not found"
+            )));
+        }
+        Ok(columns)
+    }
+
+    async fn query_as<T: DeserializeOwned>(&self, sql: &str) -> Result<Vec<T>, FireboltError> {
+        self.query(sql)
+            .await?
+            .rows
+            .iter()
+            .map(|row| row.deserialize())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Type;
+
+    fn column_info(data_type: &str, is_nullable: bool) -> ColumnInfo {
+        ColumnInfo {
+            name: "c".to_string(),
+            data_type: data_type.to_string(),
+            is_nullable,
+            ordinal_position: 1,
+        }
+    }
+
+    #[test]
+    fn test_column_info_to_column() {
+        let cases = [
+            ("integer", Type::Int),
+            ("bigint", Type::Long),
+            ("real", Type::Float),
+            ("double precision", Type::Double),
+            ("TEXT", Type::Text),
+            ("timestamptz", Type::TimestampTZ),
+            ("array(integer)", Type::Array),
+            ("boolean", Type::Boolean),
+        ];
+        for (data_type, expected) in cases {
+            assert_eq!(
+                column_info(data_type, false).to_column().unwrap().r#type,
+                expected,
+                "{data_type}"
+            );
+        }
+
+        let decimal = column_info("numeric(38, 9)", true).to_column().unwrap();
+        assert_eq!(decimal.r#type, Type::Decimal);
+        assert_eq!((decimal.precision, decimal.scale), (Some(38), Some(9)));
+        assert!(decimal.is_nullable);
+
+        assert!(column_info("hyperloglog", false).to_column().is_err());
+    }
+
+    #[test]
+    fn test_running_query_deserialization() {
+        let row = crate::result::Row::new(
+            vec![
+                serde_json::json!("q1"),
+                serde_json::Value::Null,
+                serde_json::json!("SELECT 1"),
+                serde_json::json!("RUNNING"),
+                serde_json::json!("1500"),
+            ],
+            [
+                ("query_id", Type::Text),
+                ("query_label", Type::Text),
+                ("query_text", Type::Text),
+                ("status", Type::Text),
+                ("duration_usec", Type::Long),
+            ]
+            .into_iter()
+            .map(|(name, r#type)| Column {
+                name: name.to_string(),
+                r#type,
+                precision: None,
+                scale: None,
+                is_nullable: true,
+            })
+            .collect(),
+        );
+
+        let info: RunningQueryInfo = row.deserialize().unwrap();
+        assert_eq!(
+            info,
+            RunningQueryInfo {
+                query_id: "q1".to_string(),
+                query_label: None,
+                user_name: None,
+                query_text: "SELECT 1".to_string(),
+                status: "RUNNING".to_string(),
+                duration_us: Some(1500),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_kill_queries() {
+        let mock = crate::testing::MockFirebolt::new();
+        mock.on_query_containing(
+            "SELECT query_id FROM",
+            crate::testing::MockResponse::rows(
+                &[("query_id", "text")],
+                vec![vec!["q1".into()], vec!["q2".into()]],
+            ),
+        );
+        let client = mock.connect().await.unwrap();
+        let before = mock.queries().len();
+
+        let filter = RunningQueryFilter::new()
+            .with_label("nightly")
+            .with_user("etl's");
+        assert_eq!(client.kill_queries(&filter).await.unwrap(), ["q1", "q2"]);
+        client
+            .running_queries_matching(&RunningQueryFilter::new().with_user("etl"))
+            .await
+            .unwrap();
+
+        let statements: Vec<String> = mock.queries()[before..]
+            .iter()
+            .map(|query| query.sql.clone())
+            .collect();
+        assert!(statements[0].ends_with(
+            "engine_running_queries WHERE query_label = 'nightly' AND user_name = 'etl''s'"
+        ));
+        assert_eq!(statements[1], "CANCEL QUERY WHERE query_id = 'q1'");
+        assert_eq!(statements[2], "CANCEL QUERY WHERE query_id = 'q2'");
+        assert!(statements[3].ends_with("engine_running_queries WHERE user_name = 'etl'"));
+    }
+
+    #[test]
+    fn test_column_info_deserialization() {
+        let row = crate::result::Row::new(
+            vec![
+                serde_json::json!("id"),
+                serde_json::json!("integer"),
+                serde_json::json!("NO"),
+                serde_json::json!(1),
+            ],
+            [
+                "column_name",
+                "data_type",
+                "is_nullable",
+                "ordinal_position",
+            ]
+            .iter()
+            .map(|name| Column {
+                name: name.to_string(),
+                r#type: if *name == "ordinal_position" {
+                    Type::Int
+                } else {
+                    Type::Text
+                },
+                precision: None,
+                scale: None,
+                is_nullable: false,
+            })
+            .collect(),
+        );
+
+        let info: ColumnInfo = row.deserialize().unwrap();
+        assert_eq!(info.name, "id");
+        assert!(!info.is_nullable);
+        assert_eq!(info.ordinal_position, 1);
+    }
+}
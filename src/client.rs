@@ -1,6 +1,32 @@
+use crate::auth::client_credentials::AuthOptions;
+use crate::config::ClientConfig;
+use crate::dns::Resolver;
 use crate::error::FireboltError;
+use crate::export::OutputFormat;
+use crate::hooks::{HookTransport, Hooks, RequestInfo, ResponseInfo};
 use crate::result::ResultSet;
+use crate::result_cache::ResultCache;
+use crate::retry::{RetryCause, RetryEvent, RetryPolicy, RetryStrategy};
+use crate::session_state::SessionState;
+use crate::shutdown::Shutdown;
+use crate::spill::{SpillWriter, SpilledResultSet};
+use crate::statement::{ParameterBinding, StatementCache};
+use crate::telemetry::{trace_event, REDACTED};
+use crate::transport::{
+    AliasTransport, CountingWriter, HttpOptions, HttpRequest, HttpResponse, Method, ProxyOptions,
+    RetryTransport, TimeoutTransport, TransferTiming, Transport,
+};
+use crate::types::{BytesEncoding, Column, ConversionMode};
+use futures_util::future::{self, Either};
+use secrecy::{ExposeSecret, SecretString};
 use std::collections::HashMap;
+use std::future::Future;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio_util::sync::CancellationToken;
 use url::Url;
 
 const HEADER_UPDATE_ENDPOINT: &str = "Firebolt-Update-Endpoint";
@@ -8,523 +34,3635 @@ const HEADER_UPDATE_PARAMETERS: &str = "Firebolt-Update-Parameters";
 const HEADER_RESET_SESSION: &str = "Firebolt-Reset-Session";
 const HEADER_REMOVE_PARAMETERS: &str = "Firebolt-Remove-Parameters";
 
-#[derive(Debug)]
+const DEFAULT_ENGINE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_PING_TIMEOUT: Duration = Duration::from_secs(10);
+// Firebolt's per-statement execution limit, in milliseconds.
+const STATEMENT_TIMEOUT_SETTING: &str = "statement_timeout";
+
+#[derive(Debug, Clone, Copy)]
+struct EngineWait {
+    timeout: Duration,
+    poll_interval: Duration,
+}
+
+// Settings for a single query, passed to `query_with_options`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryOptions {
+    timeout: Option<Duration>,
+}
+
+impl QueryOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Bounds how long the client waits for this query, replacing the client's
+    // `with_timeout`, and sends the same limit as the `statement_timeout`
+    // setting so the engine cancels the query instead of running on after the
+    // client gives up.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PingResult {
+    pub latency: Duration,
+    pub engine_url: String,
+}
+
+// Raw details of the HTTP response that produced a result, for debugging
+// and tooling. `elapsed` covers the final request, including any retries
+// made by the transport, but not a token refresh or engine wait before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseMetadata {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub query_id: Option<String>,
+    pub elapsed: Duration,
+    pub timing: QueryTiming,
+}
+
+// Where the client spent the time of a query. `transfer` breaks down the
+// last HTTP attempt and is `None` with a custom transport; `parse` is the
+// time spent decoding the response body into a `ResultSet`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryTiming {
+    pub transfer: Option<TransferTiming>,
+    pub parse: Duration,
+}
+
+impl ResponseMetadata {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerInfo {
+    pub version: String,
+    pub protocol_version: String,
+}
+
+impl ServerInfo {
+    pub fn version_tuple(&self) -> Option<(u32, u32, u32)> {
+        let mut parts = self.version.trim().split(['.', '-', ' ']);
+        let mut next = || parts.next().and_then(|part| part.parse::<u32>().ok());
+        Some((next()?, next().unwrap_or(0), next().unwrap_or(0)))
+    }
+
+    pub fn version_at_least(&self, major: u32, minor: u32) -> bool {
+        self.version_tuple()
+            .is_some_and(|(server_major, server_minor, _)| {
+                (server_major, server_minor) >= (major, minor)
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct AccountInfo {
+    pub id: String,
+    pub region: String,
+}
+
+// Clones share the session, so the same connection can be handed to several
+// tasks: a token refresh, engine switch or parameter change made through one
+// clone is seen by all of them.
+#[derive(Clone)]
 pub struct FireboltClient {
     _client_id: String,
-    _client_secret: String,
-    _token: String,
-    _parameters: HashMap<String, String>,
-    _engine_url: String,
+    _client_secret: SecretString,
     _api_endpoint: String,
+    _auth: AuthOptions,
+    _transport: Arc<dyn Transport>,
+    _timeout: Option<Duration>,
+    _engine_wait: Option<EngineWait>,
+    _engine_url_ttl: Duration,
+    _account_name: String,
+    _user_agent: String,
+    _conversion_mode: ConversionMode,
+    _max_rows: Option<usize>,
+    _bytes_encoding: BytesEncoding,
+    _statement_cache: Option<Arc<StatementCache>>,
+    _result_cache: Option<Arc<ResultCache>>,
+    _parameter_binding: ParameterBinding,
+    _default_settings: HashMap<String, String>,
+    _hooks: Hooks,
+    _shutdown: Arc<Shutdown>,
+    // Set until the client has connected; see `connected`.
+    _pending: Option<Arc<Pending>>,
+    _session: Arc<RwLock<Session>>,
+}
+
+struct Session {
+    token: SecretString,
+    token_expires_at: Option<u64>,
+    parameters: HashMap<String, String>,
+    engine_url: String,
+    // Part of the session rather than the client because it is updated when
+    // the account's engine moves.
+    system_engine_url: String,
+    server_info: Option<ServerInfo>,
+    account_info: Option<AccountInfo>,
+}
+
+impl Session {
+    // Sessions start on the account's system engine.
+    fn new(token: SecretString, engine_url: String) -> Arc<RwLock<Session>> {
+        Arc::new(RwLock::new(Session {
+            token,
+            token_expires_at: None,
+            parameters: HashMap::new(),
+            system_engine_url: engine_url.clone(),
+            engine_url,
+            server_info: None,
+            account_info: None,
+        }))
+    }
+}
+
+// The setup `build` does before returning and `build_lazy` leaves for the
+// first request. Clones share it, so only one of them connects.
+struct Pending {
+    ready: tokio::sync::OnceCell<()>,
+    database_name: Option<String>,
+    engine_name: Option<String>,
+    session_settings: HashMap<String, String>,
+}
+
+impl Pending {
+    fn new(
+        database_name: Option<String>,
+        engine_name: Option<String>,
+        session_settings: HashMap<String, String>,
+    ) -> Arc<Pending> {
+        Arc::new(Pending {
+            ready: tokio::sync::OnceCell::new(),
+            database_name,
+            engine_name,
+            session_settings,
+        })
+    }
+}
+
+impl std::fmt::Debug for FireboltClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let session = self.session();
+        f.debug_struct("FireboltClient")
+            .field("_client_id", &self._client_id)
+            .field("_client_secret", &REDACTED)
+            .field("_token", &REDACTED)
+            .field("_parameters", &session.parameters)
+            .field("_engine_url", &session.engine_url)
+            .field("_api_endpoint", &self._api_endpoint)
+            .field("_auth", &self._auth)
+            .field("_timeout", &self._timeout)
+            .field("_engine_wait", &self._engine_wait)
+            .field("_server_info", &session.server_info)
+            .field("_account_name", &self._account_name)
+            .field("_account_info", &session.account_info)
+            .field("_system_engine_url", &session.system_engine_url)
+            .field("_user_agent", &self._user_agent)
+            .field("_conversion_mode", &self._conversion_mode)
+            .field("_max_rows", &self._max_rows)
+            .field("_bytes_encoding", &self._bytes_encoding)
+            .field("_statement_cache", &self._statement_cache)
+            .field("_result_cache", &self._result_cache)
+            .field("_closed", &self.is_closed())
+            .field("_connected", &self.is_connected())
+            .field("_parameter_binding", &self._parameter_binding)
+            .finish()
+    }
 }
 
 impl FireboltClient {
-    pub async fn query(&mut self, sql: &str) -> Result<ResultSet, FireboltError> {
-        let engine_url = self.engine_url();
-        let url = ensure_trailing_slash(engine_url);
+    // Read-only statements are resent after a network error, within the
+    // retry policy. Use `query_non_idempotent` for a read that must not run
+    // twice, e.g. one calling a function with side effects.
+    pub async fn query(&self, sql: &str) -> Result<ResultSet, FireboltError> {
+        self.run_query(sql, is_read_only(sql)).await
+    }
 
-        let mut params = self.parameters().clone();
-        params.insert("output_format".to_string(), "JSON_Compact".to_string());
+    pub async fn query_non_idempotent(&self, sql: &str) -> Result<ResultSet, FireboltError> {
+        self.run_query(sql, false).await
+    }
 
-        self.execute_query_request(&url, sql, &params, true).await
+    pub async fn query_with_metadata(
+        &self,
+        sql: &str,
+    ) -> Result<(ResultSet, ResponseMetadata), FireboltError> {
+        self.connected().await?;
+        self.run_query_with_params(
+            sql,
+            self.request_params(),
+            is_read_only(sql),
+            self._max_rows,
+        )
+        .await
     }
 
-    async fn execute_query_request(
-        &mut self,
-        url: &str,
+    pub async fn query_with_max_rows(
+        &self,
         sql: &str,
-        params: &HashMap<String, String>,
-        should_retry: bool,
+        max_rows: usize,
     ) -> Result<ResultSet, FireboltError> {
-        let client = reqwest::Client::new();
-        let token = &self._token;
+        self.connected().await?;
+        self.run_query_with_params(
+            sql,
+            self.request_params(),
+            is_read_only(sql),
+            Some(max_rows),
+        )
+        .await
+        .map(|(result, _)| result)
+    }
 
-        let response = client
-            .post(url)
-            .query(params)
-            .header("Authorization", format!("Bearer {token}"))
-            .header("User-Agent", crate::version::user_agent())
-            .header(
-                "Firebolt-Protocol-Version",
-                crate::version::PROTOCOL_VERSION,
-            )
-            .body(sql.to_string())
-            .send()
+    // Runs one statement with settings that override the client's, such as a
+    // timeout that also stops the query on the server.
+    pub async fn query_with_options(
+        &self,
+        sql: &str,
+        options: &QueryOptions,
+    ) -> Result<ResultSet, FireboltError> {
+        self.connected().await?;
+        let mut params = self.request_params();
+        let mut timeout = self._timeout;
+        if let Some(limit) = options.timeout {
+            // Zero would disable the server's limit, so round up to 1 ms.
+            let millis = limit.as_millis().max(1);
+            params.insert(STATEMENT_TIMEOUT_SETTING.to_string(), millis.to_string());
+            timeout = Some(limit);
+        }
+        self.run_query_with_deadline(sql, params, is_read_only(sql), self._max_rows, timeout)
             .await
-            .map_err(|e| FireboltError::Network(format!("Request failed: {e}")))?;
+            .map(|(result, _)| result)
+    }
 
-        let status = response.status();
+    // Streams the result to a file in the system temp directory instead of
+    // memory and reads it back row by row, for results larger than RAM.
+    pub async fn query_spilled(&self, sql: &str) -> Result<SpilledResultSet, FireboltError> {
+        self.connected().await?;
+        let url = ensure_trailing_slash(&self.engine_url());
+        let mut params = self.request_params();
+        params.insert(
+            "output_format".to_string(),
+            crate::spill::OUTPUT_FORMAT.to_string(),
+        );
+        let (file, path) = SpilledResultSet::temp_file()?.into_parts();
+        let spill_error = |e: std::io::Error| {
+            FireboltError::Serialization(format!("Failed to write {}: {e}", path.display()))
+        };
 
-        if status == 401 && should_retry {
-            let (new_token, _expiration) = crate::auth::authenticate(
-                self.client_id().to_string(),
-                self.client_secret().to_string(),
-                self.api_endpoint().to_string(),
+        let write = async {
+            let mut writer = SpillWriter::new(file);
+            self.execute_waiting_for_engine(
+                &url,
+                sql,
+                &params,
+                is_read_only(sql),
+                Some(&mut writer),
             )
-            .await
-            .map_err(|e| FireboltError::Authentication(format!("Token refresh failed: {e}")))?;
+            .await?;
+            writer.finish().await.map_err(spill_error)
+        };
+        let operation = crate::telemetry::Operation::Query { sql };
+        crate::telemetry::instrument(operation, with_deadline(self._timeout, "Query", write))
+            .await?;
+        SpilledResultSet::open(path, self._conversion_mode)
+    }
 
-            self.set_token(new_token);
-            Box::pin(self.execute_query_request(url, sql, params, false)).await
-        } else if status == 401 {
-            Err(FireboltError::Authentication(
-                "Authentication failed after token refresh".to_string(),
-            ))
-        } else if status.is_server_error() {
-            let body = response.text().await.map_err(|e| {
-                FireboltError::Network(format!("Failed to read error response: {e}"))
-            })?;
-            Err(crate::parser::parse_server_error(body))
-        } else if status.is_success() {
-            self.process_response_headers(&response)?;
-            let body = response
-                .text()
-                .await
-                .map_err(|e| FireboltError::Network(format!("Failed to read response: {e}")))?;
-            crate::parser::parse_response(body)
-        } else {
-            let body = response.text().await.map_err(|e| {
-                FireboltError::Network(format!("Failed to read error response: {e}"))
+    // Pipes the raw response body, in `format`, into `writer` without parsing
+    // it, and returns the number of bytes written. Each chunk is written before
+    // the next one is read, so a slow writer slows the download rather than
+    // piling it up in memory.
+    pub async fn query_to_writer<W>(
+        &self,
+        sql: &str,
+        writer: &mut W,
+        format: OutputFormat,
+    ) -> Result<u64, FireboltError>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+    {
+        self.connected().await?;
+        let url = ensure_trailing_slash(&self.engine_url());
+        let mut params = self.request_params();
+        params.insert("output_format".to_string(), format.as_str().to_string());
+
+        let mut writer = writer;
+        let pipe = async {
+            let mut sink = CountingWriter::new(&mut writer);
+            self.execute_waiting_for_engine(&url, sql, &params, is_read_only(sql), Some(&mut sink))
+                .await?;
+            sink.flush().await.map_err(|e| {
+                FireboltError::Serialization(format!("Failed to write response body: {e}"))
             })?;
-            Err(crate::parser::parse_server_error(body))
+            Ok(sink.written)
+        };
+        let operation = crate::telemetry::Operation::Query { sql };
+        crate::telemetry::instrument(operation, with_deadline(self._timeout, "Query", pipe)).await
+    }
+
+    async fn run_query(&self, sql: &str, idempotent: bool) -> Result<ResultSet, FireboltError> {
+        self.connected().await?;
+        let params = self.request_params();
+        // Only idempotent statements are cached; anything else must reach the engine.
+        let cached = match &self._result_cache {
+            Some(cache) if idempotent => {
+                Some((cache, ResultCache::key(&self.engine_url(), sql, &params)))
+            }
+            _ => None,
+        };
+        if let Some(result) = cached.as_ref().and_then(|(cache, key)| cache.get(key)) {
+            return Ok(result);
         }
+        let (result, _) = self
+            .run_query_with_params(sql, params, idempotent, self._max_rows)
+            .await?;
+        if let Some((cache, key)) = cached {
+            cache.insert(key, &result);
+        }
+        Ok(result)
     }
 
-    pub fn client_id(&self) -> &str {
-        &self._client_id
+    pub(crate) async fn run_query_with_params(
+        &self,
+        sql: &str,
+        params: HashMap<String, String>,
+        idempotent: bool,
+        max_rows: Option<usize>,
+    ) -> Result<(ResultSet, ResponseMetadata), FireboltError> {
+        self.run_query_with_deadline(sql, params, idempotent, max_rows, self._timeout)
+            .await
     }
 
-    pub fn client_secret(&self) -> &str {
-        &self._client_secret
+    async fn run_query_with_deadline(
+        &self,
+        sql: &str,
+        params: HashMap<String, String>,
+        idempotent: bool,
+        max_rows: Option<usize>,
+        timeout: Option<Duration>,
+    ) -> Result<(ResultSet, ResponseMetadata), FireboltError> {
+        let url = ensure_trailing_slash(&self.engine_url());
+        let operation = crate::telemetry::Operation::Query { sql };
+        crate::telemetry::instrument(
+            operation,
+            with_deadline(timeout, "Query", async {
+                let (response, elapsed) = self
+                    .execute_waiting_for_engine(&url, sql, &params, idempotent, None)
+                    .await?;
+                self.read_query_response(response, elapsed, max_rows)
+            }),
+        )
+        .await
     }
 
-    pub fn api_endpoint(&self) -> &str {
-        &self._api_endpoint
+    // Stops waiting when `cancel` fires or the returned future is dropped, and
+    // then cancels the statement on the server. The statement is found by a
+    // `query_label` generated for this call, which replaces any session label
+    // so queries that share it are left alone.
+    pub async fn query_cancellable(
+        &self,
+        sql: &str,
+        cancel: &CancellationToken,
+    ) -> Result<ResultSet, FireboltError> {
+        self.connected().await?;
+        let mut params = self.request_params();
+        let label = uuid::Uuid::new_v4().to_string();
+        params.insert("query_label".to_string(), label.clone());
+        let mut guard = CancelOnDrop {
+            client: Some(self.clone()),
+            label,
+        };
+
+        let query =
+            Box::pin(self.run_query_with_params(sql, params, is_read_only(sql), self._max_rows));
+        let result = match future::select(query, Box::pin(cancel.cancelled())).await {
+            Either::Left((result, _)) => result.map(|(result, _)| result),
+            Either::Right((_, query)) => {
+                drop(query);
+                trace_event!(info, label = %guard.label, "Query cancelled by caller");
+                self.cancel_on_server(&guard.label).await;
+                Err(FireboltError::Cancelled(format!(
+                    "Query was cancelled ({})",
+                    query_context(sql, None, None)
+                )))
+            }
+        };
+        guard.client = None;
+        result
     }
 
-    pub fn engine_url(&self) -> &str {
-        &self._engine_url
+    pub async fn cancel_query(&self, query_id: &str) -> Result<(), FireboltError> {
+        self.query(&format!(
+            "CANCEL QUERY WHERE query_id = {}",
+            crate::sql::quote(query_id)
+        ))
+        .await
+        .map(|_| ())
     }
 
-    pub fn parameters(&self) -> &HashMap<String, String> {
-        &self._parameters
+    // Best effort: the query may already have finished or not yet be listed.
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    async fn cancel_on_server(&self, label: &str) {
+        if let Err(e) = self.cancel_labelled(label).await {
+            trace_event!(warn, error = %e, "Failed to cancel query on the server");
+        }
     }
 
-    pub fn set_token(&mut self, token: String) {
-        self._token = token;
+    async fn cancel_labelled(&self, label: &str) -> Result<(), FireboltError> {
+        let filter = crate::catalog::RunningQueryFilter::new().with_label(label);
+        self.kill_queries(&filter).await.map(|_| ())
     }
 
-    pub fn builder() -> FireboltClientFactory {
-        FireboltClientFactory::new()
+    // Returns the request `query` would send for `sql`, without sending it:
+    // the URL with its query parameters, the headers, and the body. The access
+    // token is redacted.
+    pub fn dry_run(&self, sql: &str) -> Result<HttpRequest, FireboltError> {
+        let url = ensure_trailing_slash(&self.engine_url());
+        let mut request =
+            self.build_query_request(&url, sql, &self.request_params(), is_read_only(sql))?;
+        for (name, value) in &mut request.headers {
+            if name.eq_ignore_ascii_case("Authorization") {
+                *value = format!("Bearer {REDACTED}");
+            }
+        }
+        Ok(request)
     }
 
-    fn process_response_headers(
-        &mut self,
-        response: &reqwest::Response,
-    ) -> Result<(), FireboltError> {
-        if let Some(endpoint_header) = response.headers().get(HEADER_UPDATE_ENDPOINT) {
-            let endpoint_str = endpoint_header.to_str().map_err(|e| {
-                FireboltError::HeaderParsing(format!("Invalid endpoint header: {e}"))
-            })?;
+    // Wraps the statement in a `LIMIT 0` subquery so the engine plans it and
+    // returns result metadata without scanning or returning any rows.
+    pub async fn describe_query(&self, sql: &str) -> Result<Vec<Column>, FireboltError> {
+        let statement = sql
+            .trim_start()
+            .trim_end_matches(|c: char| c == ';' || c.is_whitespace());
+        if statement.is_empty() {
+            return Err(FireboltError::Query(
+                "Cannot describe an empty query".to_string(),
+            ));
+        }
+        let wrapped = format!("SELECT * FROM ({statement}\n) AS describe_query LIMIT 0");
+        Ok(self.query(&wrapped).await?.columns)
+    }
 
-            let url = Url::parse(FireboltClientFactory::fix_schema(endpoint_str).as_str())
-                .map_err(|e| FireboltError::HeaderParsing(format!("Invalid endpoint URL: {e}")))?;
+    pub async fn ping(&self) -> Result<PingResult, FireboltError> {
+        self.ping_with_timeout(DEFAULT_PING_TIMEOUT).await
+    }
 
-            let base_url = format!("{}://{}", url.scheme(), url.host_str().unwrap_or(""));
-            let path = url.path();
-            self._engine_url = if path == "/" || path.is_empty() {
-                base_url
-            } else {
-                format!("{base_url}{path}")
-            };
+    pub async fn ping_with_timeout(&self, timeout: Duration) -> Result<PingResult, FireboltError> {
+        self.connected().await?;
+        let url = ensure_trailing_slash(&self.engine_url());
+        let params = self.request_params();
 
-            for (key, value) in url.query_pairs() {
-                self._parameters.insert(key.to_string(), value.to_string());
-            }
-        }
+        let started = std::time::Instant::now();
+        with_deadline(
+            Some(timeout),
+            "Ping",
+            self.execute_query_request(&url, "SELECT 1", &params, true, true),
+        )
+        .await?;
 
-        if let Some(params_header) = response.headers().get(HEADER_UPDATE_PARAMETERS) {
-            let params_str = params_header.to_str().map_err(|e| {
-                FireboltError::HeaderParsing(format!("Invalid parameters header: {e}"))
-            })?;
+        Ok(PingResult {
+            latency: started.elapsed(),
+            engine_url: self.engine_url(),
+        })
+    }
 
-            for param_pair in params_str.split(',') {
-                let param_pair = param_pair.trim();
-                if param_pair.is_empty() {
-                    continue;
-                }
+    pub async fn server_info(&self) -> Result<ServerInfo, FireboltError> {
+        let cached = self.session().server_info.clone();
+        if let Some(server_info) = cached {
+            return Ok(server_info);
+        }
 
-                let parts: Vec<&str> = param_pair.splitn(2, '=').collect();
-                if parts.len() != 2 {
-                    return Err(FireboltError::HeaderParsing(format!(
-                        "Invalid parameter format: {param_pair}"
-                    )));
-                }
+        let result = self.query("SELECT VERSION()").await?;
+        let version = result
+            .rows
+            .first()
+            .and_then(|row| row.data().first())
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| {
+                FireboltError::Query("VERSION() returned no version string".to_string())
+            })?
+            .to_string();
+        let server_info = ServerInfo {
+            version,
+            protocol_version: crate::version::PROTOCOL_VERSION.to_string(),
+        };
+        self.session_mut().server_info = Some(server_info.clone());
+        Ok(server_info)
+    }
 
-                let key = parts[0].trim();
-                let value = parts[1].trim();
+    // Session parameters take precedence over the client's default settings.
+    pub(crate) fn request_params(&self) -> HashMap<String, String> {
+        let mut params = self._default_settings.clone();
+        params.extend(self.parameters());
+        params.insert("output_format".to_string(), "JSON_Compact".to_string());
+        params
+    }
 
-                if key.is_empty() {
-                    return Err(FireboltError::HeaderParsing(
-                        "Parameter key cannot be empty".to_string(),
+    async fn execute_waiting_for_engine(
+        &self,
+        url: &str,
+        sql: &str,
+        params: &HashMap<String, String>,
+        idempotent: bool,
+        mut sink: Option<&mut (dyn AsyncWrite + Unpin + Send + '_)>,
+    ) -> Result<(HttpResponse, Duration), FireboltError> {
+        let Some(wait) = self._engine_wait else {
+            return self
+                .send_query_request(url, sql, params, idempotent, true, sink)
+                .await;
+        };
+
+        let deadline = std::time::Instant::now() + wait.timeout;
+        let mut attempt = 0;
+        let result = loop {
+            match self
+                .send_query_request(url, sql, params, idempotent, true, sink.as_deref_mut())
+                .await
+            {
+                Err(e) if is_engine_not_ready(&e) => {
+                    if std::time::Instant::now() + wait.poll_interval > deadline {
+                        break Err(FireboltError::Timeout(format!(
+                            "Engine did not become ready within {:?}: {e}",
+                            wait.timeout
+                        )));
+                    }
+                    attempt += 1;
+                    self._hooks.retry(RetryEvent::new(
+                        attempt,
+                        RetryCause::EngineStarting,
+                        wait.poll_interval,
                     ));
+                    crate::runtime::sleep(wait.poll_interval).await;
                 }
-
-                self._parameters.insert(key.to_string(), value.to_string());
+                result => break result,
             }
+        };
+        crate::retry::emit_outcome(attempt, result.is_ok());
+        result
+    }
+
+    async fn execute_query_request(
+        &self,
+        url: &str,
+        sql: &str,
+        params: &HashMap<String, String>,
+        idempotent: bool,
+        should_retry: bool,
+    ) -> Result<(ResultSet, ResponseMetadata), FireboltError> {
+        let (response, elapsed) = self
+            .send_query_request(url, sql, params, idempotent, should_retry, None)
+            .await?;
+        self.read_query_response(response, elapsed, self._max_rows)
+    }
+
+    // Parameters are sorted so the same query always produces the same URL.
+    fn build_query_request(
+        &self,
+        url: &str,
+        sql: &str,
+        params: &HashMap<String, String>,
+        idempotent: bool,
+    ) -> Result<HttpRequest, FireboltError> {
+        let token = self.session().token.expose_secret().to_string();
+        let mut request_url = Url::parse(url).map_err(|e| {
+            FireboltError::Network(format!("Request failed: invalid URL {url}: {e}"))
+        })?;
+        if !params.is_empty() {
+            let mut params: Vec<_> = params.iter().collect();
+            params.sort();
+            request_url.query_pairs_mut().extend_pairs(params);
         }
 
-        if response.headers().contains_key(HEADER_RESET_SESSION) {
-            let database = self._parameters.get("database").cloned();
-            let engine = self._parameters.get("engine").cloned();
+        let mut request = HttpRequest::new(Method::Post, request_url.to_string())
+            .header("Authorization", format!("Bearer {token}"))
+            .header("User-Agent", self._user_agent.as_str())
+            .header(
+                "Firebolt-Protocol-Version",
+                crate::version::PROTOCOL_VERSION,
+            )
+            .body(sql)
+            .idempotent(idempotent);
+        request.headers.extend(crate::telemetry::trace_headers());
+        Ok(request)
+    }
 
-            self._parameters.clear();
+    async fn send_query_request(
+        &self,
+        url: &str,
+        sql: &str,
+        params: &HashMap<String, String>,
+        idempotent: bool,
+        should_retry: bool,
+        mut sink: Option<&mut (dyn AsyncWrite + Unpin + Send + '_)>,
+    ) -> Result<(HttpResponse, Duration), FireboltError> {
+        let request = self.build_query_request(url, sql, params, idempotent)?;
+        trace_event!(
+            debug,
+            url,
+            params = ?crate::telemetry::redact_params(params),
+            "Sending query request"
+        );
 
-            if let Some(db) = database {
-                self._parameters.insert("database".to_string(), db);
+        let started = std::time::Instant::now();
+        let send = async {
+            match sink.as_deref_mut() {
+                Some(sink) => self._transport.send_to(request, sink).await,
+                None => self._transport.send(request).await,
             }
-            if let Some(eng) = engine {
-                self._parameters.insert("engine".to_string(), eng);
+        };
+        let sent = self._shutdown.guard(params, send).await;
+        let unreachable = match &sent {
+            Err(FireboltError::Network(_)) => Some(idempotent && sink.is_none()),
+            Ok(response) if response.status == 404 => Some(true),
+            _ => None,
+        };
+        if let Some(resendable) = unreachable {
+            self.invalidate_engine_url(url);
+            if resendable && should_retry && Box::pin(self.reresolve_engine_url(url)).await {
+                self._hooks
+                    .retry(RetryEvent::new(1, RetryCause::EngineMoved, Duration::ZERO));
+                let url = ensure_trailing_slash(&self.engine_url());
+                let result =
+                    Box::pin(self.send_query_request(&url, sql, params, idempotent, false, sink))
+                        .await;
+                crate::retry::emit_outcome(1, result.is_ok());
+                return result;
             }
         }
+        let response = sent
+            .map_err(network_context("Request failed"))
+            .map_err(|e| e.with_context(&query_context(sql, None, None)))?;
+        let elapsed = started.elapsed();
 
-        if let Some(remove_header) = response.headers().get(HEADER_REMOVE_PARAMETERS) {
-            let remove_str = remove_header.to_str().map_err(|e| {
-                FireboltError::HeaderParsing(format!("Invalid remove parameters header: {e}"))
-            })?;
+        let status = response.status;
+        crate::telemetry::record_status(status);
+        trace_event!(debug, status, "Received query response");
 
-            for param_name in remove_str.split(',') {
-                let param_name = param_name.trim();
-                if !param_name.is_empty() {
-                    self._parameters.remove(param_name);
+        if status == 401 && should_retry {
+            self._hooks
+                .retry(RetryEvent::new(1, RetryCause::Unauthorized, Duration::ZERO));
+            self.refresh_token().await?;
+            let result =
+                Box::pin(self.send_query_request(url, sql, params, idempotent, false, sink)).await;
+            crate::retry::emit_outcome(1, result.is_ok());
+            result
+        } else if status == 401 {
+            trace_event!(warn, "Access token rejected after refresh, not retrying");
+            Err(FireboltError::Authentication(
+                "Authentication failed after token refresh".to_string(),
+            )
+            .with_context(&query_context(sql, Some(status), None)))
+        } else if response.is_success() {
+            self.process_response_headers(&response)?;
+            Ok((response, elapsed))
+        } else {
+            let body = response.text();
+            let query_id = crate::parser::parse_json(&body)
+                .ok()
+                .and_then(|json| crate::parser::parse_query_id(&json).map(str::to_string));
+            let error = match crate::parser::parse_error_response(status, body) {
+                // 503s from an engine that is still starting stay `Unavailable`,
+                // so they are retried.
+                FireboltError::Query(message) if reports_engine_not_ready(&message) => {
+                    FireboltError::EngineNotRunning(message)
                 }
-            }
+                other => other,
+            };
+            Err(error.with_context(&query_context(sql, Some(status), query_id.as_deref())))
         }
-
-        Ok(())
     }
-}
 
-fn ensure_trailing_slash(url: &str) -> String {
-    if url.ends_with('/') {
-        url.to_string()
-    } else {
-        format!("{url}/")
+    fn read_query_response(
+        &self,
+        response: HttpResponse,
+        elapsed: Duration,
+        max_rows: Option<usize>,
+    ) -> Result<(ResultSet, ResponseMetadata), FireboltError> {
+        let started = std::time::Instant::now();
+        let json = crate::parser::parse_json(&response.text())?;
+        let query_id = crate::parser::parse_query_id(&json);
+        crate::telemetry::record_query_id(query_id);
+        let result = crate::parser::parse_limited_response_json(&json, max_rows)?
+            .with_conversion_mode(self._conversion_mode)
+            .with_bytes_encoding(self._bytes_encoding)?;
+        let timing = QueryTiming {
+            transfer: response.timing,
+            parse: started.elapsed(),
+        };
+        if result.is_truncated() {
+            trace_event!(warn, max_rows = ?max_rows, "Result truncated to the row limit");
+        }
+        #[cfg(feature = "tracing")]
+        for warning in result.warnings() {
+            trace_event!(warn, code = ?warning.code, "Server warning: {}", warning.message);
+        }
+        let metadata = ResponseMetadata {
+            status: response.status,
+            query_id: query_id.map(str::to_string),
+            headers: response.headers,
+            elapsed,
+            timing,
+        };
+        Ok((result, metadata))
     }
-}
 
-pub struct FireboltClientFactory {
-    client_id: Option<String>,
-    client_secret: Option<String>,
-    database_name: Option<String>,
-    engine_name: Option<String>,
-    account_name: Option<String>,
-    _api_endpoint: String,
-}
-
-impl FireboltClientFactory {
-    fn new() -> Self {
-        Self {
-            client_id: None,
-            client_secret: None,
-            database_name: None,
-            engine_name: None,
-            account_name: None,
-            _api_endpoint: "https://api.firebolt.io".to_string(),
-        }
+    async fn refresh_token(&self) -> Result<(), FireboltError> {
+        let rejected = self.session().token.expose_secret().to_string();
+        let (new_token, expiration) = crate::auth::client_credentials::refresh_token(
+            self._transport.as_ref(),
+            self.client_id().to_string(),
+            self.client_secret().expose_secret().to_string(),
+            self.api_endpoint().to_string(),
+            &self._auth,
+            &self._user_agent,
+            &rejected,
+        )
+        .await
+        .map_err(|e| match e {
+            FireboltError::Timeout(_) => e,
+            other => FireboltError::Authentication(format!(
+                "Token refresh failed: {}",
+                crate::auth::client_credentials::error_message(other)
+            )),
+        })?;
+
+        let mut session = self.session_mut();
+        session.token = SecretString::from(new_token);
+        session.token_expires_at = Some(expiration);
+        Ok(())
     }
 
-    fn fix_schema(url: &str) -> String {
-        if url.starts_with("https://") || url.starts_with("http://") {
-            url.to_string()
-        } else {
-            format!("https://{url}")
+    pub async fn resolve_account(&self) -> Result<AccountInfo, FireboltError> {
+        let cached = self.session().account_info.clone();
+        if let Some(account_info) = cached {
+            return Ok(account_info);
         }
-    }
 
-    fn get_api_endpoint() -> String {
-        let api_endpoint = std::env::var("FIREBOLT_API_ENDPOINT")
-            .unwrap_or_else(|_| "api.app.firebolt.io".to_string());
+        let url = format!(
+            "{}/web/v3/account/{}/resolve",
+            self._api_endpoint.trim_end_matches('/'),
+            self._account_name
+        );
+        trace_event!(debug, url = %url, "Resolving account");
+
+        let mut response = self.send_api_get(&url).await?;
+        if response.status == 401 {
+            self._hooks
+                .retry(RetryEvent::new(1, RetryCause::Unauthorized, Duration::ZERO));
+            self.refresh_token().await?;
+            response = self.send_api_get(&url).await?;
+            crate::retry::emit_outcome(1, response.is_success());
+        }
 
-        Self::fix_schema(&api_endpoint)
+        crate::telemetry::record_status(response.status);
+        let account_info = match response.status {
+            200 => serde_json::from_slice::<AccountInfo>(&response.body).map_err(|e| {
+                FireboltError::Query(format!("Failed to parse account response: {e}"))
+            })?,
+            401 => {
+                return Err(FireboltError::Authentication(
+                    "Authentication failed after token refresh".to_string(),
+                ))
+            }
+            404 => {
+                return Err(FireboltError::AccountNotFound(format!(
+                    "Account '{}' not found",
+                    self._account_name
+                )))
+            }
+            status @ (429 | 502 | 503 | 504) => {
+                return Err(crate::parser::parse_error_response(status, response.text()))
+            }
+            _ => return Err(FireboltError::Query(response.text())),
+        };
+        self.session_mut().account_info = Some(account_info.clone());
+        Ok(account_info)
     }
 
-    async fn get_engine_url(
-        account_name: &str,
-        api_endpoint: &str,
-        token: &str,
-    ) -> Result<String, FireboltError> {
-        let engine_url_endpoint = format!("{api_endpoint}/web/v3/account/{account_name}/engineUrl");
-        let client = reqwest::Client::new();
+    async fn send_api_get(&self, url: &str) -> Result<HttpResponse, FireboltError> {
+        let mut request = HttpRequest::new(Method::Get, url.to_string())
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.session().token.expose_secret()),
+            )
+            .header("User-Agent", self._user_agent.as_str());
+        request.headers.extend(crate::telemetry::trace_headers());
 
-        let response = client
-            .get(&engine_url_endpoint)
-            .header("Authorization", format!("Bearer {token}"))
-            .header("User-Agent", crate::version::user_agent())
-            .send()
+        self._transport
+            .send(request)
             .await
-            .map_err(|e| FireboltError::Network(format!("Failed to get engine URL: {e}")))?;
+            .map_err(network_context("Request failed"))
+    }
 
-        let status = response.status();
+    // A connection failure or 404 from the engine suggests the endpoint has
+    // moved, so the next lookup for this account should ask the server again.
+    fn invalidate_engine_url(&self, url: &str) {
+        if crate::engine_url_cache::invalidate(&self._api_endpoint, &self._account_name, url) {
+            trace_event!(
+                info,
+                url,
+                "Dropped cached engine URL after a failed request"
+            );
+        }
+    }
 
-        match status.as_u16() {
-            200 => {
-                let body = response
-                    .text()
-                    .await
-                    .map_err(|e| FireboltError::Network(format!("Failed to read response: {e}")))?;
+    // Looks the engine up again after `stale` stopped answering and points the
+    // session at its new URL. A user engine is found by running `USE ENGINE`
+    // on the account's system engine. Returns whether the URL changed; a
+    // lookup that fails or finds the same URL leaves the session as it was.
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    async fn reresolve_engine_url(&self, stale: &str) -> bool {
+        let same = |a: &str, b: &str| a.trim_end_matches('/') == b.trim_end_matches('/');
+        let (was_system, engine) = {
+            let session = self.session();
+            if !same(&session.engine_url, stale) {
+                // Another request already moved the session.
+                return true;
+            }
+            (
+                same(&session.system_engine_url, stale),
+                session.parameters.get("engine").cloned(),
+            )
+        };
 
-                let json: serde_json::Value = serde_json::from_str(&body).map_err(|e| {
-                    FireboltError::Query(format!("Failed to parse engine URL response: {e}"))
-                })?;
+        let system_url = match crate::engine_url_cache::get_or_fetch(
+            &self._api_endpoint,
+            &self._account_name,
+            self._engine_url_ttl,
+            || self.fetch_engine_url(&self._account_name),
+        )
+        .await
+        {
+            Ok(url) => url,
+            Err(e) => {
+                trace_event!(warn, error = %e, "Failed to look up the engine URL again");
+                return false;
+            }
+        };
+        self.session_mut().system_engine_url = system_url.clone();
+
+        if was_system {
+            self.session_mut().engine_url = system_url;
+        } else if let Some(engine) = engine {
+            let sql = format!(
+                "USE ENGINE {}",
+                crate::management::quote_identifier(&engine)
+            );
+            let params = self.request_params();
+            // Updates the session's engine URL from the response headers.
+            if let Err(e) = self
+                .send_query_request(&system_url, &sql, &params, false, false, None)
+                .await
+            {
+                trace_event!(warn, error = %e, "Failed to look up the engine URL again");
+                return false;
+            }
+        }
 
-                let engine_url =
-                    json.get("engineUrl")
-                        .and_then(|v| v.as_str())
-                        .ok_or_else(|| {
-                            FireboltError::Query("Missing engineUrl field in response".to_string())
-                        })?;
+        let moved = !same(&self.engine_url(), stale);
+        if moved {
+            trace_event!(info, engine_url = %self.engine_url(), "Engine moved, retrying request");
+        }
+        moved
+    }
 
-                Ok(Self::fix_schema(ensure_trailing_slash(engine_url).as_str()))
+    pub fn is_system_engine(&self) -> bool {
+        let session = self.session();
+        session.engine_url.trim_end_matches('/') == session.system_engine_url.trim_end_matches('/')
+    }
+
+    // Shares credentials, token and transport with this client, but runs
+    // queries on the system engine so management SQL works while no user
+    // engine is running.
+    pub fn system_engine_client(&self) -> FireboltClient {
+        let current = self.session();
+        let session = Session::new(current.token.clone(), current.system_engine_url.clone());
+        {
+            let mut system = session.write().unwrap_or_else(|e| e.into_inner());
+            system.token_expires_at = current.token_expires_at;
+            if let Some(database) = current.parameters.get("database") {
+                system
+                    .parameters
+                    .insert("database".to_string(), database.clone());
             }
-            404 => Err(FireboltError::Configuration(format!(
-                "Account '{account_name}' not found"
-            ))),
-            _ => {
-                let body = response.text().await.map_err(|e| {
-                    FireboltError::Network(format!("Failed to read error response: {e}"))
+            system.account_info = current.account_info.clone();
+        }
+        // Not yet connected: the system engine client connects on its own,
+        // to the same database.
+        let pending = self
+            .pending()
+            .map(|pending| Pending::new(pending.database_name.clone(), None, HashMap::new()));
+        FireboltClient {
+            _client_id: self._client_id.clone(),
+            _client_secret: self._client_secret.clone(),
+            _api_endpoint: self._api_endpoint.clone(),
+            _auth: self._auth.clone(),
+            _transport: Arc::clone(&self._transport),
+            _timeout: self._timeout,
+            _engine_wait: self._engine_wait,
+            _engine_url_ttl: self._engine_url_ttl,
+            _account_name: self._account_name.clone(),
+            _user_agent: self._user_agent.clone(),
+            _conversion_mode: self._conversion_mode,
+            _max_rows: self._max_rows,
+            _bytes_encoding: self._bytes_encoding,
+            _statement_cache: self._statement_cache.clone(),
+            _result_cache: self._result_cache.clone(),
+            _parameter_binding: self._parameter_binding,
+            _default_settings: self._default_settings.clone(),
+            _hooks: self._hooks.clone(),
+            _shutdown: self._shutdown.clone(),
+            _pending: pending,
+            _session: session,
+        }
+    }
+
+    // A clone with its own copy of the session, so parameters the server sets
+    // through it, such as a transaction id, are not seen by other clones.
+    pub(crate) fn detached(&self) -> FireboltClient {
+        let session = {
+            let current = self.session();
+            Session {
+                token: current.token.clone(),
+                token_expires_at: current.token_expires_at,
+                parameters: current.parameters.clone(),
+                engine_url: current.engine_url.clone(),
+                system_engine_url: current.system_engine_url.clone(),
+                server_info: current.server_info.clone(),
+                account_info: current.account_info.clone(),
+            }
+        };
+        let pending = self.pending().map(|pending| {
+            Pending::new(
+                pending.database_name.clone(),
+                pending.engine_name.clone(),
+                pending.session_settings.clone(),
+            )
+        });
+        FireboltClient {
+            _pending: pending,
+            _session: Arc::new(RwLock::new(session)),
+            ..self.clone()
+        }
+    }
+
+    // An open copy of a closed client, for cleaning up after it. It bypasses
+    // the result cache and does not carry the session's `query_label`, so its
+    // own queries are not mistaken for the ones it cancels.
+    pub(crate) fn reopened(&self) -> FireboltClient {
+        let client = FireboltClient {
+            _result_cache: None,
+            _shutdown: Arc::default(),
+            ..self.detached()
+        };
+        client.session_mut().parameters.remove("query_label");
+        client
+    }
+
+    // Returns a client for another account the same service account can
+    // access, connected to that account's system engine. The token and
+    // transport are reused; the token is only refreshed if the server rejects
+    // it. Session parameters are not carried over.
+    pub async fn switch_account(
+        &self,
+        account_name: &str,
+    ) -> Result<FireboltClient, FireboltError> {
+        if account_name.is_empty() {
+            return Err(FireboltError::Configuration(
+                "account_name cannot be empty".to_string(),
+            ));
+        }
+        self.connected().await?;
+        let engine_url = crate::engine_url_cache::get_or_fetch(
+            &self._api_endpoint,
+            account_name,
+            self._engine_url_ttl,
+            || {
+                crate::telemetry::instrument(
+                    crate::telemetry::Operation::ResolveEngine { account_name },
+                    self.fetch_engine_url(account_name),
+                )
+            },
+        )
+        .await?;
+
+        let (token, token_expires_at) = {
+            let session = self.session();
+            (session.token.clone(), session.token_expires_at)
+        };
+        let session = Session::new(token, engine_url);
+        session
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .token_expires_at = token_expires_at;
+        Ok(FireboltClient {
+            _client_id: self._client_id.clone(),
+            _client_secret: self._client_secret.clone(),
+            _api_endpoint: self._api_endpoint.clone(),
+            _auth: self._auth.clone(),
+            _transport: Arc::clone(&self._transport),
+            _timeout: self._timeout,
+            _engine_wait: self._engine_wait,
+            _engine_url_ttl: self._engine_url_ttl,
+            _account_name: account_name.to_string(),
+            _user_agent: self._user_agent.clone(),
+            _conversion_mode: self._conversion_mode,
+            _max_rows: self._max_rows,
+            _bytes_encoding: self._bytes_encoding,
+            _statement_cache: self._statement_cache.clone(),
+            _result_cache: self._result_cache.clone(),
+            _parameter_binding: self._parameter_binding,
+            _default_settings: self._default_settings.clone(),
+            _hooks: self._hooks.clone(),
+            _shutdown: self._shutdown.clone(),
+            _pending: None,
+            _session: session,
+        })
+    }
+
+    async fn fetch_engine_url(&self, account_name: &str) -> Result<String, FireboltError> {
+        let url = format!(
+            "{}/web/v3/account/{account_name}/engineUrl",
+            self._api_endpoint.trim_end_matches('/')
+        );
+        trace_event!(debug, url = %url, "Resolving engine URL");
+
+        let mut response = self.send_api_get(&url).await?;
+        if response.status == 401 {
+            self._hooks
+                .retry(RetryEvent::new(1, RetryCause::Unauthorized, Duration::ZERO));
+            self.refresh_token().await?;
+            response = self.send_api_get(&url).await?;
+            crate::retry::emit_outcome(1, response.is_success());
+        }
+        FireboltClientFactory::engine_url_from_response(account_name, response)
+    }
+
+    pub fn account_name(&self) -> &str {
+        &self._account_name
+    }
+
+    pub fn client_id(&self) -> &str {
+        &self._client_id
+    }
+
+    pub fn client_secret(&self) -> &SecretString {
+        &self._client_secret
+    }
+
+    pub fn api_endpoint(&self) -> &str {
+        &self._api_endpoint
+    }
+
+    pub fn user_agent(&self) -> &str {
+        &self._user_agent
+    }
+
+    pub fn engine_url(&self) -> String {
+        self.session().engine_url.clone()
+    }
+
+    pub fn parameters(&self) -> HashMap<String, String> {
+        self.session().parameters.clone()
+    }
+
+    pub fn default_settings(&self) -> &HashMap<String, String> {
+        &self._default_settings
+    }
+
+    pub(crate) fn max_rows(&self) -> Option<usize> {
+        self._max_rows
+    }
+
+    pub(crate) fn statement_cache(&self) -> Option<&StatementCache> {
+        self._statement_cache.as_deref()
+    }
+
+    pub(crate) fn result_cache(&self) -> Option<&ResultCache> {
+        self._result_cache.as_deref()
+    }
+
+    pub(crate) fn shutdown(&self) -> &Shutdown {
+        &self._shutdown
+    }
+
+    pub(crate) fn parameter_binding(&self) -> ParameterBinding {
+        self._parameter_binding
+    }
+
+    // False for a client from `build_lazy` until its first request has
+    // authenticated and set up the session.
+    pub fn is_connected(&self) -> bool {
+        self.pending().is_none()
+    }
+
+    // The setup still to do, if the client has not connected yet.
+    fn pending(&self) -> Option<&Pending> {
+        self._pending
+            .as_deref()
+            .filter(|pending| !pending.ready.initialized())
+    }
+
+    // Finishes the setup `build_lazy` deferred: authenticates, resolves the
+    // engine and runs the `USE` statements, bounded by the client's timeout
+    // and abandoned if the client is closed.
+    // Concurrent callers wait for one attempt. After a failure the next call
+    // tries again, reusing the token and engine URL if it got that far.
+    pub(crate) async fn connected(&self) -> Result<(), FireboltError> {
+        let Some(pending) = &self._pending else {
+            return Ok(());
+        };
+        let connect = pending.ready.get_or_try_init(|| {
+            with_deadline(self._timeout, "Connection", self.connect_session(pending))
+        });
+        self._shutdown
+            .guard(&HashMap::new(), connect)
+            .await
+            .map(|_| ())
+    }
+
+    async fn connect_session(&self, pending: &Pending) -> Result<(), FireboltError> {
+        if self.session().token.expose_secret().is_empty() {
+            let (token, expiration) = crate::auth::client_credentials::fetch_token(
+                self._transport.as_ref(),
+                self._client_id.clone(),
+                self._client_secret.expose_secret().to_string(),
+                self._api_endpoint.clone(),
+                &self._auth,
+                &self._user_agent,
+            )
+            .await
+            .map_err(|e| match e {
+                FireboltError::Timeout(_) => e,
+                other => FireboltError::Authentication(
+                    crate::auth::client_credentials::error_message(other),
+                ),
+            })?;
+            let mut session = self.session_mut();
+            session.token = SecretString::from(token);
+            session.token_expires_at = Some(expiration);
+        }
+
+        if self.session().engine_url.is_empty() {
+            let token = self.session().token.clone();
+            let engine_url = crate::engine_url_cache::get_or_fetch(
+                &self._api_endpoint,
+                &self._account_name,
+                self._engine_url_ttl,
+                || {
+                    crate::telemetry::instrument(
+                        crate::telemetry::Operation::ResolveEngine {
+                            account_name: &self._account_name,
+                        },
+                        FireboltClientFactory::get_engine_url(
+                            self._transport.as_ref(),
+                            &self._account_name,
+                            &self._api_endpoint,
+                            token.expose_secret(),
+                            &self._user_agent,
+                        ),
+                    )
+                },
+            )
+            .await?;
+            let mut session = self.session_mut();
+            session.system_engine_url = engine_url.clone();
+            session.engine_url = engine_url;
+        }
+
+        // The `USE` statements go through a copy that counts as connected, as
+        // they are part of connecting.
+        let client = FireboltClient {
+            _pending: None,
+            ..self.clone()
+        };
+        if let Some(database_name) = &pending.database_name {
+            let use_database_sql = format!("USE DATABASE \"{database_name}\"");
+            Box::pin(client.query(&use_database_sql))
+                .await
+                .map_err(|e| {
+                    FireboltError::Configuration(format!("Failed to set database: {e}"))
                 })?;
-                Err(FireboltError::Query(body))
+        }
+
+        if let Some(engine_name) = &pending.engine_name {
+            let use_engine_sql = format!("USE ENGINE \"{engine_name}\"");
+            Box::pin(client.query(&use_engine_sql))
+                .await
+                .map_err(|e| FireboltError::Configuration(format!("Failed to set engine: {e}")))?;
+        }
+
+        self.session_mut()
+            .parameters
+            .extend(pending.session_settings.clone());
+        Ok(())
+    }
+
+    fn session(&self) -> RwLockReadGuard<'_, Session> {
+        self._session.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn session_mut(&self) -> RwLockWriteGuard<'_, Session> {
+        self._session.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // The session is shared, so parameter and token changes made here are
+    // seen by every clone of this client.
+    pub fn set_parameter(&self, key: String, value: String) -> Result<(), FireboltError> {
+        validate_parameter(&key, &value)?;
+        trace_event!(debug, key = %key, "Session parameter set by caller");
+        self.session_mut().parameters.insert(key, value);
+        Ok(())
+    }
+
+    pub fn remove_parameter(&self, key: &str) -> Result<Option<String>, FireboltError> {
+        validate_parameter_key(key)?;
+        Ok(self.session_mut().parameters.remove(key))
+    }
+
+    pub fn reset_session(&self) {
+        reset_parameters(&mut self.session_mut().parameters);
+    }
+
+    pub fn set_token(&self, token: String) {
+        let mut session = self.session_mut();
+        session.token = SecretString::from(token);
+        session.token_expires_at = None;
+    }
+
+    // A snapshot of the session to resume later, possibly in another process,
+    // with `with_session_state`. It includes the access token. A client from
+    // `build_lazy` connects first, so the snapshot has its database and engine.
+    pub async fn session_state(&self) -> Result<SessionState, FireboltError> {
+        self.connected().await?;
+        let session = self.session();
+        Ok(SessionState {
+            client_id: self._client_id.clone(),
+            account_name: self._account_name.clone(),
+            api_endpoint: self._api_endpoint.clone(),
+            engine_url: session.engine_url.clone(),
+            system_engine_url: session.system_engine_url.clone(),
+            parameters: session.parameters.clone(),
+            token: session.token.clone(),
+            token_expires_at: session.token_expires_at,
+        })
+    }
+
+    pub fn builder() -> FireboltClientFactory {
+        FireboltClientFactory::new()
+    }
+
+    fn process_response_headers(&self, response: &HttpResponse) -> Result<(), FireboltError> {
+        let mut session = self.session_mut();
+        if let Some(endpoint_str) = response.header(HEADER_UPDATE_ENDPOINT) {
+            let url = Url::parse(FireboltClientFactory::fix_schema(endpoint_str).as_str())
+                .map_err(|e| FireboltError::HeaderParsing(format!("Invalid endpoint URL: {e}")))?;
+
+            // Keeps the port of endpoints that are not on the default one.
+            let base_url = &url[..url::Position::BeforePath];
+            let path = url.path();
+            session.engine_url = if path == "/" || path.is_empty() {
+                base_url.to_string()
+            } else {
+                format!("{base_url}{path}")
+            };
+
+            for (key, value) in url.query_pairs() {
+                session
+                    .parameters
+                    .insert(key.to_string(), value.to_string());
+            }
+
+            session.server_info = None;
+            trace_event!(debug, engine_url = %session.engine_url, "Engine endpoint updated");
+        }
+
+        if let Some(params_str) = response.header(HEADER_UPDATE_PARAMETERS) {
+            for param_pair in params_str.split(',') {
+                let param_pair = param_pair.trim();
+                if param_pair.is_empty() {
+                    continue;
+                }
+
+                let parts: Vec<&str> = param_pair.splitn(2, '=').collect();
+                if parts.len() != 2 {
+                    return Err(FireboltError::HeaderParsing(format!(
+                        "Invalid parameter format: {param_pair}"
+                    )));
+                }
+
+                let key = parts[0].trim();
+                let value = parts[1].trim();
+
+                if key.is_empty() {
+                    return Err(FireboltError::HeaderParsing(
+                        "Parameter key cannot be empty".to_string(),
+                    ));
+                }
+
+                session
+                    .parameters
+                    .insert(key.to_string(), value.to_string());
+            }
+        }
+
+        if response.has_header(HEADER_RESET_SESSION) {
+            trace_event!(debug, "Session reset by server");
+            reset_parameters(&mut session.parameters);
+        }
+
+        if let Some(remove_str) = response.header(HEADER_REMOVE_PARAMETERS) {
+            for param_name in remove_str.split(',') {
+                let param_name = param_name.trim();
+                if !param_name.is_empty() {
+                    session.parameters.remove(param_name);
+                }
             }
         }
+
+        trace_event!(
+            debug,
+            params = ?crate::telemetry::redact_params(&session.parameters),
+            "Session parameters after response"
+        );
+
+        Ok(())
     }
+}
 
-    pub fn with_credentials(mut self, client_id: String, client_secret: String) -> Self {
-        self.client_id = Some(client_id);
-        self.client_secret = Some(client_secret);
-        self
+// Cancels the labelled query on the server if `query_cancellable` is dropped
+// before it finishes. Needs a Tokio runtime to spawn the cancellation on.
+struct CancelOnDrop {
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    client: Option<FireboltClient>,
+    label: String,
+}
+
+#[cfg(feature = "tokio")]
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        let Some(client) = self.client.take() else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let label = std::mem::take(&mut self.label);
+        trace_event!(info, label = %label, "Query dropped before completion, cancelling");
+        runtime.spawn(async move { client.cancel_on_server(&label).await });
     }
+}
 
-    pub fn with_database(mut self, database_name: String) -> Self {
-        self.database_name = Some(database_name);
-        self
+// Keeps only the database and engine selection.
+fn reset_parameters(parameters: &mut HashMap<String, String>) {
+    parameters.retain(|key, _| key == "database" || key == "engine");
+}
+
+// Session settings accepted by `with_setting` without the unchecked escape hatch.
+const KNOWN_SETTINGS: &[&str] = &[
+    "cancel_query_on_connection_drop",
+    "enable_result_cache",
+    "enable_subresult_cache",
+    "insert_sharding",
+    "max_execution_time",
+    "max_insert_threads",
+    "max_result_rows",
+    "query_label",
+    "standard_conforming_strings",
+    "statement_timeout",
+    "tablet_max_size_bytes",
+    "tablet_min_size_bytes",
+    "time_zone",
+];
+
+// `output_format` is chosen by the SDK per request, so callers cannot override it.
+const RESERVED_PARAMETERS: &[&str] = &["output_format"];
+
+fn validate_parameter_key(key: &str) -> Result<(), FireboltError> {
+    if key.is_empty() {
+        return Err(FireboltError::Configuration(
+            "Parameter key cannot be empty".to_string(),
+        ));
     }
+    if !key
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+    {
+        return Err(FireboltError::Configuration(format!(
+            "Invalid parameter key '{key}': only letters, digits, '_' and '.' are allowed"
+        )));
+    }
+    if RESERVED_PARAMETERS.contains(&key) {
+        return Err(FireboltError::Configuration(format!(
+            "Parameter '{key}' is managed by the SDK and cannot be changed"
+        )));
+    }
+    Ok(())
+}
 
-    pub fn with_engine(mut self, engine_name: String) -> Self {
-        self.engine_name = Some(engine_name);
-        self
+fn validate_parameter(key: &str, value: &str) -> Result<(), FireboltError> {
+    validate_parameter_key(key)?;
+    if value.chars().any(char::is_control) {
+        return Err(FireboltError::Configuration(format!(
+            "Value of parameter '{key}' contains control characters"
+        )));
     }
+    Ok(())
+}
 
-    pub fn with_account(mut self, account_name: String) -> Self {
-        self.account_name = Some(account_name);
-        self
+fn validate_api_endpoint(api_endpoint: &str) -> Result<(), FireboltError> {
+    let invalid = |reason: &dyn std::fmt::Display| {
+        Err(FireboltError::Configuration(format!(
+            "Invalid API endpoint '{api_endpoint}': {reason}"
+        )))
+    };
+    match Url::parse(api_endpoint) {
+        Ok(url) if url.host_str().is_none_or(str::is_empty) => invalid(&"it has no host"),
+        Ok(url) if url.query().is_some() || url.fragment().is_some() => {
+            invalid(&"it must not have a query or fragment")
+        }
+        Ok(_) => Ok(()),
+        Err(e) => invalid(&e),
     }
+}
 
-    pub async fn build(self) -> Result<FireboltClient, FireboltError> {
-        // 1. Validate required parameters
-        let client_id = self
-            .client_id
-            .ok_or_else(|| FireboltError::Configuration("client_id is required".to_string()))?;
-        let client_secret = self
-            .client_secret
-            .ok_or_else(|| FireboltError::Configuration("client_secret is required".to_string()))?;
-        let account_name = self
-            .account_name
-            .ok_or_else(|| FireboltError::Configuration("account_name is required".to_string()))?;
+// The name and version are joined as `name/version` in a header value, so
+// they must be single tokens.
+fn validate_application(name: &str, version: &str) -> Result<(), FireboltError> {
+    for (field, value) in [("name", name), ("version", version)] {
+        if value.is_empty()
+            || value
+                .chars()
+                .any(|c| c.is_whitespace() || c.is_control() || c == '/')
+        {
+            return Err(FireboltError::Configuration(format!(
+                "Invalid application {field} '{value}': it must be non-empty and contain no spaces, '/' or control characters"
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn network_context(context: &str) -> impl Fn(FireboltError) -> FireboltError + '_ {
+    move |e| match e {
+        FireboltError::Network(msg) => FireboltError::Network(format!("{context}: {msg}")),
+        other => other,
+    }
+}
+
+// Identifies the failed request in error messages. The statement is shown
+// with literals masked, so it is safe to log.
+fn query_context(sql: &str, status: Option<u16>, query_id: Option<&str>) -> String {
+    let mut parts = Vec::new();
+    if let Some(status) = status {
+        parts.push(format!("HTTP {status}"));
+    }
+    if let Some(query_id) = query_id {
+        parts.push(format!("query ID {query_id}"));
+    }
+    parts.push(format!(
+        "statement: {}",
+        crate::telemetry::statement_summary(sql)
+    ));
+    parts.join(", ")
+}
 
-        let api_endpoint = Self::get_api_endpoint();
+// Statements that only read data can be resent safely if the connection
+// drops before the response arrives.
+pub(crate) fn is_read_only(sql: &str) -> bool {
+    let mut rest = sql.trim_start();
+    loop {
+        if let Some(comment) = rest.strip_prefix("--") {
+            rest = comment.split_once('\n').map_or("", |(_, after)| after);
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            rest = comment.split_once("*/").map_or("", |(_, after)| after);
+        } else if let Some(inner) = rest.strip_prefix('(') {
+            rest = inner;
+        } else {
+            break;
+        }
+        rest = rest.trim_start();
+    }
+    let keyword: String = rest
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_ascii_uppercase();
+    matches!(
+        keyword.as_str(),
+        "SELECT" | "WITH" | "SHOW" | "DESCRIBE" | "EXPLAIN" | "VALUES"
+    )
+}
+
+fn is_engine_not_ready(error: &FireboltError) -> bool {
+    match error {
+        FireboltError::EngineNotRunning(_) => true,
+        FireboltError::Query(message) | FireboltError::Unavailable(message) => {
+            reports_engine_not_ready(message)
+        }
+        _ => false,
+    }
+}
+
+fn reports_engine_not_ready(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    message.contains("engine")
+        && [
+            "not running",
+            "is starting",
+            "is stopped",
+            "not started",
+            "is being started",
+        ]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+async fn with_deadline<T>(
+    timeout: Option<Duration>,
+    operation: &str,
+    future: impl Future<Output = Result<T, FireboltError>>,
+) -> Result<T, FireboltError> {
+    match timeout {
+        Some(timeout) => crate::runtime::timeout(timeout, future)
+            .await
+            .map_err(|_| {
+                FireboltError::Timeout(format!("{operation} did not complete within {timeout:?}"))
+            })?,
+        None => future.await,
+    }
+}
+
+fn ensure_trailing_slash(url: &str) -> String {
+    if url.ends_with('/') {
+        url.to_string()
+    } else {
+        format!("{url}/")
+    }
+}
+
+pub struct FireboltClientFactory {
+    client_id: Option<String>,
+    client_secret: Option<SecretString>,
+    database_name: Option<String>,
+    engine_name: Option<String>,
+    account_name: Option<String>,
+    api_endpoint: Option<String>,
+    auth: AuthOptions,
+    host_aliases: HashMap<String, String>,
+    transport: Option<Arc<dyn Transport>>,
+    http_options: HttpOptions,
+    request_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    engine_wait_timeout: Option<Duration>,
+    engine_poll_interval: Duration,
+    engine_url_ttl: Duration,
+    retry_policy: RetryPolicy,
+    retry_strategy: Option<Arc<dyn RetryStrategy>>,
+    hooks: Hooks,
+    application: Option<(String, String)>,
+    conversion_mode: ConversionMode,
+    max_rows: Option<usize>,
+    bytes_encoding: BytesEncoding,
+    statement_cache: Option<usize>,
+    result_cache: Option<(usize, Duration)>,
+    parameter_binding: ParameterBinding,
+    default_settings: HashMap<String, String>,
+    session_settings: HashMap<String, String>,
+    unknown_settings: Vec<String>,
+    session_state: Option<SessionState>,
+}
+
+impl FireboltClientFactory {
+    fn new() -> Self {
+        Self {
+            client_id: None,
+            client_secret: None,
+            database_name: None,
+            engine_name: None,
+            account_name: None,
+            api_endpoint: None,
+            auth: AuthOptions::default(),
+            host_aliases: HashMap::new(),
+            transport: None,
+            http_options: HttpOptions::default(),
+            request_timeout: None,
+            timeout: None,
+            engine_wait_timeout: None,
+            engine_poll_interval: DEFAULT_ENGINE_POLL_INTERVAL,
+            engine_url_ttl: Duration::ZERO,
+            retry_policy: RetryPolicy::default(),
+            retry_strategy: None,
+            hooks: Hooks::default(),
+            application: None,
+            conversion_mode: ConversionMode::Strict,
+            max_rows: None,
+            bytes_encoding: BytesEncoding::Auto,
+            statement_cache: None,
+            result_cache: None,
+            parameter_binding: ParameterBinding::Server,
+            default_settings: HashMap::new(),
+            session_settings: HashMap::new(),
+            unknown_settings: Vec::new(),
+            session_state: None,
+        }
+    }
+
+    fn fix_schema(url: &str) -> String {
+        if url.contains("://") {
+            url.to_string()
+        } else {
+            format!("https://{url}")
+        }
+    }
+
+    fn get_api_endpoint() -> String {
+        let api_endpoint = std::env::var("FIREBOLT_API_ENDPOINT")
+            .unwrap_or_else(|_| "api.app.firebolt.io".to_string());
+
+        Self::fix_schema(&api_endpoint)
+    }
+
+    async fn get_engine_url(
+        transport: &dyn Transport,
+        account_name: &str,
+        api_endpoint: &str,
+        token: &str,
+        user_agent: &str,
+    ) -> Result<String, FireboltError> {
+        let engine_url_endpoint = format!("{api_endpoint}/web/v3/account/{account_name}/engineUrl");
+
+        trace_event!(debug, url = %engine_url_endpoint, "Resolving engine URL");
+
+        let mut request = HttpRequest::new(Method::Get, engine_url_endpoint)
+            .header("Authorization", format!("Bearer {token}"))
+            .header("User-Agent", user_agent);
+        request.headers.extend(crate::telemetry::trace_headers());
+
+        let response = transport
+            .send(request)
+            .await
+            .map_err(network_context("Failed to get engine URL"))?;
+
+        Self::engine_url_from_response(account_name, response)
+    }
+
+    fn engine_url_from_response(
+        account_name: &str,
+        response: HttpResponse,
+    ) -> Result<String, FireboltError> {
+        crate::telemetry::record_status(response.status);
+        trace_event!(
+            debug,
+            status = response.status,
+            "Received engine URL response"
+        );
+
+        match response.status {
+            200 => {
+                let json: serde_json::Value =
+                    serde_json::from_slice(&response.body).map_err(|e| {
+                        FireboltError::EngineResolution(format!(
+                            "Failed to parse engine URL response: {e}"
+                        ))
+                    })?;
+
+                let engine_url =
+                    json.get("engineUrl")
+                        .and_then(|v| v.as_str())
+                        .ok_or_else(|| {
+                            FireboltError::EngineResolution(
+                                "Missing engineUrl field in response".to_string(),
+                            )
+                        })?;
+
+                Ok(Self::fix_schema(ensure_trailing_slash(engine_url).as_str()))
+            }
+            404 => Err(FireboltError::AccountNotFound(format!(
+                "Account '{account_name}' not found"
+            ))),
+            status @ (429 | 502 | 503 | 504) => {
+                Err(crate::parser::parse_error_response(status, response.text()))
+            }
+            status => Err(FireboltError::EngineResolution(format!(
+                "HTTP {status}: {}",
+                response.text()
+            ))),
+        }
+    }
+
+    pub fn from_env() -> Result<Self, FireboltError> {
+        Ok(Self::from_credentials(
+            crate::config::credentials_from_env()?
+        ))
+    }
+
+    pub fn from_credentials_file(path: impl AsRef<Path>) -> Result<Self, FireboltError> {
+        Self::from_credentials_profile(path, crate::config::DEFAULT_PROFILE)
+    }
+
+    pub fn from_credentials_profile(
+        path: impl AsRef<Path>,
+        profile: &str,
+    ) -> Result<Self, FireboltError> {
+        let credentials = crate::config::read_credentials_file(path.as_ref(), profile)?;
+        Ok(Self::from_credentials(credentials))
+    }
+
+    pub fn from_config(config: ClientConfig) -> Self {
+        let mut factory = Self::new();
+        factory.client_id = config.client_id;
+        factory.client_secret = config.client_secret;
+        factory.account_name = config.account;
+        factory.database_name = config.database;
+        factory.engine_name = config.engine;
+        factory.api_endpoint = config.api_endpoint;
+        if let Some(audience) = config.audience {
+            factory.auth.audience = audience;
+        }
+        factory.auth.scopes = config.scopes;
+        factory.auth.token_url = config.auth_url;
+        factory.host_aliases = config
+            .host_aliases
+            .into_iter()
+            .map(|(host, url)| (host.to_ascii_lowercase(), url))
+            .collect();
+        factory.http_options.connect_timeout = config.connect_timeout;
+        factory.request_timeout = config.request_timeout;
+        factory.timeout = config.timeout;
+        factory.engine_wait_timeout = config.engine_wait_timeout;
+        if let Some(poll_interval) = config.engine_poll_interval {
+            factory.engine_poll_interval = poll_interval;
+        }
+        if let Some(retry_policy) = config.retry {
+            factory.retry_policy = retry_policy;
+        }
+        factory
+    }
+
+    pub(crate) fn from_credentials(credentials: crate::config::Credentials) -> Self {
+        let mut factory = Self::new();
+        factory.client_id = credentials.client_id;
+        factory.client_secret = credentials.client_secret.map(SecretString::from);
+        factory.account_name = credentials.account_name;
+        factory.database_name = credentials.database_name;
+        factory.engine_name = credentials.engine_name;
+        factory
+    }
+
+    pub fn with_credentials(mut self, client_id: String, client_secret: String) -> Self {
+        self.client_id = Some(client_id);
+        self.client_secret = Some(SecretString::from(client_secret));
+        self
+    }
+
+    pub fn with_database(mut self, database_name: String) -> Self {
+        self.database_name = Some(database_name);
+        self
+    }
+
+    pub fn with_engine(mut self, engine_name: String) -> Self {
+        self.engine_name = Some(engine_name);
+        self
+    }
+
+    pub fn with_system_engine(mut self) -> Self {
+        self.engine_name = None;
+        self
+    }
+
+    pub fn with_account(mut self, account_name: String) -> Self {
+        self.account_name = Some(account_name);
+        self
+    }
+
+    pub fn with_api_endpoint(mut self, api_endpoint: String) -> Self {
+        self.api_endpoint = Some(api_endpoint);
+        self
+    }
+
+    // The OAuth audience tokens are requested for. Defaults to
+    // `https://api.firebolt.io`; other identity environments may need their own.
+    pub fn with_audience(mut self, audience: String) -> Self {
+        self.auth.audience = audience;
+        self
+    }
+
+    // Scopes to request with each token, sent space-separated. None are
+    // requested by default.
+    pub fn with_scopes(mut self, scopes: Vec<String>) -> Self {
+        self.auth.scopes = scopes;
+        self
+    }
+
+    // The full URL tokens are requested from, e.g.
+    // `https://id.firebolt.example.com/oauth/token`. By default it is derived
+    // from an API endpoint of the form `api.<env>.firebolt.io`; set it for
+    // private-link or DNS-aliased endpoints that do not follow that pattern.
+    pub fn with_auth_url(mut self, auth_url: String) -> Self {
+        self.auth.token_url = Some(auth_url);
+        self
+    }
+
+    // Sends every request for `host` to `url` instead, keeping the path and
+    // query; a path in `url` is used as a prefix. For VPC endpoints and
+    // reverse proxies with their own host names, such as
+    // `with_host_alias("api.app.firebolt.io", "https://firebolt-api.corp.internal")`.
+    // Applies to the API endpoint, the token URL and engine URLs alike.
+    pub fn with_host_alias(mut self, host: &str, url: String) -> Self {
+        self.host_aliases.insert(host.to_ascii_lowercase(), url);
+        self
+    }
+
+    pub fn with_transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.http_options.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    pub fn with_dns_override(mut self, host: String, addrs: Vec<IpAddr>) -> Self {
+        self.http_options
+            .dns
+            .overrides
+            .insert(host.to_ascii_lowercase(), addrs);
+        self
+    }
+
+    pub fn with_dns_resolver<R: Resolver + 'static>(mut self, resolver: R) -> Self {
+        self.http_options.dns.resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    pub fn with_dns_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.http_options.dns.cache_ttl = Some(ttl);
+        self
+    }
+
+    pub fn with_proxy(mut self, proxy_url: String) -> Self {
+        let credentials = self.http_options.proxy.take().and_then(|p| p.credentials);
+        self.http_options.proxy = Some(ProxyOptions {
+            url: proxy_url,
+            credentials,
+        });
+        self
+    }
+
+    pub fn with_proxy_credentials(mut self, username: String, password: String) -> Self {
+        let url = self
+            .http_options
+            .proxy
+            .take()
+            .map(|p| p.url)
+            .unwrap_or_default();
+        self.http_options.proxy = Some(ProxyOptions {
+            url,
+            credentials: Some((username, SecretString::from(password))),
+        });
+        self
+    }
+
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = Some(request_timeout);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_engine_wait(mut self, timeout: Duration) -> Self {
+        self.engine_wait_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_engine_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.engine_poll_interval = poll_interval;
+        self
+    }
+
+    // Lets clients in this process reuse an account's engine URL for `ttl`
+    // instead of looking it up on every connect. Off by default.
+    pub fn with_engine_url_ttl(mut self, ttl: Duration) -> Self {
+        self.engine_url_ttl = ttl;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self.retry_strategy = None;
+        self
+    }
+
+    // Replaces the retry policy with custom retry logic.
+    pub fn with_retry_strategy(mut self, strategy: impl RetryStrategy + 'static) -> Self {
+        self.retry_strategy = Some(Arc::new(strategy));
+        self
+    }
+
+    // Called before each HTTP request, including every retry.
+    pub fn on_request(mut self, hook: impl Fn(&RequestInfo) + Send + Sync + 'static) -> Self {
+        self.hooks.on_request = Some(Arc::new(hook));
+        self
+    }
+
+    // Called when each HTTP request finishes, with its status or error.
+    pub fn on_response(mut self, hook: impl Fn(&ResponseInfo) + Send + Sync + 'static) -> Self {
+        self.hooks.on_response = Some(Arc::new(hook));
+        self
+    }
+
+    pub fn on_retry(mut self, hook: impl Fn(&RetryEvent) + Send + Sync + 'static) -> Self {
+        self.hooks.on_retry = Some(Arc::new(hook));
+        self
+    }
+
+    // Resumes a session saved with `session_state` instead of starting a new
+    // one: `build` skips authentication while the saved token is fresh, and
+    // skips engine resolution, `with_database`, `with_engine` and `USE`
+    // statements, restoring the saved engine URL and parameters instead.
+    // Credentials are still needed to refresh the token.
+    pub fn with_session_state(mut self, state: SessionState) -> Self {
+        self.session_state = Some(state);
+        self
+    }
+
+    pub fn with_application_name(mut self, name: String, version: String) -> Self {
+        self.application = Some((name, version));
+        self
+    }
+
+    pub fn with_conversion_mode(mut self, mode: ConversionMode) -> Self {
+        self.conversion_mode = mode;
+        self
+    }
+
+    // Keeps at most `max_rows` rows of each result and marks larger results
+    // as truncated, so an unbounded SELECT cannot exhaust memory in an
+    // interactive tool. `query_with_max_rows` overrides it for one query.
+    pub fn with_max_rows(mut self, max_rows: usize) -> Self {
+        self.max_rows = Some(max_rows);
+        self
+    }
+
+    pub fn with_bytes_encoding(mut self, encoding: BytesEncoding) -> Self {
+        self.bytes_encoding = encoding;
+        self
+    }
+
+    // Keeps the prepared form of the `capacity` most recently used statements,
+    // so `prepare` and `query_with_params` skip scanning SQL they have seen
+    // before. Clones of the client share the cache.
+    pub fn with_statement_cache(mut self, capacity: usize) -> Self {
+        self.statement_cache = Some(capacity);
+        self
+    }
+
+    // Serves repeated read-only queries from memory for `ttl`, keeping at most
+    // `capacity` results. Queries match when their SQL differs only in
+    // whitespace and they run with the same session parameters on the same
+    // engine. Clones of the client share the cache. Off by default.
+    pub fn with_result_cache(mut self, capacity: usize, ttl: Duration) -> Self {
+        self.result_cache = Some((capacity, ttl));
+        self
+    }
+
+    pub fn with_parameter_binding(mut self, binding: ParameterBinding) -> Self {
+        self.parameter_binding = binding;
+        self
+    }
+
+    // Settings sent with every query, such as `time_zone` or
+    // `max_execution_time`. Repeated calls add to the map; a parameter set on
+    // the session with the same key wins.
+    pub fn with_default_settings(mut self, settings: HashMap<String, String>) -> Self {
+        self.default_settings.extend(settings);
+        self
+    }
+
+    // Sets a session parameter on the client before `build()` returns, as if
+    // `set_parameter` had been called. Unlike default settings, it is cleared
+    // by `reset_session`. Keys are checked against the settings Firebolt
+    // documents, so a typo fails the build; use `with_unchecked_setting` for
+    // one the SDK does not know yet.
+    pub fn with_setting(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let key = key.into();
+        if !KNOWN_SETTINGS.contains(&key.as_str()) {
+            self.unknown_settings.push(key.clone());
+        }
+        self.session_settings.insert(key, value.into());
+        self
+    }
+
+    pub fn with_unchecked_setting(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.session_settings.insert(key.into(), value.into());
+        self
+    }
+
+    pub async fn build(self) -> Result<FireboltClient, FireboltError> {
+        let client = self.assemble()?;
+        client.connected().await?;
+        Ok(client)
+    }
+
+    // Returns the client without sending any requests. Authentication, engine
+    // resolution and the `USE` statements happen on the first request, and
+    // their errors are returned from it; a failed attempt is retried on the
+    // next one. Until then `engine_url`, `parameters` and `dry_run` reflect
+    // the unconnected session.
+    pub fn build_lazy(self) -> Result<FireboltClient, FireboltError> {
+        self.assemble()
+    }
+
+    // Checks the configuration without sending any requests, so mistakes
+    // surface at startup rather than on the first query. Every problem found
+    // is listed in one `Configuration` error. `build` runs the same checks.
+    pub fn validate(&self) -> Result<(), FireboltError> {
+        let problems = self.problems();
+        match problems.len() {
+            0 => Ok(()),
+            1 => Err(FireboltError::Configuration(problems.concat())),
+            count => Err(FireboltError::Configuration(format!(
+                "{count} problems: {}",
+                problems.join("; ")
+            ))),
+        }
+    }
+
+    fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut check = |result: Result<(), FireboltError>| match result {
+            Ok(()) => {}
+            Err(FireboltError::Configuration(message)) => problems.push(message),
+            Err(e) => problems.push(e.to_string()),
+        };
+
+        for (field, value) in [
+            ("client_id", self.client_id.as_deref()),
+            (
+                "client_secret",
+                self.client_secret.as_ref().map(|s| s.expose_secret()),
+            ),
+            ("account_name", self.account_name.as_deref()),
+        ] {
+            if value.is_none_or(str::is_empty) {
+                check(Err(FireboltError::Configuration(format!(
+                    "{field} is required"
+                ))));
+            }
+        }
+        let api_endpoint = match &self.api_endpoint {
+            Some(api_endpoint) => Self::fix_schema(api_endpoint),
+            None => Self::get_api_endpoint(),
+        };
+        check(validate_api_endpoint(&api_endpoint).and_then(|()| {
+            self.auth.token_url(&api_endpoint).map(drop).map_err(|e| {
+                FireboltError::Configuration(format!(
+                    "{e}; use with_auth_url to set the token URL for other endpoints"
+                ))
+            })
+        }));
+        check(self.auth.validate());
+        if let Some(state) = &self.session_state {
+            for (field, configured, saved) in [
+                ("client_id", self.client_id.as_deref(), &state.client_id),
+                (
+                    "account_name",
+                    self.account_name.as_deref(),
+                    &state.account_name,
+                ),
+                (
+                    "api_endpoint",
+                    Some(api_endpoint.as_str()),
+                    &state.api_endpoint,
+                ),
+            ] {
+                if configured.is_some_and(|configured| {
+                    configured.trim_end_matches('/') != saved.trim_end_matches('/')
+                }) {
+                    check(Err(FireboltError::Configuration(format!(
+                        "The session state was saved with {field} '{saved}', which does not match this configuration"
+                    ))));
+                }
+            }
+        }
+        for (host, url) in &self.host_aliases {
+            check(crate::transport::validate_alias(host, url));
+        }
+        for (field, name) in [
+            ("database", &self.database_name),
+            ("engine", &self.engine_name),
+        ] {
+            if let Some(name) = name {
+                if name.is_empty() || name.contains('"') {
+                    check(Err(FireboltError::Configuration(format!(
+                        "Invalid {field} name '{name}': it must be non-empty and contain no '\"'"
+                    ))));
+                }
+            }
+        }
+        if let Some((name, version)) = &self.application {
+            check(validate_application(name, version));
+        }
+        for (key, value) in self.default_settings.iter().chain(&self.session_settings) {
+            check(validate_parameter(key, value));
+        }
+        for key in &self.unknown_settings {
+            check(Err(FireboltError::Configuration(format!(
+                "Unknown setting '{key}'; use with_unchecked_setting to send it anyway"
+            ))));
+        }
+
+        match &self.transport {
+            Some(_) => {
+                let http_options = [
+                    (
+                        "with_connect_timeout",
+                        self.http_options.connect_timeout.is_some(),
+                    ),
+                    ("with_proxy", self.http_options.proxy.is_some()),
+                    (
+                        "with_dns_override, with_dns_resolver or with_dns_cache_ttl",
+                        !self.http_options.dns.is_default(),
+                    ),
+                ];
+                for (option, set) in http_options {
+                    if set {
+                        check(Err(FireboltError::Configuration(format!(
+                            "{option} only applies to the built-in transport and is ignored with with_transport"
+                        ))));
+                    }
+                }
+            }
+            None => check(crate::transport::validate_options(&self.http_options)),
+        }
+
+        for (option, duration) in [
+            ("timeout", self.timeout),
+            ("request timeout", self.request_timeout),
+            ("engine wait timeout", self.engine_wait_timeout),
+        ] {
+            if duration.is_some_and(|duration| duration.is_zero()) {
+                check(Err(FireboltError::Configuration(format!(
+                    "The {option} must be greater than zero"
+                ))));
+            }
+        }
+        if self.engine_wait_timeout.is_some() && self.engine_poll_interval.is_zero() {
+            check(Err(FireboltError::Configuration(
+                "The engine poll interval must be greater than zero".to_string(),
+            )));
+        }
+        if self.statement_cache == Some(0) {
+            check(Err(FireboltError::Configuration(
+                "The statement cache capacity must be greater than zero".to_string(),
+            )));
+        }
+        if let Some((capacity, ttl)) = self.result_cache {
+            if capacity == 0 || ttl.is_zero() {
+                check(Err(FireboltError::Configuration(
+                    "The result cache capacity and TTL must be greater than zero".to_string(),
+                )));
+            }
+        }
+        problems
+    }
+
+    // Builds the client without sending any requests; `connected` finishes
+    // the job before the first request.
+    fn assemble(self) -> Result<FireboltClient, FireboltError> {
+        self.validate()?;
+        let client_id = self
+            .client_id
+            .ok_or_else(|| FireboltError::Configuration("client_id is required".to_string()))?;
+        let client_secret = self
+            .client_secret
+            .ok_or_else(|| FireboltError::Configuration("client_secret is required".to_string()))?;
+        let account_name = self
+            .account_name
+            .ok_or_else(|| FireboltError::Configuration("account_name is required".to_string()))?;
+
+        let api_endpoint = match self.api_endpoint {
+            Some(api_endpoint) => Self::fix_schema(&api_endpoint),
+            None => Self::get_api_endpoint(),
+        };
+        let user_agent = match &self.application {
+            Some((name, version)) => crate::version::user_agent_with_application(name, version),
+            None => crate::version::user_agent(),
+        };
+        let mut transport: Arc<dyn Transport> = match self.transport {
+            Some(transport) => transport,
+            None => crate::transport::default_transport(&self.http_options)?,
+        };
+        if !self.host_aliases.is_empty() {
+            transport = Arc::new(AliasTransport::new(transport, &self.host_aliases));
+        }
+        if let Some(request_timeout) = self.request_timeout {
+            transport = Arc::new(TimeoutTransport::new(transport, request_timeout));
+        }
+        if self.hooks.observes_requests() {
+            transport = Arc::new(HookTransport::new(transport, self.hooks.clone()));
+        }
+        if let Some(strategy) = self.retry_strategy {
+            transport = Arc::new(RetryTransport::new(transport, strategy, self.hooks.clone()));
+        } else if self.retry_policy.max_retries() > 0 {
+            transport = Arc::new(RetryTransport::new(
+                transport,
+                Arc::new(self.retry_policy),
+                self.hooks.clone(),
+            ));
+        }
+
+        let session = Session::new(SecretString::from(String::new()), String::new());
+        // A resumed session already has its database and engine, unless it
+        // was saved before it had an engine.
+        let resumed = self
+            .session_state
+            .as_ref()
+            .is_some_and(|state| !state.engine_url.is_empty());
+        if let Some(state) = self.session_state {
+            trace_event!(debug, engine_url = %state.engine_url, "Resuming saved session");
+            let mut session = session.write().unwrap_or_else(|e| e.into_inner());
+            if state.token_is_fresh() {
+                session.token = state.token;
+                session.token_expires_at = state.token_expires_at;
+            }
+            session.engine_url = state.engine_url;
+            session.system_engine_url = state.system_engine_url;
+            session.parameters = state.parameters;
+        }
+        let pending = Pending::new(
+            self.database_name.filter(|_| !resumed),
+            self.engine_name.filter(|_| !resumed),
+            self.session_settings,
+        );
+
+        Ok(FireboltClient {
+            _client_id: client_id,
+            _client_secret: client_secret,
+            _api_endpoint: api_endpoint,
+            _auth: self.auth,
+            _transport: transport,
+            _timeout: self.timeout,
+            _engine_wait: self.engine_wait_timeout.map(|timeout| EngineWait {
+                timeout,
+                poll_interval: self.engine_poll_interval,
+            }),
+            _engine_url_ttl: self.engine_url_ttl,
+            _account_name: account_name,
+            _user_agent: user_agent,
+            _conversion_mode: self.conversion_mode,
+            _max_rows: self.max_rows,
+            _bytes_encoding: self.bytes_encoding,
+            _statement_cache: self
+                .statement_cache
+                .map(|capacity| Arc::new(StatementCache::new(capacity))),
+            _result_cache: self
+                .result_cache
+                .map(|(capacity, ttl)| Arc::new(ResultCache::new(capacity, ttl))),
+            _parameter_binding: self.parameter_binding,
+            _default_settings: self.default_settings,
+            _hooks: self.hooks,
+            _shutdown: Arc::default(),
+            _pending: Some(pending),
+            _session: session,
+        })
+    }
+}
+
+// The tests talk to mockito servers through the reqwest transport.
+#[cfg(all(test, feature = "reqwest"))]
+mod tests {
+    use super::*;
+    use crate::transport::ReqwestTransport;
+
+    #[tokio::test]
+    async fn test_execute_query_request_success() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"meta": [{"name": "test", "type": "int"}], "data": [[1]]}"#)
+            .create_async()
+            .await;
+
+        let client = create_test_client();
+        client.session_mut().engine_url = server.url();
+
+        let result = client
+            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true, true)
+            .await;
+
+        mock.assert_async().await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_execute_query_request_retry_on_401() {
+        let mut server = mockito::Server::new_async().await;
+
+        let mock_401 = server
+            .mock("POST", "/")
+            .with_status(401)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let _mock_success = server
+            .mock("POST", "/")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"meta": [{"name": "test", "type": "int"}], "data": [[1]]}"#)
+            .create_async()
+            .await;
+
+        let client = create_test_client();
+        client.session_mut().engine_url = server.url();
+
+        let result = client
+            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true, true)
+            .await;
+
+        mock_401.assert_async().await;
+
+        assert!(result.is_err());
+        assert!(matches!(
+            result.unwrap_err(),
+            FireboltError::Authentication(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_query_request_no_retry_on_second_401() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/")
+            .with_status(401)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = create_test_client();
+        client.session_mut().engine_url = server.url();
+
+        let result = client
+            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true, false)
+            .await;
+
+        mock.assert_async().await;
+        assert!(result.is_err());
+        assert!(matches!(
+            result.unwrap_err(),
+            FireboltError::Authentication(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_engine_moved() {
+        let mut server = mockito::Server::new_async().await;
+        let old = server
+            .mock("POST", mockito::Matcher::Regex("^/old/".to_string()))
+            .with_status(404)
+            .with_body("Not Found")
+            .create_async()
+            .await;
+        let lookup = server
+            .mock("GET", "/web/v3/account/test_account/engineUrl")
+            .with_status(200)
+            .with_body(format!(r#"{{"engineUrl": "{}/new"}}"#, server.url()))
+            .create_async()
+            .await;
+        let new = server
+            .mock("POST", mockito::Matcher::Regex("^/new/".to_string()))
+            .with_status(200)
+            .with_body(r#"{"meta": [{"name": "one", "type": "int"}], "data": [[1]]}"#)
+            .create_async()
+            .await;
+
+        let client = FireboltClient {
+            _api_endpoint: server.url(),
+            _session: Session::new(
+                SecretString::from("test_token"),
+                format!("{}/old/", server.url()),
+            ),
+            ..create_test_client()
+        };
+        let result = client.query("SELECT 1 AS one").await.unwrap();
+        assert_eq!(result.rows[0].get::<i32>("one").unwrap(), 1);
+        assert_eq!(client.engine_url(), format!("{}/new/", server.url()));
+        assert!(client.is_system_engine());
+
+        old.assert_async().await;
+        lookup.assert_async().await;
+        new.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_execute_query_request_5xx_error() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/")
+            .with_status(500)
+            .with_body("Internal Server Error")
+            .create_async()
+            .await;
+
+        let client = create_test_client();
+        client.session_mut().engine_url = server.url();
+
+        let result = client
+            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true, true)
+            .await;
+
+        mock.assert_async().await;
+        assert!(result.is_err());
+        let error = result.unwrap_err();
+        assert!(matches!(error, FireboltError::Query(_)));
+        assert!(format!("{error:?}").contains("Internal Server Error"));
+    }
+
+    #[test]
+    fn test_client_getters() {
+        let client = create_test_client();
+        assert_eq!(client.client_id(), "test_id");
+        assert_eq!(client.client_secret().expose_secret(), "test_secret");
+        assert_eq!(client.api_endpoint(), "https://api.test.firebolt.io");
+        assert_eq!(client.engine_url(), "https://test.engine.url/");
+        assert!(client.parameters().is_empty());
+    }
+
+    #[test]
+    fn test_set_token() {
+        let client = create_test_client();
+        client.set_token("new_token".to_string());
+        assert_eq!(client.session().token.expose_secret(), "new_token");
+    }
+
+    #[tokio::test]
+    async fn test_clones_share_session() {
+        fn assert_shareable<T: Clone + Send + Sync + 'static>() {}
+        assert_shareable::<FireboltClient>();
+
+        let mock = crate::testing::MockFirebolt::new();
+        mock.on_query(
+            "USE DATABASE db1",
+            crate::testing::MockResponse::empty().with_updated_parameters(&[("database", "db1")]),
+        );
+        let client = mock.connect().await.unwrap();
+        let clone = client.clone();
+        clone.query("USE DATABASE db1").await.unwrap();
+        assert_eq!(
+            client.parameters().get("database"),
+            Some(&"db1".to_string())
+        );
+
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move { client.query("SELECT 1").await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+        assert_eq!(mock.queries().len(), 5);
+        assert!(mock
+            .queries()
+            .iter()
+            .skip(1)
+            .all(|query| query.parameters.get("database") == Some(&"db1".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_query_with_metadata() {
+        let mock = crate::testing::MockFirebolt::new();
+        mock.set_default_response(
+            crate::testing::MockResponse::rows(&[("one", "int")], vec![vec![1.into()]])
+                .with_header("X-Request-Id", "req-42"),
+        );
+        let client = mock.connect().await.unwrap();
+
+        let (result, metadata) = client.query_with_metadata("SELECT 1 AS one").await.unwrap();
+        assert_eq!(result.rows.len(), 1);
+        assert_eq!(metadata.status, 200);
+        assert_eq!(metadata.header("x-request-id"), Some("req-42"));
+        assert_eq!(metadata.query_id, None);
+        assert_eq!(metadata.timing.transfer, None);
+    }
+
+    #[tokio::test]
+    async fn test_query_spilled() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "output_format".to_string(),
+                "JSONLines_Compact".to_string(),
+            ))
+            .with_status(200)
+            .with_body(concat!(
+                r#"{"message_type":"START","result_columns":[{"name":"n","type":"int"}]}"#,
+                "\n",
+                r#"{"message_type":"DATA","data":[[1],[2]]}"#,
+                "\n",
+                r#"{"message_type":"FINISH_SUCCESSFULLY","statistics":{}}"#,
+                "\n",
+            ))
+            .create_async()
+            .await;
+
+        let client = create_test_client();
+        client.session_mut().engine_url = server.url();
+
+        let result = client.query_spilled("SELECT n FROM t").await.unwrap();
+        mock.assert_async().await;
+        assert!(result.path().exists());
+        let values: Vec<i32> = result
+            .rows()
+            .unwrap()
+            .map(|row| row.unwrap().get("n").unwrap())
+            .collect();
+        assert_eq!(values, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_query_to_writer() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "output_format".to_string(),
+                "CSVWithNames".to_string(),
+            ))
+            .with_status(200)
+            .with_body("n\n1\n2\n")
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/")
+            .match_body("SELECT missing")
+            .with_status(400)
+            .with_body("no such column")
+            .create_async()
+            .await;
+
+        let client = create_test_client();
+        client.session_mut().engine_url = server.url();
+
+        let mut output = Vec::new();
+        let written = client
+            .query_to_writer("SELECT n FROM t", &mut output, OutputFormat::CsvWithNames)
+            .await
+            .unwrap();
+        mock.assert_async().await;
+        assert_eq!(written, 6);
+        assert_eq!(output, b"n\n1\n2\n");
+
+        let mut output = Vec::new();
+        let result = client
+            .query_to_writer("SELECT missing", &mut output, OutputFormat::Csv)
+            .await;
+        assert!(matches!(result, Err(FireboltError::Query(_))));
+        assert!(output.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_with_conversion_mode() {
+        let mock = crate::testing::MockFirebolt::new();
+        mock.set_default_response(crate::testing::MockResponse::rows(
+            &[("total", "text")],
+            vec![vec!["42".into()]],
+        ));
+        let client = mock
+            .client_factory()
+            .with_conversion_mode(ConversionMode::Lenient)
+            .build()
+            .await
+            .unwrap();
+        let result = client.query("SELECT total FROM t").await.unwrap();
+        assert_eq!(result.rows[0].get::<i32>("total").unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn test_with_max_rows() {
+        let mock = crate::testing::MockFirebolt::new();
+        mock.set_default_response(crate::testing::MockResponse::rows(
+            &[("n", "int")],
+            vec![vec![1.into()], vec![2.into()], vec![3.into()]],
+        ));
+        let client = mock
+            .client_factory()
+            .with_max_rows(2)
+            .build()
+            .await
+            .unwrap();
+
+        let result = client.query("SELECT n FROM t").await.unwrap();
+        assert_eq!(result.rows.len(), 2);
+        assert_eq!(result.rows[1].get::<i32>("n").unwrap(), 2);
+        assert!(result.is_truncated());
+
+        let result = client
+            .query_with_max_rows("SELECT n FROM t", 3)
+            .await
+            .unwrap();
+        assert_eq!(result.rows.len(), 3);
+        assert!(!result.is_truncated());
+
+        let result = client
+            .query_with_max_rows("SELECT n FROM t", 0)
+            .await
+            .unwrap();
+        assert!(result.rows.is_empty());
+        assert!(result.is_truncated());
+    }
+
+    #[tokio::test]
+    async fn test_with_default_settings() {
+        let mock = crate::testing::MockFirebolt::new();
+        let settings = HashMap::from([
+            ("time_zone".to_string(), "UTC".to_string()),
+            ("max_execution_time".to_string(), "60".to_string()),
+        ]);
+        let client = mock
+            .client_factory()
+            .with_default_settings(settings.clone())
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(client.default_settings(), &settings);
+        assert!(client.parameters().is_empty());
+
+        client.query("SELECT 1").await.unwrap();
+        client
+            .set_parameter("time_zone".to_string(), "Europe/Berlin".to_string())
+            .unwrap();
+        client.query("SELECT 2").await.unwrap();
+        client
+            .system_engine_client()
+            .query("SELECT 2")
+            .await
+            .unwrap();
+        client.reset_session();
+        client.query("SELECT 3").await.unwrap();
+
+        let time_zones: Vec<_> = mock
+            .queries()
+            .iter()
+            .map(|query| query.parameters.get("time_zone").cloned())
+            .collect();
+        assert_eq!(
+            time_zones,
+            [
+                Some("UTC".to_string()),
+                Some("Europe/Berlin".to_string()),
+                Some("UTC".to_string()),
+                Some("UTC".to_string())
+            ]
+        );
+        assert!(mock
+            .queries()
+            .iter()
+            .all(|query| query.parameters.get("max_execution_time") == Some(&"60".to_string())));
+
+        let result = mock
+            .client_factory()
+            .with_default_settings(HashMap::from([(
+                "output_format".to_string(),
+                "CSV".to_string(),
+            )]))
+            .build()
+            .await;
+        assert!(matches!(result, Err(FireboltError::Configuration(_))));
+    }
+
+    #[tokio::test]
+    async fn test_with_setting() {
+        let mock = crate::testing::MockFirebolt::new();
+        let client = mock
+            .client_factory()
+            .with_setting("time_zone", "Europe/Berlin")
+            .with_unchecked_setting("experimental_feature", "1")
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(
+            client.parameters().get("time_zone"),
+            Some(&"Europe/Berlin".to_string())
+        );
+
+        client.query("SELECT 1").await.unwrap();
+        client.reset_session();
+        client.query("SELECT 2").await.unwrap();
+        let queries = mock.queries();
+        let sent = &queries[queries.len() - 2].parameters;
+        assert_eq!(sent.get("time_zone"), Some(&"Europe/Berlin".to_string()));
+        assert_eq!(sent.get("experimental_feature"), Some(&"1".to_string()));
+        assert_eq!(queries[queries.len() - 1].parameters.get("time_zone"), None);
+
+        let result = mock
+            .client_factory()
+            .with_setting("time_zome", "UTC")
+            .build()
+            .await;
+        assert!(matches!(
+            result,
+            Err(FireboltError::Configuration(message)) if message.contains("time_zome")
+        ));
+        let result = mock
+            .client_factory()
+            .with_unchecked_setting("output_format", "CSV")
+            .build()
+            .await;
+        assert!(matches!(result, Err(FireboltError::Configuration(_))));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run() {
+        let mock = crate::testing::MockFirebolt::new();
+        let client = mock
+            .client_factory()
+            .with_application_name("Orders".to_string(), "2.1.0".to_string())
+            .build()
+            .await
+            .unwrap();
+        client
+            .set_parameter("database".to_string(), "sales db".to_string())
+            .unwrap();
+        let before = mock.queries().len();
+
+        let request = client.dry_run("INSERT INTO t VALUES (1)").unwrap();
+        assert_eq!(mock.queries().len(), before);
+        assert_eq!(request.method, Method::Post);
+        assert_eq!(
+            request.url,
+            "https://engine.mock.firebolt.io/?database=sales+db&output_format=JSON_Compact"
+        );
+        assert_eq!(
+            request.body.as_deref(),
+            Some(&b"INSERT INTO t VALUES (1)"[..])
+        );
+        assert!(!request.idempotent);
+        assert_eq!(
+            request.headers[..3],
+            [
+                ("Authorization".to_string(), "Bearer <redacted>".to_string()),
+                ("User-Agent".to_string(), client.user_agent().to_string()),
+                (
+                    "Firebolt-Protocol-Version".to_string(),
+                    crate::version::PROTOCOL_VERSION.to_string()
+                ),
+            ]
+        );
+        assert!(client.dry_run("SELECT 1").unwrap().idempotent);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mock = crate::testing::MockFirebolt::new();
+        assert!(mock.client_factory().validate().is_ok());
+
+        let factory = FireboltClient::builder()
+            .with_credentials("id".to_string(), String::new())
+            .with_api_endpoint("https://api.firebolt.io?region=us".to_string())
+            .with_engine("my\"engine".to_string())
+            .with_setting("time_zome", "UTC")
+            .with_transport(mock)
+            .with_proxy("http://proxy.local:3128".to_string())
+            .with_timeout(Duration::ZERO);
+        let Err(FireboltError::Configuration(message)) = factory.validate() else {
+            panic!("expected a configuration error");
+        };
+        for problem in [
+            "7 problems: ",
+            "client_secret is required",
+            "account_name is required",
+            "Invalid API endpoint 'https://api.firebolt.io?region=us'",
+            "Invalid engine name",
+            "Unknown setting 'time_zome'",
+            "with_proxy only applies to the built-in transport",
+            "The timeout must be greater than zero",
+        ] {
+            assert!(
+                message.contains(problem),
+                "{problem} missing from {message}"
+            );
+        }
+
+        let single = FireboltClient::builder()
+            .with_credentials("id".to_string(), "secret".to_string())
+            .with_api_endpoint(crate::testing::MOCK_API_ENDPOINT.to_string())
+            .validate();
+        assert!(matches!(
+            single,
+            Err(FireboltError::Configuration(message)) if message == "account_name is required"
+        ));
+
+        let private_link = crate::testing::MockFirebolt::new()
+            .client_factory()
+            .with_api_endpoint("https://firebolt.corp.internal".to_string());
+        assert!(matches!(
+            private_link.validate(),
+            Err(FireboltError::Configuration(message)) if message.contains("with_auth_url")
+        ));
+        assert!(private_link
+            .with_auth_url("https://id.firebolt.corp.internal/oauth/token".to_string())
+            .validate()
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_with_application_name() {
+        let mock = crate::testing::MockFirebolt::new();
+        let client = mock
+            .client_factory()
+            .with_application_name("Orders".to_string(), "2.1.0".to_string())
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(
+            client.user_agent(),
+            format!("Rust SDK {} Orders/2.1.0", crate::version::VERSION)
+        );
+        assert_eq!(
+            client.system_engine_client().user_agent(),
+            client.user_agent()
+        );
+
+        let result = mock
+            .client_factory()
+            .with_application_name("Order Service".to_string(), "2.1.0".to_string())
+            .build()
+            .await;
+        assert!(matches!(result, Err(FireboltError::Configuration(_))));
+    }
+
+    #[tokio::test]
+    async fn test_build_lazy() {
+        use crate::testing::{MockFirebolt, MockResponse};
+        let mock = MockFirebolt::new();
+        mock.on_query(
+            "USE DATABASE \"sales\"",
+            MockResponse::empty().with_updated_parameters(&[("database", "sales")]),
+        );
+        mock.reject_auth(true);
+        let client = mock
+            .client_factory()
+            .with_database("sales".to_string())
+            .with_setting("time_zone", "UTC")
+            .build_lazy()
+            .unwrap();
+        assert!(!client.is_connected());
+        assert_eq!(mock.auth_requests(), 0);
+
+        let result = client.query("SELECT 1").await;
+        assert!(matches!(result, Err(FireboltError::Authentication(_))));
+        assert!(!client.is_connected());
+        assert!(mock.queries().is_empty());
+
+        mock.reject_auth(false);
+        let clone = client.clone();
+        let (first, second) = tokio::join!(clone.query("SELECT 1"), client.query("SELECT 2"));
+        first.unwrap();
+        second.unwrap();
+        assert!(client.is_connected());
+        assert_eq!(mock.auth_requests(), 2);
+        let queries = mock.queries();
+        assert_eq!(queries.len(), 3);
+        assert_eq!(queries[0].sql, "USE DATABASE \"sales\"");
+        assert_eq!(queries[2].parameters["database"], "sales");
+        assert_eq!(queries[2].parameters["time_zone"], "UTC");
+    }
+
+    #[tokio::test]
+    async fn test_build_lazy_after_close() {
+        let mock = crate::testing::MockFirebolt::new();
+        let client = mock.client_factory().build_lazy().unwrap();
+        client.close();
+        assert!(matches!(
+            client.query("SELECT 1").await,
+            Err(FireboltError::Cancelled(_))
+        ));
+        assert_eq!(mock.auth_requests(), 0);
+    }
+
+    fn mock_running_query() -> crate::testing::MockFirebolt {
+        use crate::testing::{MockFirebolt, MockResponse};
+        let mock = MockFirebolt::new();
+        mock.on_query(
+            "SELECT pg_sleep(60)",
+            MockResponse::empty().with_delay(Duration::from_secs(60)),
+        );
+        mock.on_query_containing(
+            "engine_running_queries",
+            MockResponse::rows(&[("query_id", "text")], vec![vec!["q1".into()]]),
+        );
+        mock
+    }
+
+    #[tokio::test]
+    async fn test_query_cancellable_cancels_on_server() {
+        let mock = mock_running_query();
+        let client = mock.connect().await.unwrap();
+        client
+            .set_parameter("query_label".to_string(), "shared".to_string())
+            .unwrap();
+        let cancel = CancellationToken::new();
+        let canceller = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            canceller.cancel();
+        });
+
+        let result = client
+            .query_cancellable("SELECT pg_sleep(60)", &cancel)
+            .await;
+        assert!(matches!(result, Err(FireboltError::Cancelled(_))));
+
+        let queries = mock.queries();
+        let label = queries[0].parameters.get("query_label").unwrap();
+        assert_ne!(label, "shared");
+        assert!(queries[1].sql.contains(&format!("query_label = '{label}'")));
+        assert_eq!(queries[2].sql, "CANCEL QUERY WHERE query_id = 'q1'");
+    }
+
+    #[tokio::test]
+    async fn test_query_cancellable_cancels_on_drop() {
+        let mock = mock_running_query();
+        let client = mock.connect().await.unwrap();
+        let cancel = CancellationToken::new();
+
+        let result = tokio::time::timeout(
+            Duration::from_millis(20),
+            client.query_cancellable("SELECT pg_sleep(60)", &cancel),
+        )
+        .await;
+        assert!(result.is_err());
+
+        for _ in 0..50 {
+            if mock.queries().len() == 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(mock.queries()[2].sql, "CANCEL QUERY WHERE query_id = 'q1'");
+    }
+
+    struct RecordingTransport {
+        requests: std::sync::Mutex<Vec<HttpRequest>>,
+    }
+
+    impl Transport for RecordingTransport {
+        fn send(
+            &self,
+            request: HttpRequest,
+        ) -> crate::transport::BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+            self.requests.lock().unwrap().push(request);
+            Box::pin(async {
+                Ok(HttpResponse::new(
+                    200,
+                    vec![(
+                        HEADER_UPDATE_PARAMETERS.to_string(),
+                        "database=db".to_string(),
+                    )],
+                    br#"{"meta": [{"name": "test", "type": "int"}], "data": [[7]]}"#.to_vec(),
+                ))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_uses_custom_transport() {
+        let transport = Arc::new(RecordingTransport {
+            requests: std::sync::Mutex::new(Vec::new()),
+        });
+        let mut client = create_test_client();
+        client._transport = transport.clone();
+
+        let result = client.query("SELECT 7").await.unwrap();
+
+        assert_eq!(result.rows[0].get::<i32>(0).unwrap(), 7);
+        assert_eq!(client.parameters().get("database"), Some(&"db".to_string()));
+
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, Method::Post);
+        assert_eq!(
+            requests[0].url,
+            "https://test.engine.url/?output_format=JSON_Compact"
+        );
+        assert_eq!(requests[0].body.as_deref(), Some(b"SELECT 7".as_slice()));
+        assert!(requests[0]
+            .headers
+            .contains(&("Authorization".to_string(), "Bearer test_token".to_string())));
+    }
+
+    struct SlowTransport;
+
+    impl Transport for SlowTransport {
+        fn send(
+            &self,
+            _request: HttpRequest,
+        ) -> crate::transport::BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(HttpResponse::new(200, Vec::new(), Vec::new()))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_timeout() {
+        let mut client = create_test_client();
+        client._transport = Arc::new(SlowTransport);
+        client._timeout = Some(Duration::from_millis(10));
+
+        let result = client.query("SELECT 1").await;
+
+        assert!(matches!(result, Err(FireboltError::Timeout(_))));
+    }
+
+    #[tokio::test]
+    async fn test_query_options_timeout() {
+        let mock = crate::testing::MockFirebolt::new();
+        mock.on_query(
+            "SELECT slow",
+            crate::testing::MockResponse::empty().with_delay(Duration::from_secs(5)),
+        );
+        let client = mock
+            .client_factory()
+            .with_timeout(Duration::from_millis(10))
+            .build()
+            .await
+            .unwrap();
+
+        let options = QueryOptions::new().with_timeout(Duration::from_millis(1500));
+        client
+            .query_with_options("SELECT 1", &options)
+            .await
+            .unwrap();
+        let result = client
+            .query_with_options(
+                "SELECT slow",
+                &QueryOptions::new().with_timeout(Duration::from_millis(20)),
+            )
+            .await;
+        assert!(matches!(result, Err(FireboltError::Timeout(_))));
+        client
+            .query_with_options("SELECT 2", &QueryOptions::new())
+            .await
+            .unwrap();
+
+        let timeouts: Vec<_> = mock
+            .queries()
+            .iter()
+            .map(|query| query.parameters.get("statement_timeout").cloned())
+            .collect();
+        assert_eq!(
+            timeouts,
+            [Some("1500".to_string()), Some("20".to_string()), None]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_build_timeouts() {
+        let result = FireboltClient::builder()
+            .with_credentials("id".to_string(), "secret".to_string())
+            .with_account("account".to_string())
+            .with_transport(SlowTransport)
+            .with_timeout(Duration::from_millis(10))
+            .build()
+            .await;
+        assert!(matches!(result, Err(FireboltError::Timeout(_))));
+
+        let result = FireboltClient::builder()
+            .with_credentials("id".to_string(), "secret".to_string())
+            .with_account("account".to_string())
+            .with_transport(SlowTransport)
+            .with_request_timeout(Duration::from_millis(10))
+            .build()
+            .await;
+        assert!(matches!(result, Err(FireboltError::Timeout(_))));
+    }
+
+    struct StartingEngineTransport {
+        attempts: std::sync::atomic::AtomicUsize,
+        ready_after: usize,
+    }
+
+    impl Transport for StartingEngineTransport {
+        fn send(
+            &self,
+            _request: HttpRequest,
+        ) -> crate::transport::BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+            let attempt = self
+                .attempts
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let response = if attempt < self.ready_after {
+                HttpResponse::new(
+                    503,
+                    Vec::new(),
+                    b"Engine my_engine is starting. Please retry later".to_vec(),
+                )
+            } else {
+                HttpResponse::new(
+                    200,
+                    Vec::new(),
+                    br#"{"meta": [{"name": "test", "type": "int"}], "data": [[1]]}"#.to_vec(),
+                )
+            };
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_waits_for_engine() {
+        let transport = Arc::new(StartingEngineTransport {
+            attempts: std::sync::atomic::AtomicUsize::new(0),
+            ready_after: 2,
+        });
+        let mut client = create_test_client();
+        client._transport = transport.clone();
+        client._engine_wait = Some(EngineWait {
+            timeout: Duration::from_secs(5),
+            poll_interval: Duration::from_millis(1),
+        });
+
+        let result = client.query("SELECT 1").await;
+
+        assert!(result.is_ok());
+        assert_eq!(
+            transport.attempts.load(std::sync::atomic::Ordering::SeqCst),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_engine_wait_deadline() {
+        let mut client = create_test_client();
+        client._transport = Arc::new(StartingEngineTransport {
+            attempts: std::sync::atomic::AtomicUsize::new(0),
+            ready_after: usize::MAX,
+        });
+        client._engine_wait = Some(EngineWait {
+            timeout: Duration::from_millis(20),
+            poll_interval: Duration::from_millis(5),
+        });
+
+        let result = client.query("SELECT 1").await;
+        assert!(matches!(result, Err(FireboltError::Timeout(_))));
+
+        client._engine_wait = None;
+        let result = client.query("SELECT 1").await;
+        assert!(matches!(result, Err(FireboltError::Unavailable(_))));
+    }
+
+    #[tokio::test]
+    async fn test_query_error_context() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/")
+            .with_status(400)
+            .with_body(
+                r#"{"query": {"query_id": "17EC6A1A"}, "errors": [{"description": "boom"}]}"#,
+            )
+            .create_async()
+            .await;
 
-        let (token, _expiration) = crate::auth::authenticate(
-            client_id.clone(),
-            client_secret.clone(),
-            api_endpoint.clone(),
-        )
-        .await
-        .map_err(FireboltError::Authentication)?;
+        let client = create_test_client();
+        let error = client
+            .execute_query_request(
+                &server.url(),
+                "SELECT *\n FROM users WHERE email = 'a@b.c'",
+                &HashMap::new(),
+                true,
+                true,
+            )
+            .await
+            .unwrap_err();
 
-        let engine_url = Self::get_engine_url(&account_name, &api_endpoint, &token).await?;
+        let message = error.to_string();
+        assert!(matches!(error, FireboltError::Query(_)));
+        assert!(message.contains("HTTP 400, query ID 17EC6A1A"));
+        assert!(message.ends_with("statement: SELECT * FROM users WHERE email = ?)"));
+        assert!(!message.contains("a@b.c"));
 
-        let mut client = FireboltClient {
-            _client_id: client_id,
-            _client_secret: client_secret,
-            _token: token,
-            _parameters: HashMap::new(),
-            _engine_url: engine_url,
-            _api_endpoint: api_endpoint,
-        };
+        let long = format!("SELECT {}", "x, ".repeat(100));
+        assert!(query_context(&long, None, None).ends_with("..."));
+    }
 
-        if let Some(database_name) = self.database_name {
-            let use_database_sql = format!("USE DATABASE \"{database_name}\"");
-            client.query(&use_database_sql).await.map_err(|e| {
-                FireboltError::Configuration(format!("Failed to set database: {e}"))
-            })?;
+    #[test]
+    fn test_is_read_only() {
+        for sql in [
+            "SELECT 1",
+            "  with t AS (SELECT 1) SELECT * FROM t",
+            "-- report\n/* daily */ (SELECT 1)",
+            "EXPLAIN SELECT 1",
+        ] {
+            assert!(is_read_only(sql), "{sql}");
         }
-
-        if let Some(engine_name) = self.engine_name {
-            let use_engine_sql = format!("USE ENGINE \"{engine_name}\"");
-            client
-                .query(&use_engine_sql)
-                .await
-                .map_err(|e| FireboltError::Configuration(format!("Failed to set engine: {e}")))?;
+        for sql in [
+            "INSERT INTO t SELECT 1",
+            "CREATE TABLE t (id int)",
+            "START ENGINE e",
+            "-- SELECT\nDELETE FROM t",
+            "",
+        ] {
+            assert!(!is_read_only(sql), "{sql}");
         }
+    }
 
-        Ok(client)
+    #[test]
+    fn test_is_engine_not_ready() {
+        assert!(is_engine_not_ready(&FireboltError::Query(
+            "Server error: Engine my_engine is not running".to_string()
+        )));
+        assert!(!is_engine_not_ready(&FireboltError::Query(
+            "Server error: relation \"t\" does not exist".to_string()
+        )));
+        assert!(!is_engine_not_ready(&FireboltError::Network(
+            "engine is starting".to_string()
+        )));
+        assert!(is_engine_not_ready(&FireboltError::EngineNotRunning(
+            String::new()
+        )));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[tokio::test]
+    async fn test_engine_not_running_error() {
+        let mock = crate::testing::MockFirebolt::new();
+        mock.set_default_response(crate::testing::MockResponse::error(
+            400,
+            "Engine my_engine is not running",
+        ));
+        let client = mock.connect().await.unwrap();
+        assert!(matches!(
+            client.query("SELECT 1").await,
+            Err(FireboltError::EngineNotRunning(_))
+        ));
+    }
+
+    #[test]
+    fn test_debug_redacts_secrets() {
+        let client = create_test_client();
+        let debug = format!("{client:?}");
+        assert!(debug.contains("test_id"));
+        assert!(!debug.contains("test_secret"));
+        assert!(!debug.contains("test_token"));
+    }
 
     #[tokio::test]
-    async fn test_execute_query_request_success() {
+    async fn test_execute_query_request_headers() {
         let mut server = mockito::Server::new_async().await;
         let mock = server
             .mock("POST", "/")
+            .match_header("User-Agent", crate::version::user_agent().as_str())
+            .match_header(
+                "Firebolt-Protocol-Version",
+                crate::version::PROTOCOL_VERSION,
+            )
+            .match_header("Authorization", "Bearer test_token")
             .with_status(200)
             .with_header("content-type", "application/json")
             .with_body(r#"{"meta": [{"name": "test", "type": "int"}], "data": [[1]]}"#)
             .create_async()
             .await;
 
-        let mut client = create_test_client();
-        client._engine_url = server.url();
+        let client = create_test_client();
+        client.session_mut().engine_url = server.url();
 
         let result = client
-            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true)
+            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true, true)
             .await;
 
         mock.assert_async().await;
         assert!(result.is_ok());
     }
 
-    #[tokio::test]
-    async fn test_execute_query_request_retry_on_401() {
-        let mut server = mockito::Server::new_async().await;
+    #[test]
+    fn test_factory_from_credentials_file() {
+        let path =
+            std::env::temp_dir().join(format!("firebolt-credentials-{}", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            "[dev]\nclient_id = \"id\"\nclient_secret = \"secret\"\naccount = \"acc\"\nengine = \"eng\"\n",
+        )
+        .unwrap();
 
-        let mock_401 = server
-            .mock("POST", "/")
-            .with_status(401)
-            .expect(1)
-            .create_async()
-            .await;
+        let factory = FireboltClientFactory::from_credentials_profile(&path, "dev").unwrap();
+        assert_eq!(factory.client_id.as_deref(), Some("id"));
+        assert_eq!(
+            factory.client_secret.as_ref().map(|s| s.expose_secret()),
+            Some("secret")
+        );
+        assert_eq!(factory.account_name.as_deref(), Some("acc"));
+        assert_eq!(factory.engine_name.as_deref(), Some("eng"));
+        assert_eq!(factory.database_name, None);
 
-        let _mock_success = server
-            .mock("POST", "/")
-            .with_status(200)
-            .with_header("content-type", "application/json")
-            .with_body(r#"{"meta": [{"name": "test", "type": "int"}], "data": [[1]]}"#)
-            .create_async()
-            .await;
+        let result = FireboltClientFactory::from_credentials_file(&path);
+        assert!(matches!(result, Err(FireboltError::Configuration(_))));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_factory_from_config() {
+        let config: ClientConfig = serde_json::from_value(serde_json::json!({
+            "client_id": "id",
+            "client_secret": "secret",
+            "account": "acc",
+            "database": "db",
+            "api_endpoint": "api.dev.firebolt.io",
+            "request_timeout": "10s",
+            "engine_poll_interval": "1s",
+            "retry": { "max_retries": 0 }
+        }))
+        .unwrap();
+
+        let factory = FireboltClientFactory::from_config(config);
+        assert_eq!(factory.client_id.as_deref(), Some("id"));
+        assert_eq!(
+            factory.client_secret.as_ref().map(|s| s.expose_secret()),
+            Some("secret")
+        );
+        assert_eq!(factory.account_name.as_deref(), Some("acc"));
+        assert_eq!(factory.database_name.as_deref(), Some("db"));
+        assert_eq!(factory.engine_name, None);
+        assert_eq!(factory.api_endpoint.as_deref(), Some("api.dev.firebolt.io"));
+        assert_eq!(factory.request_timeout, Some(Duration::from_secs(10)));
+        assert_eq!(factory.timeout, None);
+        assert_eq!(factory.engine_poll_interval, Duration::from_secs(1));
+        assert_eq!(factory.retry_policy.max_retries(), 0);
+    }
 
+    #[tokio::test]
+    async fn test_set_and_remove_parameter() {
+        let transport = Arc::new(RecordingTransport {
+            requests: std::sync::Mutex::new(Vec::new()),
+        });
         let mut client = create_test_client();
-        client._engine_url = server.url();
+        client._transport = transport.clone();
 
-        let result = client
-            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true)
-            .await;
+        client
+            .set_parameter("time_zone".to_string(), "UTC".to_string())
+            .unwrap();
+        assert_eq!(
+            client.parameters().get("time_zone"),
+            Some(&"UTC".to_string())
+        );
 
-        mock_401.assert_async().await;
+        client.query("SELECT 1").await.unwrap();
+        assert!(transport.requests.lock().unwrap()[0]
+            .url
+            .contains("time_zone=UTC"));
 
-        assert!(result.is_err());
+        assert_eq!(
+            client.remove_parameter("time_zone").unwrap(),
+            Some("UTC".to_string())
+        );
+        assert_eq!(client.remove_parameter("time_zone").unwrap(), None);
+
+        for key in ["", "bad key", "a&b", "output_format"] {
+            assert!(matches!(
+                client.set_parameter(key.to_string(), "x".to_string()),
+                Err(FireboltError::Configuration(_))
+            ));
+        }
         assert!(matches!(
-            result.unwrap_err(),
-            FireboltError::Authentication(_)
+            client.set_parameter("query_label".to_string(), "a\nb".to_string()),
+            Err(FireboltError::Configuration(_))
         ));
     }
 
-    #[tokio::test]
-    async fn test_execute_query_request_no_retry_on_second_401() {
-        let mut server = mockito::Server::new_async().await;
-        let mock = server
-            .mock("POST", "/")
-            .with_status(401)
-            .expect(1)
-            .create_async()
-            .await;
+    #[test]
+    fn test_reset_session_keeps_database_and_engine() {
+        let client = create_test_client();
+        for (key, value) in [("database", "db"), ("engine", "eng"), ("time_zone", "UTC")] {
+            client
+                .set_parameter(key.to_string(), value.to_string())
+                .unwrap();
+        }
+
+        client.reset_session();
 
+        let mut keys: Vec<String> = client.parameters().into_keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["database", "engine"]);
+    }
+
+    #[tokio::test]
+    async fn test_describe_query() {
+        let transport = Arc::new(RecordingTransport {
+            requests: std::sync::Mutex::new(Vec::new()),
+        });
         let mut client = create_test_client();
-        client._engine_url = server.url();
+        client._transport = transport.clone();
 
-        let result = client
-            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), false)
-            .await;
+        let columns = client
+            .describe_query("SELECT 7 AS test -- comment;\n;")
+            .await
+            .unwrap();
+        assert_eq!(columns.len(), 1);
+        assert_eq!(columns[0].name, "test");
+        assert_eq!(
+            transport.requests.lock().unwrap()[0].body.as_deref(),
+            Some(
+                b"SELECT * FROM (SELECT 7 AS test -- comment\n) AS describe_query LIMIT 0"
+                    .as_slice()
+            )
+        );
 
-        mock.assert_async().await;
-        assert!(result.is_err());
         assert!(matches!(
-            result.unwrap_err(),
-            FireboltError::Authentication(_)
+            client.describe_query(" ; ").await,
+            Err(FireboltError::Query(_))
         ));
     }
 
     #[tokio::test]
-    async fn test_execute_query_request_5xx_error() {
+    async fn test_ping() {
+        let transport = Arc::new(RecordingTransport {
+            requests: std::sync::Mutex::new(Vec::new()),
+        });
+        let mut client = create_test_client();
+        client._transport = transport.clone();
+
+        let result = client.ping().await.unwrap();
+        assert_eq!(result.engine_url, client.engine_url());
+        assert_eq!(
+            transport.requests.lock().unwrap()[0].body.as_deref(),
+            Some(b"SELECT 1".as_slice())
+        );
+
+        client._transport = Arc::new(SlowTransport);
+        let result = client.ping_with_timeout(Duration::from_millis(10)).await;
+        assert!(matches!(result, Err(FireboltError::Timeout(_))));
+    }
+
+    struct VersionTransport {
+        requests: std::sync::atomic::AtomicU32,
+    }
+
+    impl Transport for VersionTransport {
+        fn send(
+            &self,
+            _request: HttpRequest,
+        ) -> crate::transport::BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+            self.requests
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Box::pin(async {
+                Ok(HttpResponse::new(
+                    200,
+                    Vec::new(),
+                    br#"{"meta": [{"name": "version()", "type": "text"}], "data": [["4.12.3-abc"]]}"#
+                        .to_vec(),
+                ))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_server_info_is_cached() {
+        let transport = Arc::new(VersionTransport {
+            requests: std::sync::atomic::AtomicU32::new(0),
+        });
+        let mut client = create_test_client();
+        client._transport = transport.clone();
+
+        let info = client.server_info().await.unwrap().clone();
+        assert_eq!(info.version, "4.12.3-abc");
+        assert_eq!(info.protocol_version, crate::version::PROTOCOL_VERSION);
+        assert_eq!(info.version_tuple(), Some((4, 12, 3)));
+        assert!(info.version_at_least(4, 12));
+        assert!(!info.version_at_least(4, 13));
+
+        client.server_info().await.unwrap();
+        assert_eq!(
+            transport.requests.load(std::sync::atomic::Ordering::SeqCst),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_account_is_cached() {
         let mut server = mockito::Server::new_async().await;
         let mock = server
-            .mock("POST", "/")
-            .with_status(500)
-            .with_body("Internal Server Error")
+            .mock("GET", "/web/v3/account/test_account/resolve")
+            .match_header("Authorization", "Bearer test_token")
+            .with_status(200)
+            .with_body(r#"{"id": "01abc", "region": "us-east-1"}"#)
+            .expect(1)
             .create_async()
             .await;
 
         let mut client = create_test_client();
-        client._engine_url = server.url();
+        client._api_endpoint = server.url();
 
-        let result = client
-            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true)
-            .await;
+        let info = client.resolve_account().await.unwrap();
+        assert_eq!(info.id, "01abc");
+        assert_eq!(info.region, "us-east-1");
+        client.resolve_account().await.unwrap();
 
         mock.assert_async().await;
-        assert!(result.is_err());
-        let error = result.unwrap_err();
-        assert!(matches!(error, FireboltError::Query(_)));
-        assert!(format!("{error:?}").contains("Internal Server Error"));
     }
 
-    #[test]
-    fn test_client_getters() {
-        let client = create_test_client();
-        assert_eq!(client.client_id(), "test_id");
-        assert_eq!(client.client_secret(), "test_secret");
-        assert_eq!(client.api_endpoint(), "https://api.test.firebolt.io");
-        assert_eq!(client.engine_url(), "https://test.engine.url/");
-        assert!(client.parameters().is_empty());
-    }
+    #[tokio::test]
+    async fn test_resolve_account_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/web/v3/account/test_account/resolve")
+            .with_status(404)
+            .create_async()
+            .await;
 
-    #[test]
-    fn test_set_token() {
         let mut client = create_test_client();
-        client.set_token("new_token".to_string());
-        assert_eq!(client._token, "new_token".to_string());
+        client._api_endpoint = server.url();
+
+        assert!(matches!(
+            client.resolve_account().await,
+            Err(FireboltError::AccountNotFound(_))
+        ));
     }
 
     #[tokio::test]
-    async fn test_execute_query_request_headers() {
+    async fn test_switch_account() {
         let mut server = mockito::Server::new_async().await;
         let mock = server
-            .mock("POST", "/")
-            .match_header("User-Agent", crate::version::user_agent().as_str())
-            .match_header(
-                "Firebolt-Protocol-Version",
-                crate::version::PROTOCOL_VERSION,
-            )
+            .mock("GET", "/web/v3/account/other_account/engineUrl")
             .match_header("Authorization", "Bearer test_token")
             .with_status(200)
-            .with_header("content-type", "application/json")
-            .with_body(r#"{"meta": [{"name": "test", "type": "int"}], "data": [[1]]}"#)
+            .with_body(r#"{"engineUrl": "other.engine.url"}"#)
+            .create_async()
+            .await;
+        let _missing = server
+            .mock("GET", "/web/v3/account/missing/engineUrl")
+            .with_status(404)
             .create_async()
             .await;
 
         let mut client = create_test_client();
-        client._engine_url = server.url();
-
-        let result = client
-            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true)
-            .await;
+        client._api_endpoint = server.url();
+        client
+            .set_parameter("database".to_string(), "db".to_string())
+            .unwrap();
 
+        let other = client.switch_account("other_account").await.unwrap();
         mock.assert_async().await;
-        assert!(result.is_ok());
+        assert_eq!(other.account_name(), "other_account");
+        assert_eq!(other.engine_url(), "https://other.engine.url/");
+        assert!(other.is_system_engine());
+        assert!(!other.session().parameters.contains_key("database"));
+        assert_eq!(client.account_name(), "test_account");
+
+        assert!(matches!(
+            client.switch_account("missing").await,
+            Err(FireboltError::AccountNotFound(_))
+        ));
+        assert!(matches!(
+            client.switch_account("").await,
+            Err(FireboltError::Configuration(_))
+        ));
+    }
+
+    #[test]
+    fn test_system_engine_client() {
+        let client = create_test_client();
+        assert!(client.is_system_engine());
+
+        client.session_mut().engine_url = "https://user.engine.url/".to_string();
+        client
+            .set_parameter("database".to_string(), "db".to_string())
+            .unwrap();
+        client
+            .set_parameter("engine".to_string(), "my_engine".to_string())
+            .unwrap();
+        assert!(!client.is_system_engine());
+
+        let system = client.system_engine_client();
+        assert!(system.is_system_engine());
+        assert_eq!(system.engine_url(), "https://test.engine.url/");
+        assert_eq!(system.parameters().get("database"), Some(&"db".to_string()));
+        assert_eq!(system.parameters().get("engine"), None);
+        assert_eq!(system.session().token.expose_secret(), "test_token");
     }
 
     #[test]
@@ -543,11 +3681,29 @@ mod tests {
     fn create_test_client() -> FireboltClient {
         FireboltClient {
             _client_id: "test_id".to_string(),
-            _client_secret: "test_secret".to_string(),
-            _token: "test_token".to_string(),
-            _parameters: HashMap::new(),
-            _engine_url: "https://test.engine.url/".to_string(),
+            _client_secret: SecretString::from("test_secret"),
             _api_endpoint: "https://api.test.firebolt.io".to_string(),
+            _auth: AuthOptions::default(),
+            _transport: Arc::new(ReqwestTransport::new()),
+            _timeout: None,
+            _engine_wait: None,
+            _engine_url_ttl: Duration::ZERO,
+            _account_name: "test_account".to_string(),
+            _user_agent: crate::version::user_agent(),
+            _conversion_mode: ConversionMode::Strict,
+            _max_rows: None,
+            _bytes_encoding: BytesEncoding::Auto,
+            _statement_cache: None,
+            _result_cache: None,
+            _parameter_binding: ParameterBinding::Server,
+            _default_settings: HashMap::new(),
+            _hooks: Hooks::default(),
+            _shutdown: Arc::default(),
+            _pending: None,
+            _session: Session::new(
+                SecretString::from("test_token"),
+                "https://test.engine.url/".to_string(),
+            ),
         }
     }
 
@@ -578,11 +3734,12 @@ mod tests {
 
         let factory_no_id = FireboltClientFactory {
             client_id: None,
-            client_secret: Some("secret".to_string()),
+            client_secret: Some(SecretString::from("secret")),
             database_name: None,
             engine_name: None,
             account_name: Some("test_account".to_string()),
-            _api_endpoint: api_endpoint,
+            api_endpoint: Some(api_endpoint),
+            ..FireboltClientFactory::new()
         };
 
         let result = factory_no_id.build().await;
@@ -627,7 +3784,8 @@ mod tests {
             database_name: None,
             engine_name: None,
             account_name: Some("test_account".to_string()),
-            _api_endpoint: api_endpoint,
+            api_endpoint: Some(api_endpoint),
+            ..FireboltClientFactory::new()
         };
 
         let result = factory_no_secret.build().await;
@@ -668,11 +3826,12 @@ mod tests {
 
         let factory_no_account = FireboltClientFactory {
             client_id: Some("client_id".to_string()),
-            client_secret: Some("secret".to_string()),
+            client_secret: Some(SecretString::from("secret")),
             database_name: None,
             engine_name: None,
             account_name: None,
-            _api_endpoint: api_endpoint,
+            api_endpoint: Some(api_endpoint),
+            ..FireboltClientFactory::new()
         };
 
         let result = factory_no_account.build().await;
@@ -692,11 +3851,12 @@ mod tests {
 
         let factory = FireboltClientFactory {
             client_id: Some("test_client_id".to_string()),
-            client_secret: Some("test_client_secret".to_string()),
+            client_secret: Some(SecretString::from("test_client_secret")),
             database_name: None,
             engine_name: None,
             account_name: Some("test_account".to_string()),
-            _api_endpoint: "https://api.test.firebolt.io".to_string(),
+            api_endpoint: Some("https://api.test.firebolt.io".to_string()),
+            ..FireboltClientFactory::new()
         };
 
         let result = factory.build().await;
@@ -716,11 +3876,12 @@ mod tests {
 
         let factory = FireboltClientFactory {
             client_id: Some("test_client_id".to_string()),
-            client_secret: Some("test_client_secret".to_string()),
+            client_secret: Some(SecretString::from("test_client_secret")),
             database_name: None,
             engine_name: None,
             account_name: Some("nonexistent_account".to_string()),
-            _api_endpoint: "https://api.test.firebolt.io".to_string(),
+            api_endpoint: Some("https://api.test.firebolt.io".to_string()),
+            ..FireboltClientFactory::new()
         };
 
         let result = factory.build().await;
@@ -740,11 +3901,12 @@ mod tests {
 
         let factory = FireboltClientFactory {
             client_id: Some("test_client_id".to_string()),
-            client_secret: Some("test_client_secret".to_string()),
+            client_secret: Some(SecretString::from("test_client_secret")),
             database_name: None,
             engine_name: None,
             account_name: Some("test_account".to_string()),
-            _api_endpoint: "https://api.test.firebolt.io".to_string(),
+            api_endpoint: Some("https://api.test.firebolt.io".to_string()),
+            ..FireboltClientFactory::new()
         };
 
         let result = factory.build().await;
@@ -803,17 +3965,24 @@ mod tests {
     #[tokio::test]
     async fn test_get_engine_url_success() {
         let mut server = mockito::Server::new_async().await;
+        let user_agent = crate::version::user_agent_with_application("Orders", "2.1.0");
         let mock = server
             .mock("GET", "/web/v3/account/test_account/engineUrl")
+            .match_header("User-Agent", user_agent.as_str())
             .with_status(200)
             .with_header("content-type", "application/json")
             .with_body(r#"{"engineUrl": "engine.test.firebolt.io"}"#)
             .create_async()
             .await;
 
-        let result =
-            FireboltClientFactory::get_engine_url("test_account", &server.url(), "test_token")
-                .await;
+        let result = FireboltClientFactory::get_engine_url(
+            &ReqwestTransport::new(),
+            "test_account",
+            &server.url(),
+            "test_token",
+            &user_agent,
+        )
+        .await;
 
         mock.assert_async().await;
         assert!(result.is_ok());
@@ -831,14 +4000,20 @@ mod tests {
             .create_async()
             .await;
 
-        let result =
-            FireboltClientFactory::get_engine_url("nonexistent", &server.url(), "test_token").await;
+        let result = FireboltClientFactory::get_engine_url(
+            &ReqwestTransport::new(),
+            "nonexistent",
+            &server.url(),
+            "test_token",
+            &crate::version::user_agent(),
+        )
+        .await;
 
         mock.assert_async().await;
         assert!(result.is_err());
         assert!(matches!(
             result.unwrap_err(),
-            FireboltError::Configuration(_)
+            FireboltError::AccountNotFound(_)
         ));
     }
 
@@ -852,13 +4027,21 @@ mod tests {
             .create_async()
             .await;
 
-        let result =
-            FireboltClientFactory::get_engine_url("test_account", &server.url(), "test_token")
-                .await;
+        let result = FireboltClientFactory::get_engine_url(
+            &ReqwestTransport::new(),
+            "test_account",
+            &server.url(),
+            "test_token",
+            &crate::version::user_agent(),
+        )
+        .await;
 
         mock.assert_async().await;
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), FireboltError::Query(_)));
+        assert!(matches!(
+            result.unwrap_err(),
+            FireboltError::EngineResolution(_)
+        ));
     }
 
     #[tokio::test]
@@ -872,13 +4055,21 @@ mod tests {
             .create_async()
             .await;
 
-        let result =
-            FireboltClientFactory::get_engine_url("test_account", &server.url(), "test_token")
-                .await;
+        let result = FireboltClientFactory::get_engine_url(
+            &ReqwestTransport::new(),
+            "test_account",
+            &server.url(),
+            "test_token",
+            &crate::version::user_agent(),
+        )
+        .await;
 
         mock.assert_async().await;
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), FireboltError::Query(_)));
+        assert!(matches!(
+            result.unwrap_err(),
+            FireboltError::EngineResolution(_)
+        ));
     }
 
     #[tokio::test]
@@ -892,13 +4083,63 @@ mod tests {
             .create_async()
             .await;
 
-        let result =
-            FireboltClientFactory::get_engine_url("test_account", &server.url(), "test_token")
-                .await;
+        let result = FireboltClientFactory::get_engine_url(
+            &ReqwestTransport::new(),
+            "test_account",
+            &server.url(),
+            "test_token",
+            &crate::version::user_agent(),
+        )
+        .await;
 
         mock.assert_async().await;
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), FireboltError::Query(_)));
+        assert!(matches!(
+            result.unwrap_err(),
+            FireboltError::EngineResolution(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_host_aliases() {
+        let mut server = mockito::Server::new_async().await;
+        let auth = server
+            .mock("POST", "/oauth/token")
+            .with_body(r#"{"access_token": "token", "expires_in": 3600}"#)
+            .create_async()
+            .await;
+        let lookup = server
+            .mock("GET", "/web/v3/account/acme/engineUrl")
+            .with_body(r#"{"engineUrl": "engine.corp.firebolt.io"}"#)
+            .create_async()
+            .await;
+        let query = server
+            .mock("POST", "/")
+            .match_query(mockito::Matcher::Any)
+            .with_header(
+                HEADER_UPDATE_ENDPOINT,
+                "https://engine.corp.internal:8443/e1",
+            )
+            .with_body(r#"{"meta": [{"name": "one", "type": "int"}], "data": [[1]]}"#)
+            .create_async()
+            .await;
+
+        let client = FireboltClient::builder()
+            .with_credentials(uuid::Uuid::new_v4().to_string(), "secret".to_string())
+            .with_account("acme".to_string())
+            .with_api_endpoint("api.corp.firebolt.io".to_string())
+            .with_host_alias("api.corp.firebolt.io", server.url())
+            .with_host_alias("ID.corp.firebolt.io", server.url())
+            .with_host_alias("engine.corp.firebolt.io", server.url())
+            .build()
+            .await
+            .unwrap();
+        client.query("SELECT 1 AS one").await.unwrap();
+
+        auth.assert_async().await;
+        lookup.assert_async().await;
+        query.assert_async().await;
+        assert_eq!(client.engine_url(), "https://engine.corp.internal:8443/e1");
     }
 
     #[tokio::test]
@@ -916,22 +4157,22 @@ mod tests {
             .create_async()
             .await;
 
-        let mut client = create_test_client();
-        client._engine_url = server.url();
+        let client = create_test_client();
+        client.session_mut().engine_url = server.url();
 
         let result = client
-            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true)
+            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true, true)
             .await;
 
         mock.assert_async().await;
         assert!(result.is_ok());
-        assert_eq!(client._engine_url, "https://new.engine.url/path");
+        assert_eq!(client.engine_url(), "https://new.engine.url/path");
         assert_eq!(
-            client._parameters.get("param1"),
+            client.parameters().get("param1"),
             Some(&"value1".to_string())
         );
         assert_eq!(
-            client._parameters.get("param2"),
+            client.parameters().get("param2"),
             Some(&"value2".to_string())
         );
     }
@@ -951,24 +4192,27 @@ mod tests {
             .create_async()
             .await;
 
-        let mut client = create_test_client();
-        client._engine_url = server.url();
+        let client = create_test_client();
+        client.session_mut().engine_url = server.url();
 
         let result = client
-            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true)
+            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true, true)
             .await;
 
         mock.assert_async().await;
         assert!(result.is_ok());
         assert_eq!(
-            client._parameters.get("database"),
+            client.parameters().get("database"),
             Some(&"new_db".to_string())
         );
         assert_eq!(
-            client._parameters.get("engine"),
+            client.parameters().get("engine"),
             Some(&"new_engine".to_string())
         );
-        assert_eq!(client._parameters.get("custom"), Some(&"value".to_string()));
+        assert_eq!(
+            client.parameters().get("custom"),
+            Some(&"value".to_string())
+        );
     }
 
     #[tokio::test]
@@ -983,34 +4227,37 @@ mod tests {
             .create_async()
             .await;
 
-        let mut client = create_test_client();
-        client._engine_url = server.url();
+        let client = create_test_client();
+        client.session_mut().engine_url = server.url();
         client
-            ._parameters
+            .session_mut()
+            .parameters
             .insert("database".to_string(), "test_db".to_string());
         client
-            ._parameters
+            .session_mut()
+            .parameters
             .insert("engine".to_string(), "test_engine".to_string());
         client
-            ._parameters
+            .session_mut()
+            .parameters
             .insert("custom_param".to_string(), "custom_value".to_string());
 
         let result = client
-            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true)
+            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true, true)
             .await;
 
         mock.assert_async().await;
         assert!(result.is_ok());
         assert_eq!(
-            client._parameters.get("database"),
+            client.parameters().get("database"),
             Some(&"test_db".to_string())
         );
         assert_eq!(
-            client._parameters.get("engine"),
+            client.parameters().get("engine"),
             Some(&"test_engine".to_string())
         );
-        assert_eq!(client._parameters.get("custom_param"), None);
-        assert_eq!(client._parameters.len(), 2);
+        assert_eq!(client.parameters().get("custom_param"), None);
+        assert_eq!(client.parameters().len(), 2);
     }
 
     #[tokio::test]
@@ -1025,31 +4272,34 @@ mod tests {
             .create_async()
             .await;
 
-        let mut client = create_test_client();
-        client._engine_url = server.url();
+        let client = create_test_client();
+        client.session_mut().engine_url = server.url();
         client
-            ._parameters
+            .session_mut()
+            .parameters
             .insert("param1".to_string(), "value1".to_string());
         client
-            ._parameters
+            .session_mut()
+            .parameters
             .insert("param2".to_string(), "value2".to_string());
         client
-            ._parameters
+            .session_mut()
+            .parameters
             .insert("param3".to_string(), "value3".to_string());
 
         let result = client
-            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true)
+            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true, true)
             .await;
 
         mock.assert_async().await;
         assert!(result.is_ok());
-        assert_eq!(client._parameters.get("param1"), None);
+        assert_eq!(client.parameters().get("param1"), None);
         assert_eq!(
-            client._parameters.get("param2"),
+            client.parameters().get("param2"),
             Some(&"value2".to_string())
         );
-        assert_eq!(client._parameters.get("param3"), None);
-        assert_eq!(client._parameters.len(), 1);
+        assert_eq!(client.parameters().get("param3"), None);
+        assert_eq!(client.parameters().len(), 1);
     }
 
     #[tokio::test]
@@ -1064,11 +4314,11 @@ mod tests {
             .create_async()
             .await;
 
-        let mut client = create_test_client();
-        client._engine_url = server.url();
+        let client = create_test_client();
+        client.session_mut().engine_url = server.url();
 
         let result = client
-            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true)
+            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true, true)
             .await;
 
         mock.assert_async().await;
@@ -1091,11 +4341,11 @@ mod tests {
             .create_async()
             .await;
 
-        let mut client = create_test_client();
-        client._engine_url = server.url();
+        let client = create_test_client();
+        client.session_mut().engine_url = server.url();
 
         let result = client
-            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true)
+            .execute_query_request(&server.url(), "SELECT 1", &HashMap::new(), true, true)
             .await;
 
         mock.assert_async().await;
@@ -0,0 +1,433 @@
+use crate::error::FireboltError;
+use crate::retry::RetryPolicy;
+use secrecy::SecretString;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    pub client_id: Option<String>,
+    #[serde(deserialize_with = "deserialize_secret")]
+    pub client_secret: Option<SecretString>,
+    #[serde(alias = "account_name")]
+    pub account: Option<String>,
+    #[serde(alias = "database_name")]
+    pub database: Option<String>,
+    #[serde(alias = "engine_name")]
+    pub engine: Option<String>,
+    #[serde(alias = "endpoint")]
+    pub api_endpoint: Option<String>,
+    pub auth_url: Option<String>,
+    pub audience: Option<String>,
+    pub scopes: Vec<String>,
+    pub host_aliases: HashMap<String, String>,
+    #[serde(deserialize_with = "deserialize_optional_duration")]
+    pub connect_timeout: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_optional_duration")]
+    pub request_timeout: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_optional_duration")]
+    pub timeout: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_optional_duration")]
+    pub engine_wait_timeout: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_optional_duration")]
+    pub engine_poll_interval: Option<Duration>,
+    pub retry: Option<RetryPolicy>,
+}
+
+fn deserialize_secret<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<SecretString>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.map(SecretString::from))
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawDuration {
+    Seconds(f64),
+    Text(String),
+}
+
+// Durations are written either as a number of seconds (`30`, `0.5`) or as a
+// number with a unit suffix (`500ms`, `30s`, `5m`, `1h`).
+pub(crate) fn deserialize_duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Duration, D::Error> {
+    let seconds = match RawDuration::deserialize(deserializer)? {
+        RawDuration::Seconds(seconds) => seconds,
+        RawDuration::Text(text) => {
+            parse_duration_seconds(&text).map_err(serde::de::Error::custom)?
+        }
+    };
+    Duration::try_from_secs_f64(seconds).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_optional_duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(deserialize_with = "deserialize_duration")] Duration);
+
+    Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(duration)| duration))
+}
+
+fn parse_duration_seconds(text: &str) -> Result<f64, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{text}'"))?;
+    let scale = match unit.trim() {
+        "ms" => 0.001,
+        "" | "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        other => return Err(format!("unknown duration unit '{other}' in '{text}'")),
+    };
+    Ok(number * scale)
+}
+
+pub fn default_credentials_path() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(PathBuf::from(home).join(".firebolt").join("credentials"))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Credentials {
+    pub(crate) client_id: Option<String>,
+    pub(crate) client_secret: Option<String>,
+    pub(crate) account_name: Option<String>,
+    pub(crate) database_name: Option<String>,
+    pub(crate) engine_name: Option<String>,
+}
+
+pub(crate) fn read_credentials_file(
+    path: &Path,
+    profile: &str,
+) -> Result<Credentials, FireboltError> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        FireboltError::Configuration(format!(
+            "Failed to read credentials file {}: {e}",
+            path.display()
+        ))
+    })?;
+    parse_credentials(&contents, profile).map_err(|msg| {
+        FireboltError::Configuration(format!(
+            "Invalid credentials file {}: {msg}",
+            path.display()
+        ))
+    })
+}
+
+pub const ENV_CLIENT_ID: &str = "FIREBOLT_CLIENT_ID";
+pub const ENV_CLIENT_SECRET: &str = "FIREBOLT_CLIENT_SECRET";
+pub const ENV_ACCOUNT: &str = "FIREBOLT_ACCOUNT";
+pub const ENV_DATABASE: &str = "FIREBOLT_DATABASE";
+pub const ENV_ENGINE: &str = "FIREBOLT_ENGINE";
+
+pub(crate) fn credentials_from_env() -> Result<Credentials, FireboltError> {
+    credentials_from_lookup(|name| std::env::var(name).ok())
+}
+
+fn credentials_from_lookup(
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Credentials, FireboltError> {
+    let read = |name: &str| lookup(name).filter(|value| !value.trim().is_empty());
+
+    let credentials = Credentials {
+        client_id: read(ENV_CLIENT_ID),
+        client_secret: read(ENV_CLIENT_SECRET),
+        account_name: read(ENV_ACCOUNT),
+        database_name: read(ENV_DATABASE),
+        engine_name: read(ENV_ENGINE),
+    };
+
+    let missing: Vec<&str> = [
+        (ENV_CLIENT_ID, credentials.client_id.is_none()),
+        (ENV_CLIENT_SECRET, credentials.client_secret.is_none()),
+        (ENV_ACCOUNT, credentials.account_name.is_none()),
+    ]
+    .into_iter()
+    .filter_map(|(name, is_missing)| is_missing.then_some(name))
+    .collect();
+
+    if !missing.is_empty() {
+        return Err(FireboltError::Configuration(format!(
+            "Missing required environment variables: {}",
+            missing.join(", ")
+        )));
+    }
+    Ok(credentials)
+}
+
+// Parses `firebolt://<client_id>:<client_secret>@<account>[/<database>][?engine=<engine>]`,
+// the connection URL used by the sqlx and Diesel integrations.
+#[cfg(any(feature = "sqlx", feature = "diesel"))]
+pub(crate) fn credentials_from_url(url: &url::Url) -> Result<Credentials, FireboltError> {
+    if url.scheme() != "firebolt" {
+        return Err(FireboltError::Configuration(format!(
+            "Unsupported URL scheme '{}'",
+            url.scheme()
+        )));
+    }
+
+    let decode = |component: &str| {
+        percent_encoding::percent_decode_str(component)
+            .decode_utf8()
+            .map(|decoded| decoded.into_owned())
+            .map_err(|e| FireboltError::Configuration(format!("Invalid URL encoding: {e}")))
+    };
+
+    let client_id = decode(url.username())?;
+    let client_secret = decode(url.password().unwrap_or(""))?;
+    if client_id.is_empty() || client_secret.is_empty() {
+        return Err(FireboltError::Configuration(
+            "client_id and client_secret are required in the URL".to_string(),
+        ));
+    }
+
+    let account = url
+        .host_str()
+        .filter(|host| !host.is_empty())
+        .ok_or_else(|| {
+            FireboltError::Configuration("account name is required in the URL".to_string())
+        })?
+        .to_string();
+
+    let mut credentials = Credentials {
+        client_id: Some(client_id),
+        client_secret: Some(client_secret),
+        account_name: Some(account),
+        ..Credentials::default()
+    };
+
+    let database = url.path().trim_start_matches('/');
+    if !database.is_empty() {
+        credentials.database_name = Some(decode(database)?);
+    }
+
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "engine" => credentials.engine_name = Some(value.into_owned()),
+            "database" => credentials.database_name = Some(value.into_owned()),
+            _ => {
+                return Err(FireboltError::Configuration(format!(
+                    "Unknown connection parameter '{key}'"
+                )))
+            }
+        }
+    }
+
+    Ok(credentials)
+}
+
+// Accepts the common subset of TOML and INI: `[profile]` sections with
+// `key = value` pairs, optionally quoted. Keys before the first section
+// belong to the default profile.
+fn parse_credentials(contents: &str, profile: &str) -> Result<Credentials, String> {
+    let mut sections: HashMap<String, Vec<(usize, String, String)>> = HashMap::new();
+    let mut current = DEFAULT_PROFILE.to_string();
+
+    for (index, raw_line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[') {
+            let name = name
+                .strip_suffix(']')
+                .ok_or_else(|| format!("line {line_number}: unterminated section header"))?;
+            current = unquote(name.trim()).to_string();
+            sections.entry(current.clone()).or_default();
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .or_else(|| line.split_once(':'))
+            .ok_or_else(|| format!("line {line_number}: expected 'key = value'"))?;
+        sections.entry(current.clone()).or_default().push((
+            line_number,
+            key.trim().to_string(),
+            unquote(value.trim()).to_string(),
+        ));
+    }
+
+    let entries = sections
+        .remove(profile)
+        .ok_or_else(|| format!("profile '{profile}' not found"))?;
+
+    let mut credentials = Credentials::default();
+    for (line_number, key, value) in entries {
+        let slot = match key.as_str() {
+            "client_id" => &mut credentials.client_id,
+            "client_secret" => &mut credentials.client_secret,
+            "account" | "account_name" => &mut credentials.account_name,
+            "database" | "database_name" => &mut credentials.database_name,
+            "engine" | "engine_name" => &mut credentials.engine_name,
+            other => return Err(format!("line {line_number}: unknown key '{other}'")),
+        };
+        *slot = Some(value);
+    }
+    Ok(credentials)
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+# Firebolt credentials
+client_id = "default_id"
+client_secret = "default_secret"
+account = "default_account"
+
+[staging]
+client_id = 'staging_id'
+client_secret = staging_secret
+account_name = staging_account
+database = staging_db
+engine = "staging_engine"
+"#;
+
+    #[test]
+    fn test_parse_credentials_profiles() {
+        let default = parse_credentials(FILE, DEFAULT_PROFILE).unwrap();
+        assert_eq!(default.client_id.as_deref(), Some("default_id"));
+        assert_eq!(default.client_secret.as_deref(), Some("default_secret"));
+        assert_eq!(default.account_name.as_deref(), Some("default_account"));
+        assert_eq!(default.database_name, None);
+
+        let staging = parse_credentials(FILE, "staging").unwrap();
+        assert_eq!(staging.client_id.as_deref(), Some("staging_id"));
+        assert_eq!(staging.client_secret.as_deref(), Some("staging_secret"));
+        assert_eq!(staging.account_name.as_deref(), Some("staging_account"));
+        assert_eq!(staging.database_name.as_deref(), Some("staging_db"));
+        assert_eq!(staging.engine_name.as_deref(), Some("staging_engine"));
+    }
+
+    #[test]
+    fn test_parse_credentials_errors() {
+        assert!(parse_credentials(FILE, "missing")
+            .unwrap_err()
+            .contains("profile 'missing' not found"));
+        assert!(
+            parse_credentials("[default]\nclient_key = x", DEFAULT_PROFILE)
+                .unwrap_err()
+                .contains("unknown key 'client_key'")
+        );
+        assert!(parse_credentials("[default\n", DEFAULT_PROFILE).is_err());
+        assert!(parse_credentials("client_id", DEFAULT_PROFILE).is_err());
+    }
+
+    #[test]
+    fn test_credentials_from_lookup() {
+        let vars: HashMap<&str, &str> = [
+            (ENV_CLIENT_ID, "id"),
+            (ENV_CLIENT_SECRET, "secret"),
+            (ENV_ACCOUNT, "account"),
+            (ENV_ENGINE, "engine"),
+        ]
+        .into_iter()
+        .collect();
+        let credentials = credentials_from_lookup(|name| vars.get(name).map(|v| v.to_string()));
+        let credentials = credentials.unwrap();
+        assert_eq!(credentials.client_id.as_deref(), Some("id"));
+        assert_eq!(credentials.account_name.as_deref(), Some("account"));
+        assert_eq!(credentials.engine_name.as_deref(), Some("engine"));
+        assert_eq!(credentials.database_name, None);
+
+        let result = credentials_from_lookup(|name| match name {
+            ENV_CLIENT_ID => Some("id".to_string()),
+            ENV_CLIENT_SECRET => Some("  ".to_string()),
+            _ => None,
+        });
+        match result {
+            Err(FireboltError::Configuration(msg)) => assert_eq!(
+                msg,
+                "Missing required environment variables: FIREBOLT_CLIENT_SECRET, FIREBOLT_ACCOUNT"
+            ),
+            other => panic!("Expected configuration error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_client_config_deserialize() {
+        let json = serde_json::json!({
+            "client_id": "id",
+            "client_secret": "secret",
+            "account_name": "account",
+            "engine": "engine",
+            "endpoint": "api.dev.firebolt.io",
+            "scopes": ["read"],
+            "connect_timeout": 5,
+            "request_timeout": "1.5s",
+            "engine_wait_timeout": "2m",
+            "engine_poll_interval": "500ms",
+            "retry": { "max_retries": 5, "initial_backoff": "100ms" }
+        });
+        let config: ClientConfig = serde_json::from_value(json).unwrap();
+
+        assert_eq!(config.client_id.as_deref(), Some("id"));
+        assert_eq!(config.account.as_deref(), Some("account"));
+        assert_eq!(config.database, None);
+        assert_eq!(config.api_endpoint.as_deref(), Some("api.dev.firebolt.io"));
+        assert_eq!(config.audience, None);
+        assert_eq!(config.scopes, ["read"]);
+        assert_eq!(config.connect_timeout, Some(Duration::from_secs(5)));
+        assert_eq!(config.request_timeout, Some(Duration::from_millis(1500)));
+        assert_eq!(config.timeout, None);
+        assert_eq!(config.engine_wait_timeout, Some(Duration::from_secs(120)));
+        assert_eq!(
+            config.engine_poll_interval,
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            config.retry,
+            Some(
+                RetryPolicy::new()
+                    .with_max_retries(5)
+                    .with_initial_backoff(Duration::from_millis(100))
+            )
+        );
+        assert!(!format!("{config:?}").contains("\"secret\""));
+    }
+
+    #[test]
+    fn test_client_config_rejects_invalid_values() {
+        for json in [
+            serde_json::json!({ "request_timeout": "5 weeks" }),
+            serde_json::json!({ "request_timeout": -1 }),
+            serde_json::json!({ "unknown": true }),
+        ] {
+            assert!(serde_json::from_value::<ClientConfig>(json).is_err());
+        }
+    }
+
+    #[test]
+    fn test_read_credentials_file_missing() {
+        let result = read_credentials_file(Path::new("/nonexistent/credentials"), DEFAULT_PROFILE);
+        assert!(matches!(result, Err(FireboltError::Configuration(_))));
+    }
+}
@@ -0,0 +1,301 @@
+use crate::client::FireboltClient;
+use crate::error::FireboltError;
+use crate::sql::quote;
+use secrecy::{ExposeSecret, SecretString};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Tsv,
+    Json,
+    Parquet,
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Csv => f.write_str("CSV"),
+            ExportFormat::Tsv => f.write_str("TSV"),
+            ExportFormat::Json => f.write_str("JSON"),
+            ExportFormat::Parquet => f.write_str("PARQUET"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    None,
+    #[default]
+    Gzip,
+    Snappy,
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compression::None => f.write_str("NONE"),
+            Compression::Gzip => f.write_str("GZIP"),
+            Compression::Snappy => f.write_str("SNAPPY"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum StorageCredentials {
+    AccessKey {
+        key_id: String,
+        secret_key: SecretString,
+        session_token: Option<SecretString>,
+    },
+    Role {
+        arn: String,
+        external_id: Option<String>,
+    },
+}
+
+impl StorageCredentials {
+    fn to_sql(&self) -> String {
+        match self {
+            StorageCredentials::AccessKey {
+                key_id,
+                secret_key,
+                session_token,
+            } => {
+                let mut sql = format!(
+                    "AWS_ACCESS_KEY_ID = {} AWS_SECRET_ACCESS_KEY = {}",
+                    quote(key_id),
+                    quote(secret_key.expose_secret())
+                );
+                if let Some(token) = session_token {
+                    sql.push_str(&format!(
+                        " AWS_SESSION_TOKEN = {}",
+                        quote(token.expose_secret())
+                    ));
+                }
+                sql
+            }
+            StorageCredentials::Role { arn, external_id } => {
+                let mut sql = format!("AWS_ROLE_ARN = {}", quote(arn));
+                if let Some(external_id) = external_id {
+                    sql.push_str(&format!(" AWS_ROLE_EXTERNAL_ID = {}", quote(external_id)));
+                }
+                sql
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CopyToOptions {
+    format: ExportFormat,
+    compression: Option<Compression>,
+    credentials: Option<StorageCredentials>,
+    file_name_prefix: Option<String>,
+    include_query_id_in_file_name: Option<bool>,
+    single_file: bool,
+    max_file_size: Option<u64>,
+    overwrite_existing_files: bool,
+}
+
+impl CopyToOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_format(mut self, format: ExportFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    pub fn with_credentials(mut self, credentials: StorageCredentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    pub fn with_file_name_prefix(mut self, prefix: &str) -> Self {
+        self.file_name_prefix = Some(prefix.to_string());
+        self
+    }
+
+    pub fn with_query_id_in_file_name(mut self, include: bool) -> Self {
+        self.include_query_id_in_file_name = Some(include);
+        self
+    }
+
+    pub fn with_single_file(mut self, single_file: bool) -> Self {
+        self.single_file = single_file;
+        self
+    }
+
+    pub fn with_max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    pub fn with_overwrite_existing_files(mut self, overwrite: bool) -> Self {
+        self.overwrite_existing_files = overwrite;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CopyToSummary {
+    pub location: String,
+    pub format: ExportFormat,
+    pub compression: Compression,
+    pub elapsed: Duration,
+}
+
+// Renders `COPY (<query>) TO '<location>'`. TYPE is always written; other
+// options are only written when set, leaving the rest to server defaults.
+pub(crate) fn copy_to_statement(
+    query: &str,
+    location: &str,
+    options: &CopyToOptions,
+) -> Result<String, FireboltError> {
+    let query = query
+        .trim_start()
+        .trim_end_matches(|c: char| c == ';' || c.is_whitespace());
+    if query.is_empty() {
+        return Err(FireboltError::Query(
+            "Cannot export an empty query".to_string(),
+        ));
+    }
+    if location.trim().is_empty() {
+        return Err(FireboltError::Configuration(
+            "Export location must not be empty".to_string(),
+        ));
+    }
+
+    let mut sql = format!("COPY ({query}\n) TO {}", quote(location));
+    if let Some(credentials) = &options.credentials {
+        sql.push_str(&format!(" CREDENTIALS = ({})", credentials.to_sql()));
+    }
+    sql.push_str(&format!(" TYPE = {}", options.format));
+    if let Some(compression) = options.compression {
+        sql.push_str(&format!(" COMPRESSION = {compression}"));
+    }
+    if let Some(include) = options.include_query_id_in_file_name {
+        sql.push_str(&format!(
+            " INCLUDE_QUERY_ID_IN_FILE_NAME = {}",
+            bool_literal(include)
+        ));
+    }
+    if let Some(prefix) = &options.file_name_prefix {
+        sql.push_str(&format!(" FILE_NAME_PREFIX = {}", quote(prefix)));
+    }
+    if options.single_file {
+        sql.push_str(" SINGLE_FILE = TRUE");
+    }
+    if let Some(bytes) = options.max_file_size {
+        sql.push_str(&format!(" MAX_FILE_SIZE = {bytes}"));
+    }
+    if options.overwrite_existing_files {
+        sql.push_str(" OVERWRITE_EXISTING_FILES = TRUE");
+    }
+    Ok(sql)
+}
+
+fn bool_literal(value: bool) -> &'static str {
+    if value {
+        "TRUE"
+    } else {
+        "FALSE"
+    }
+}
+
+impl FireboltClient {
+    pub async fn copy_to(
+        &self,
+        query: &str,
+        location: &str,
+        options: CopyToOptions,
+    ) -> Result<CopyToSummary, FireboltError> {
+        let sql = copy_to_statement(query, location, &options)?;
+        let started = Instant::now();
+        self.query(&sql).await?;
+        Ok(CopyToSummary {
+            location: location.to_string(),
+            format: options.format,
+            compression: options.compression.unwrap_or_default(),
+            elapsed: started.elapsed(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_to_statement_defaults() {
+        let sql = copy_to_statement(
+            "SELECT * FROM t;",
+            "s3://bucket/out/",
+            &CopyToOptions::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "COPY (SELECT * FROM t\n) TO 's3://bucket/out/' TYPE = CSV"
+        );
+    }
+
+    #[test]
+    fn test_copy_to_statement_options() {
+        let options = CopyToOptions::new()
+            .with_format(ExportFormat::Parquet)
+            .with_compression(Compression::Snappy)
+            .with_credentials(StorageCredentials::Role {
+                arn: "arn:aws:iam::1:role/r".to_string(),
+                external_id: Some("ext".to_string()),
+            })
+            .with_file_name_prefix("daily_")
+            .with_query_id_in_file_name(false)
+            .with_single_file(true)
+            .with_max_file_size(1024)
+            .with_overwrite_existing_files(true);
+        let sql = copy_to_statement("SELECT 1", "s3://b/p/", &options).unwrap();
+        assert_eq!(
+            sql,
+            "COPY (SELECT 1\n) TO 's3://b/p/' \
+             CREDENTIALS = (AWS_ROLE_ARN = 'arn:aws:iam::1:role/r' AWS_ROLE_EXTERNAL_ID = 'ext') \
+             TYPE = PARQUET COMPRESSION = SNAPPY INCLUDE_QUERY_ID_IN_FILE_NAME = FALSE \
+             FILE_NAME_PREFIX = 'daily_' SINGLE_FILE = TRUE MAX_FILE_SIZE = 1024 \
+             OVERWRITE_EXISTING_FILES = TRUE"
+        );
+    }
+
+    #[test]
+    fn test_copy_to_statement_validation() {
+        assert!(matches!(
+            copy_to_statement(" ; ", "s3://b/", &CopyToOptions::new()),
+            Err(FireboltError::Query(_))
+        ));
+        assert!(matches!(
+            copy_to_statement("SELECT 1", "", &CopyToOptions::new()),
+            Err(FireboltError::Configuration(_))
+        ));
+
+        let credentials = StorageCredentials::AccessKey {
+            key_id: "AKIA".to_string(),
+            secret_key: SecretString::from("s'cret"),
+            session_token: None,
+        };
+        let sql = copy_to_statement(
+            "SELECT 1",
+            "s3://b/",
+            &CopyToOptions::new().with_credentials(credentials),
+        )
+        .unwrap();
+        assert!(sql.contains("AWS_ACCESS_KEY_ID = 'AKIA' AWS_SECRET_ACCESS_KEY = 's''cret'"));
+    }
+}
@@ -0,0 +1,232 @@
+use crate::error::FireboltError;
+use crate::result::{ResultSet, Row};
+use crate::types::{Column, Type};
+use chrono::{NaiveDate, NaiveDateTime};
+use polars::prelude::{DataFrame, IntoColumn, NamedFrom, Series, TimeUnit, TimeZone};
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+impl ResultSet {
+    pub fn to_dataframe(&self) -> Result<DataFrame, FireboltError> {
+        rows_to_dataframe(&self.columns, &self.rows)
+    }
+}
+
+pub fn rows_to_dataframe<'a>(
+    columns: &[Column],
+    rows: impl IntoIterator<Item = &'a Row>,
+) -> Result<DataFrame, FireboltError> {
+    let rows: Vec<&Row> = rows.into_iter().collect();
+
+    let series = columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| column_to_series(column, index, &rows).map(IntoColumn::into_column))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    DataFrame::new(rows.len(), series)
+        .map_err(|e| FireboltError::Serialization(format!("Failed to build DataFrame: {e}")))
+}
+
+fn column_to_series(column: &Column, index: usize, rows: &[&Row]) -> Result<Series, FireboltError> {
+    let name = column.name.as_str().into();
+
+    let series = match column.r#type {
+        Type::Int => Series::new(name, collect::<Option<i32>>(rows, index)?),
+        Type::Long => Series::new(name, collect_with(rows, index, parse_long)?),
+        Type::Float => Series::new(name, collect::<Option<f32>>(rows, index)?),
+        Type::Double => Series::new(name, collect::<Option<f64>>(rows, index)?),
+        Type::Decimal => decimal_series(column, index, rows)?,
+        Type::Boolean => Series::new(name, collect::<Option<bool>>(rows, index)?),
+        Type::Text => Series::new(name, collect::<Option<String>>(rows, index)?),
+        Type::Bytes => Series::new(name, collect::<Option<Vec<u8>>>(rows, index)?),
+        Type::Date => Series::new(name, collect_with(rows, index, parse_date)?).into_date(),
+        Type::Timestamp => Series::new(name, collect_with(rows, index, parse_timestamp)?)
+            .into_datetime(TimeUnit::Microseconds, None),
+        Type::TimestampTZ => Series::new(name, collect_with(rows, index, parse_timestamptz)?)
+            .into_datetime(TimeUnit::Microseconds, Some(TimeZone::UTC)),
+        Type::Array | Type::Struct | Type::Geography | Type::Json | Type::Interval => {
+            Series::new(name, collect_with(rows, index, json_text)?)
+        }
+    };
+
+    Ok(series)
+}
+
+fn collect<T>(rows: &[&Row], index: usize) -> Result<Vec<T>, FireboltError>
+where
+    T: crate::types::TypeConversion,
+{
+    rows.iter().map(|row| row.get::<T>(index)).collect()
+}
+
+fn collect_with<T>(
+    rows: &[&Row],
+    index: usize,
+    convert: impl Fn(&serde_json::Value) -> Result<T, FireboltError>,
+) -> Result<Vec<Option<T>>, FireboltError> {
+    rows.iter()
+        .map(|row| {
+            let value = row.get::<serde_json::Value>(index)?;
+            if value.is_null() {
+                Ok(None)
+            } else {
+                convert(&value).map(Some)
+            }
+        })
+        .collect()
+}
+
+fn decimal_series(column: &Column, index: usize, rows: &[&Row]) -> Result<Series, FireboltError> {
+    let values = collect::<Option<rust_decimal::Decimal>>(rows, index)?;
+
+    let scale = match column.scale {
+        Some(scale) => scale as u32,
+        None => values
+            .iter()
+            .flatten()
+            .map(|v| v.scale())
+            .max()
+            .unwrap_or(0),
+    };
+    let precision = column.precision.map(|p| p as usize).unwrap_or(38);
+
+    let mantissas: Vec<Option<i128>> = values
+        .into_iter()
+        .map(|value| {
+            value.map(|mut v| {
+                v.rescale(scale);
+                v.mantissa()
+            })
+        })
+        .collect();
+
+    Series::new(column.name.as_str().into(), mantissas)
+        .into_decimal(precision, scale as usize)
+        .map_err(|e| FireboltError::Serialization(format!("Failed to build decimal column: {e}")))
+}
+
+fn as_str(value: &serde_json::Value) -> Result<&str, FireboltError> {
+    value
+        .as_str()
+        .ok_or_else(|| FireboltError::Serialization(format!("Expected string, got {value}")))
+}
+
+fn parse_long(value: &serde_json::Value) -> Result<i64, FireboltError> {
+    if let Some(v) = value.as_i64() {
+        return Ok(v);
+    }
+    as_str(value)?
+        .parse()
+        .map_err(|_| FireboltError::Serialization("Failed to convert to i64".to_string()))
+}
+
+fn parse_date(value: &serde_json::Value) -> Result<i32, FireboltError> {
+    let date = NaiveDate::parse_from_str(as_str(value)?, "%Y-%m-%d")
+        .map_err(|e| FireboltError::Serialization(format!("Failed to parse date: {e}")))?;
+    Ok((date - NaiveDate::default()).num_days() as i32)
+}
+
+fn parse_timestamp(value: &serde_json::Value) -> Result<i64, FireboltError> {
+    NaiveDateTime::parse_from_str(as_str(value)?, TIMESTAMP_FORMAT)
+        .map(|ts| ts.and_utc().timestamp_micros())
+        .map_err(|e| FireboltError::Serialization(format!("Failed to parse timestamp: {e}")))
+}
+
+fn parse_timestamptz(value: &serde_json::Value) -> Result<i64, FireboltError> {
+    crate::types::parse_timestamptz(as_str(value)?).map(|ts| ts.timestamp_micros())
+}
+
+fn json_text(value: &serde_json::Value) -> Result<String, FireboltError> {
+    Ok(match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::DataType;
+
+    fn column(name: &str, r#type: Type, precision: Option<i32>, scale: Option<i32>) -> Column {
+        Column {
+            name: name.to_string(),
+            r#type,
+            precision,
+            scale,
+            is_nullable: true,
+        }
+    }
+
+    #[test]
+    fn test_to_dataframe_dtypes() {
+        let columns = vec![
+            column("id", Type::Int, None, None),
+            column("big", Type::Long, None, None),
+            column("price", Type::Decimal, Some(10), Some(2)),
+            column("name", Type::Text, None, None),
+            column("day", Type::Date, None, None),
+            column("ts", Type::Timestamp, None, None),
+            column("tstz", Type::TimestampTZ, None, None),
+            column("tags", Type::Array, None, None),
+        ];
+        let rows = vec![
+            Row::new(
+                vec![
+                    serde_json::json!(1),
+                    serde_json::json!("30000000000"),
+                    serde_json::json!("12.5"),
+                    serde_json::json!("a"),
+                    serde_json::json!("2024-02-29"),
+                    serde_json::json!("2024-02-29 10:11:12.123456"),
+                    serde_json::json!("2024-02-29 10:11:12.5+02"),
+                    serde_json::json!([1, 2]),
+                ],
+                columns.clone(),
+            ),
+            Row::new(vec![serde_json::Value::Null; 8], columns.clone()),
+        ];
+        let result_set = ResultSet::new(columns, rows);
+
+        let df = result_set.to_dataframe().unwrap();
+
+        assert_eq!(df.height(), 2);
+        assert_eq!(
+            df.dtypes(),
+            vec![
+                DataType::Int32,
+                DataType::Int64,
+                DataType::Decimal(10, 2),
+                DataType::String,
+                DataType::Date,
+                DataType::Datetime(TimeUnit::Microseconds, None),
+                DataType::Datetime(TimeUnit::Microseconds, Some(TimeZone::UTC)),
+                DataType::String,
+            ]
+        );
+        assert_eq!(
+            df.column("big").unwrap().i64().unwrap().get(0),
+            Some(30000000000)
+        );
+        assert_eq!(df.column("id").unwrap().null_count(), 1);
+        assert_eq!(
+            df.column("tags").unwrap().str().unwrap().get(0),
+            Some("[1,2]")
+        );
+    }
+
+    #[test]
+    fn test_parse_temporal_values() {
+        assert_eq!(parse_date(&serde_json::json!("1970-01-02")).unwrap(), 1);
+        assert_eq!(
+            parse_timestamp(&serde_json::json!("1970-01-01 00:00:01.5")).unwrap(),
+            1_500_000
+        );
+        assert_eq!(
+            parse_timestamptz(&serde_json::json!("1970-01-01 02:00:00+02")).unwrap(),
+            0
+        );
+        assert!(parse_date(&serde_json::json!(5)).is_err());
+    }
+}
@@ -0,0 +1,437 @@
+use crate::error::FireboltError;
+use crate::result::Row;
+use crate::types::{Column, Type};
+use serde::de::value::{BorrowedStrDeserializer, SeqDeserializer};
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::Value;
+use std::fmt::Display;
+
+impl de::Error for FireboltError {
+    fn custom<T: Display>(msg: T) -> Self {
+        FireboltError::Serialization(msg.to_string())
+    }
+}
+
+fn json_error(e: serde_json::Error) -> FireboltError {
+    FireboltError::Serialization(e.to_string())
+}
+
+pub struct RowDeserializer<'de> {
+    row: &'de Row,
+}
+
+impl<'de> RowDeserializer<'de> {
+    pub fn new(row: &'de Row) -> Self {
+        Self { row }
+    }
+
+    fn cells(&self) -> impl Iterator<Item = (&'de Column, CellDeserializer<'de>)> {
+        self.row
+            .columns()
+            .iter()
+            .zip(self.row.data())
+            .map(|(column, value)| (column, CellDeserializer::new(value, &column.r#type)))
+    }
+}
+
+impl<'de> Deserializer<'de> for RowDeserializer<'de> {
+    type Error = FireboltError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(RowMapAccess {
+            cells: Box::new(self.cells()),
+            value: None,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(RowSeqAccess {
+            cells: Box::new(self.cells().map(|(_, cell)| cell)),
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct enum identifier ignored_any
+    }
+}
+
+struct RowMapAccess<'de> {
+    cells: Box<dyn Iterator<Item = (&'de Column, CellDeserializer<'de>)> + 'de>,
+    value: Option<CellDeserializer<'de>>,
+}
+
+impl<'de> MapAccess<'de> for RowMapAccess<'de> {
+    type Error = FireboltError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.cells.next() {
+            Some((column, cell)) => {
+                self.value = Some(cell);
+                seed.deserialize(BorrowedStrDeserializer::new(column.name.as_str()))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let cell = self.value.take().ok_or_else(|| {
+            FireboltError::Serialization("Value requested before key".to_string())
+        })?;
+        seed.deserialize(cell)
+    }
+}
+
+struct RowSeqAccess<'de> {
+    cells: Box<dyn Iterator<Item = CellDeserializer<'de>> + 'de>,
+}
+
+impl<'de> SeqAccess<'de> for RowSeqAccess<'de> {
+    type Error = FireboltError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        match self.cells.next() {
+            Some(cell) => seed.deserialize(cell).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+struct CellDeserializer<'de> {
+    value: &'de Value,
+    r#type: &'de Type,
+}
+
+impl<'de> CellDeserializer<'de> {
+    fn new(value: &'de Value, r#type: &'de Type) -> Self {
+        Self { value, r#type }
+    }
+
+    // Decodes like `Row::get::<Vec<u8>>`. Base64 values were already turned
+    // into hex by `ResultSet::with_bytes_encoding`.
+    fn bytes(&self) -> Result<Option<Vec<u8>>, FireboltError> {
+        if *self.r#type != Type::Bytes {
+            return Ok(None);
+        }
+        self.value
+            .as_str()
+            .map(crate::types::decode_bytea)
+            .transpose()
+    }
+}
+
+fn parse<T: std::str::FromStr>(s: &str) -> Result<T, FireboltError>
+where
+    T::Err: Display,
+{
+    s.parse()
+        .map_err(|e| FireboltError::Serialization(format!("Failed to parse '{s}': {e}")))
+}
+
+// Firebolt sends bigint and decimal values as JSON strings, and special floats
+// such as "inf" and "nan" as strings too, so numeric hints parse them first.
+macro_rules! deserialize_number {
+    ($($method:ident => $visit:ident: $ty:ty),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self.value.as_str() {
+                    Some(s) => visitor.$visit(parse::<$ty>(s)?),
+                    None => self.value.$method(visitor).map_err(json_error),
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! deserialize_value {
+    ($($method:ident),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.value.$method(visitor).map_err(json_error)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for CellDeserializer<'de> {
+    type Error = FireboltError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let (Type::Long, Some(s)) = (self.r#type, self.value.as_str()) {
+            if let Ok(v) = s.parse::<i64>() {
+                return visitor.visit_i64(v);
+            }
+        }
+        self.value.deserialize_any(visitor).map_err(json_error)
+    }
+
+    deserialize_number! {
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_i128 => visit_i128: i128,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_u128 => visit_u128: u128,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+    }
+
+    deserialize_value! {
+        deserialize_bool,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_unit,
+        deserialize_map,
+        deserialize_identifier,
+        deserialize_ignored_any,
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.bytes()? {
+            Some(bytes) => visitor.visit_byte_buf(bytes),
+            None => self.value.deserialize_byte_buf(visitor).map_err(json_error),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.bytes()? {
+            Some(bytes) => visitor.visit_seq(SeqDeserializer::new(bytes.into_iter())),
+            None => self.value.deserialize_seq(visitor).map_err(json_error),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.value.is_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value
+            .deserialize_unit_struct(name, visitor)
+            .map_err(json_error)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value
+            .deserialize_tuple(len, visitor)
+            .map_err(json_error)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value
+            .deserialize_tuple_struct(name, len, visitor)
+            .map_err(json_error)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value
+            .deserialize_struct(name, fields, visitor)
+            .map_err(json_error)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value
+            .deserialize_enum(name, variants, visitor)
+            .map_err(json_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    fn column(name: &str, r#type: Type) -> Column {
+        Column {
+            name: name.to_string(),
+            r#type,
+            precision: None,
+            scale: None,
+            is_nullable: true,
+        }
+    }
+
+    fn test_row() -> Row {
+        Row::new(
+            vec![
+                json!(1),
+                json!("30000000000"),
+                json!("12.50"),
+                json!("hello"),
+                json!("inf"),
+                json!("\\x6869"),
+                json!(null),
+                json!([1, 2]),
+            ],
+            vec![
+                column("id", Type::Int),
+                column("big", Type::Long),
+                column("price", Type::Decimal),
+                column("name", Type::Text),
+                column("ratio", Type::Double),
+                column("payload", Type::Bytes),
+                column("missing", Type::Int),
+                column("tags", Type::Array),
+            ],
+        )
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_deserialize_struct() {
+        #[derive(Deserialize)]
+        struct Record<'a> {
+            id: i32,
+            big: i64,
+            price: rust_decimal::Decimal,
+            name: &'a str,
+            ratio: f64,
+            payload: Vec<u8>,
+            missing: Option<i32>,
+            tags: Vec<i32>,
+        }
+
+        let row = test_row();
+        let record: Record = row.deserialize().unwrap();
+
+        assert_eq!(record.id, 1);
+        assert_eq!(record.big, 30000000000);
+        assert_eq!(record.price, rust_decimal::Decimal::new(1250, 2));
+        assert_eq!(record.name, "hello");
+        assert!(record.ratio.is_infinite());
+        assert_eq!(record.payload, b"hi".to_vec());
+        assert_eq!(record.missing, None);
+        assert_eq!(record.tags, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_deserialize_tuple_and_map() {
+        let row = test_row();
+
+        let (id, big, price): (u8, i128, String) = row.deserialize().unwrap();
+        assert_eq!(id, 1);
+        assert_eq!(big, 30000000000);
+        assert_eq!(price, "12.50");
+
+        let map: std::collections::HashMap<String, serde_json::Value> = row.deserialize().unwrap();
+        assert_eq!(map["big"], json!(30000000000i64));
+        assert_eq!(map["missing"], json!(null));
+    }
+
+    #[test]
+    fn test_deserialize_errors() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Missing {
+            not_a_column: i32,
+        }
+
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct WrongType {
+            name: i32,
+        }
+
+        let row = test_row();
+        assert!(matches!(
+            row.deserialize::<Missing>(),
+            Err(FireboltError::Serialization(msg)) if msg.contains("not_a_column")
+        ));
+        assert!(matches!(
+            row.deserialize::<WrongType>(),
+            Err(FireboltError::Serialization(_))
+        ));
+    }
+}
@@ -0,0 +1,228 @@
+// Host name resolution for the built-in transports. Static overrides pin a
+// host to fixed addresses, a custom `Resolver` replaces the system lookup (for
+// example with hickory-dns), and an optional TTL caches successful lookups.
+
+use crate::error::FireboltError;
+use crate::transport::BoxFuture;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub trait Resolver: Send + Sync {
+    fn resolve<'a>(&'a self, host: &'a str) -> BoxFuture<'a, Result<Vec<IpAddr>, FireboltError>>;
+}
+
+#[cfg_attr(not(any(feature = "reqwest", feature = "hyper")), allow(dead_code))]
+#[derive(Clone, Default)]
+pub(crate) struct DnsOptions {
+    pub(crate) overrides: HashMap<String, Vec<IpAddr>>,
+    pub(crate) resolver: Option<Arc<dyn Resolver>>,
+    pub(crate) cache_ttl: Option<Duration>,
+}
+
+impl std::fmt::Debug for DnsOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DnsOptions")
+            .field("overrides", &self.overrides)
+            .field("resolver", &self.resolver.as_ref().map(|_| "custom"))
+            .field("cache_ttl", &self.cache_ttl)
+            .finish()
+    }
+}
+
+impl DnsOptions {
+    pub(crate) fn is_default(&self) -> bool {
+        self.overrides.is_empty() && self.resolver.is_none() && self.cache_ttl.is_none()
+    }
+}
+
+#[cfg(any(feature = "reqwest", feature = "hyper"))]
+type DnsCache = std::sync::Mutex<HashMap<String, (std::time::Instant, Vec<IpAddr>)>>;
+
+#[cfg(any(feature = "reqwest", feature = "hyper"))]
+#[derive(Clone)]
+pub(crate) struct DnsResolver {
+    options: Arc<DnsOptions>,
+    cache: Arc<DnsCache>,
+}
+
+#[cfg(any(feature = "reqwest", feature = "hyper"))]
+impl DnsResolver {
+    pub(crate) fn new(options: &DnsOptions) -> Self {
+        Self {
+            options: Arc::new(options.clone()),
+            cache: Arc::default(),
+        }
+    }
+
+    pub(crate) async fn lookup(&self, host: &str) -> Result<Vec<IpAddr>, FireboltError> {
+        let started = std::time::Instant::now();
+        let result = self.resolve(host).await;
+        crate::timing::record_dns(started.elapsed());
+        result
+    }
+
+    async fn resolve(&self, host: &str) -> Result<Vec<IpAddr>, FireboltError> {
+        let host = host.to_ascii_lowercase();
+        if let Some(addrs) = self.options.overrides.get(&host) {
+            return Ok(addrs.clone());
+        }
+        if let Some(ttl) = self.options.cache_ttl {
+            let cache = self.cache.lock().unwrap();
+            if let Some((resolved_at, addrs)) = cache.get(&host) {
+                if resolved_at.elapsed() < ttl {
+                    return Ok(addrs.clone());
+                }
+            }
+        }
+
+        let addrs = match &self.options.resolver {
+            Some(resolver) => resolver.resolve(&host).await?,
+            None => tokio::net::lookup_host((host.as_str(), 0))
+                .await
+                .map_err(|e| FireboltError::Network(format!("Failed to resolve '{host}': {e}")))?
+                .map(|addr| addr.ip())
+                .collect(),
+        };
+        if addrs.is_empty() {
+            return Err(FireboltError::Network(format!(
+                "No addresses found for '{host}'"
+            )));
+        }
+
+        if self.options.cache_ttl.is_some() {
+            self.cache
+                .lock()
+                .unwrap()
+                .insert(host, (std::time::Instant::now(), addrs.clone()));
+        }
+        Ok(addrs)
+    }
+}
+
+// The port is ignored by both transports, which connect to the port in the URL.
+#[cfg(feature = "reqwest")]
+impl reqwest::dns::Resolve for DnsResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let addrs = resolver.lookup(name.as_str()).await?;
+            let addrs: reqwest::dns::Addrs =
+                Box::new(addrs.into_iter().map(|ip| std::net::SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl tower_service::Service<hyper_util::client::legacy::connect::dns::Name> for DnsResolver {
+    type Response = std::vec::IntoIter<std::net::SocketAddr>;
+    type Error = FireboltError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, name: hyper_util::client::legacy::connect::dns::Name) -> Self::Future {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let addrs = resolver.lookup(name.as_str()).await?;
+            Ok(addrs
+                .into_iter()
+                .map(|ip| std::net::SocketAddr::new(ip, 0))
+                .collect::<Vec<_>>()
+                .into_iter())
+        })
+    }
+}
+
+#[cfg(all(test, any(feature = "reqwest", feature = "hyper")))]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingResolver(AtomicUsize);
+
+    impl Resolver for CountingResolver {
+        fn resolve<'a>(
+            &'a self,
+            _host: &'a str,
+        ) -> BoxFuture<'a, Result<Vec<IpAddr>, FireboltError>> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Ok(vec![IpAddr::from([10, 0, 0, 1])]) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lookup_overrides_and_cache() {
+        let counting = Arc::new(CountingResolver(AtomicUsize::new(0)));
+        let mut options = DnsOptions {
+            resolver: Some(counting.clone()),
+            cache_ttl: Some(Duration::from_secs(60)),
+            ..DnsOptions::default()
+        };
+        options.overrides.insert(
+            "pinned.firebolt.io".to_string(),
+            vec![IpAddr::from([127, 0, 0, 1])],
+        );
+        let resolver = DnsResolver::new(&options);
+
+        assert_eq!(
+            resolver.lookup("Pinned.Firebolt.io").await.unwrap(),
+            vec![IpAddr::from([127, 0, 0, 1])]
+        );
+        assert_eq!(counting.0.load(Ordering::SeqCst), 0);
+
+        for _ in 0..3 {
+            assert_eq!(
+                resolver.lookup("engine.firebolt.io").await.unwrap(),
+                vec![IpAddr::from([10, 0, 0, 1])]
+            );
+        }
+        assert_eq!(counting.0.load(Ordering::SeqCst), 1);
+
+        let uncached = DnsResolver::new(&DnsOptions {
+            cache_ttl: None,
+            ..options
+        });
+        uncached.lookup("engine.firebolt.io").await.unwrap();
+        uncached.lookup("engine.firebolt.io").await.unwrap();
+        assert_eq!(counting.0.load(Ordering::SeqCst), 3);
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_reqwest_transport_uses_override() {
+        use crate::transport::{HttpOptions, HttpRequest, Method, ReqwestTransport, Transport};
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/")
+            .with_body("pinned")
+            .create_async()
+            .await;
+
+        let mut options = HttpOptions::default();
+        options.dns.overrides.insert(
+            "engine.firebolt.invalid".to_string(),
+            vec![IpAddr::from([127, 0, 0, 1])],
+        );
+        let url = format!(
+            "http://engine.firebolt.invalid:{}/",
+            server.socket_address().port()
+        );
+        let response = ReqwestTransport::from_options(&options)
+            .unwrap()
+            .send(HttpRequest::new(Method::Get, url))
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(response.text(), "pinned");
+    }
+}
@@ -0,0 +1,110 @@
+use crate::error::FireboltError;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+struct CachedUrl {
+    url: String,
+    expires: Instant,
+}
+
+// Shared by every client in the process and keyed by (api_endpoint, account_name).
+// An entry is used until its TTL runs out, or until a request to that URL
+// fails in a way that suggests the endpoint has moved.
+fn entries() -> &'static Mutex<HashMap<(String, String), CachedUrl>> {
+    static ENTRIES: OnceLock<Mutex<HashMap<(String, String), CachedUrl>>> = OnceLock::new();
+    ENTRIES.get_or_init(Default::default)
+}
+
+fn lock() -> std::sync::MutexGuard<'static, HashMap<(String, String), CachedUrl>> {
+    entries().lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn key(api_endpoint: &str, account_name: &str) -> (String, String) {
+    (api_endpoint.to_string(), account_name.to_string())
+}
+
+// A zero `ttl` turns the cache off: the URL is always fetched and not stored.
+pub(crate) async fn get_or_fetch<F, Fut>(
+    api_endpoint: &str,
+    account_name: &str,
+    ttl: Duration,
+    fetch: F,
+) -> Result<String, FireboltError>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<String, FireboltError>>,
+{
+    if ttl.is_zero() {
+        return fetch().await;
+    }
+    if let Some(entry) = lock().get(&key(api_endpoint, account_name)) {
+        if entry.expires > Instant::now() {
+            return Ok(entry.url.clone());
+        }
+    }
+
+    let url = fetch().await?;
+    lock().insert(
+        key(api_endpoint, account_name),
+        CachedUrl {
+            url: url.clone(),
+            expires: Instant::now() + ttl,
+        },
+    );
+    Ok(url)
+}
+
+// Drops the entry only if it still holds `url`, so a newer lookup made by
+// another client is kept. Returns whether an entry was dropped.
+pub(crate) fn invalidate(api_endpoint: &str, account_name: &str, url: &str) -> bool {
+    let mut entries = lock();
+    let key = key(api_endpoint, account_name);
+    let stale = entries
+        .get(&key)
+        .is_some_and(|entry| entry.url.trim_end_matches('/') == url.trim_end_matches('/'));
+    if stale {
+        entries.remove(&key);
+    }
+    stale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_engine_url_cache() {
+        let endpoint = format!("https://api.{}.firebolt.io", uuid::Uuid::new_v4());
+        let fetches = AtomicUsize::new(0);
+        let fetch = || {
+            let count = fetches.fetch_add(1, Ordering::SeqCst);
+            async move { Ok(format!("https://engine{count}.firebolt.io/")) }
+        };
+        let ttl = Duration::from_secs(60);
+
+        let first = get_or_fetch(&endpoint, "acme", ttl, fetch).await.unwrap();
+        let second = get_or_fetch(&endpoint, "acme", ttl, fetch).await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        let other = get_or_fetch(&endpoint, "other", ttl, fetch).await.unwrap();
+        assert_eq!(other, "https://engine1.firebolt.io/");
+
+        assert!(!invalidate(
+            &endpoint,
+            "acme",
+            "https://elsewhere.firebolt.io"
+        ));
+        assert!(invalidate(&endpoint, "acme", "https://engine0.firebolt.io"));
+        let refreshed = get_or_fetch(&endpoint, "acme", ttl, fetch).await.unwrap();
+        assert_eq!(refreshed, "https://engine2.firebolt.io/");
+
+        let uncached = get_or_fetch(&endpoint, "acme", Duration::ZERO, fetch)
+            .await
+            .unwrap();
+        assert_eq!(uncached, "https://engine3.firebolt.io/");
+    }
+}
@@ -12,8 +12,120 @@ pub enum FireboltError {
     Serialization(String),
     #[error("Configuration error: {0}")]
     Configuration(String),
+    #[error("Timeout error: {0}")]
+    Timeout(String),
+    #[error("Service unavailable: {0}")]
+    Unavailable(String),
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+    #[error("Account not found: {0}")]
+    AccountNotFound(String),
+    #[error("Engine resolution error: {0}")]
+    EngineResolution(String),
+    #[error("Engine not running: {0}")]
+    EngineNotRunning(String),
+    #[error("Column not found: {0}")]
+    ColumnNotFound(String),
+    #[error("Null value: {0}")]
+    NullValue(String),
+    #[error("Type mismatch: {0}")]
+    TypeMismatch(String),
     #[error("Header parsing error: {0}")]
     HeaderParsing(String),
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    Authentication,
+    Network,
+    Query,
+    Serialization,
+    Configuration,
+    Timeout,
+    Unavailable,
+    Cancelled,
+    AccountNotFound,
+    EngineResolution,
+    EngineNotRunning,
+    ColumnNotFound,
+    NullValue,
+    TypeMismatch,
+    HeaderParsing,
+    Unknown,
+}
+
+impl FireboltError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            FireboltError::Authentication(_) => ErrorKind::Authentication,
+            FireboltError::Network(_) => ErrorKind::Network,
+            FireboltError::Query(_) => ErrorKind::Query,
+            FireboltError::Serialization(_) => ErrorKind::Serialization,
+            FireboltError::Configuration(_) => ErrorKind::Configuration,
+            FireboltError::Timeout(_) => ErrorKind::Timeout,
+            FireboltError::Unavailable(_) => ErrorKind::Unavailable,
+            FireboltError::Cancelled(_) => ErrorKind::Cancelled,
+            FireboltError::AccountNotFound(_) => ErrorKind::AccountNotFound,
+            FireboltError::EngineResolution(_) => ErrorKind::EngineResolution,
+            FireboltError::EngineNotRunning(_) => ErrorKind::EngineNotRunning,
+            FireboltError::ColumnNotFound(_) => ErrorKind::ColumnNotFound,
+            FireboltError::NullValue(_) => ErrorKind::NullValue,
+            FireboltError::TypeMismatch(_) => ErrorKind::TypeMismatch,
+            FireboltError::HeaderParsing(_) => ErrorKind::HeaderParsing,
+            FireboltError::Unknown(_) => ErrorKind::Unknown,
+        }
+    }
+
+    // Appends `context` to the message, keeping the variant.
+    pub(crate) fn with_context(self, context: &str) -> Self {
+        let append = |message: String| format!("{message} ({context})");
+        match self {
+            FireboltError::Authentication(m) => FireboltError::Authentication(append(m)),
+            FireboltError::Network(m) => FireboltError::Network(append(m)),
+            FireboltError::Query(m) => FireboltError::Query(append(m)),
+            FireboltError::Serialization(m) => FireboltError::Serialization(append(m)),
+            FireboltError::Configuration(m) => FireboltError::Configuration(append(m)),
+            FireboltError::Timeout(m) => FireboltError::Timeout(append(m)),
+            FireboltError::Unavailable(m) => FireboltError::Unavailable(append(m)),
+            FireboltError::Cancelled(m) => FireboltError::Cancelled(append(m)),
+            FireboltError::AccountNotFound(m) => FireboltError::AccountNotFound(append(m)),
+            FireboltError::EngineResolution(m) => FireboltError::EngineResolution(append(m)),
+            FireboltError::EngineNotRunning(m) => FireboltError::EngineNotRunning(append(m)),
+            FireboltError::ColumnNotFound(m) => FireboltError::ColumnNotFound(append(m)),
+            FireboltError::NullValue(m) => FireboltError::NullValue(append(m)),
+            FireboltError::TypeMismatch(m) => FireboltError::TypeMismatch(append(m)),
+            FireboltError::HeaderParsing(m) => FireboltError::HeaderParsing(append(m)),
+            FireboltError::Unknown(m) => FireboltError::Unknown(append(m)),
+        }
+    }
+
+    // Transient failures that may succeed if the same request is sent again.
+    // Expired access tokens never reach the caller: the client refreshes the
+    // token and retries once on its own.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.kind(),
+            ErrorKind::Network | ErrorKind::Timeout | ErrorKind::Unavailable
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(FireboltError::Network("connection reset".to_string()).is_retryable());
+        assert!(FireboltError::Timeout("Query timed out".to_string()).is_retryable());
+        assert!(FireboltError::Unavailable("HTTP 429".to_string()).is_retryable());
+        assert!(!FireboltError::Query("syntax error".to_string()).is_retryable());
+        assert!(!FireboltError::Authentication("denied".to_string()).is_retryable());
+        assert_eq!(
+            FireboltError::Configuration(String::new()).kind(),
+            ErrorKind::Configuration
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use crate::client::FireboltClient;
+use crate::error::FireboltError;
+use crate::result::ResultSet;
+use crate::transport::BoxFuture;
+
+// Lets application code take `&mut impl Queryable` instead of the concrete
+// client, so tests can pass an in-memory fake.
+pub trait Queryable: Send {
+    fn query<'a>(&'a mut self, sql: &'a str) -> BoxFuture<'a, Result<ResultSet, FireboltError>>;
+
+    // Runs a statement whose result set is not needed, such as DDL or INSERT.
+    fn execute<'a>(&'a mut self, sql: &'a str) -> BoxFuture<'a, Result<(), FireboltError>> {
+        Box::pin(async move { self.query(sql).await.map(|_| ()) })
+    }
+}
+
+impl Queryable for FireboltClient {
+    fn query<'a>(&'a mut self, sql: &'a str) -> BoxFuture<'a, Result<ResultSet, FireboltError>> {
+        Box::pin(FireboltClient::query(self, sql))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockFirebolt, MockResponse};
+
+    async fn count_users(db: &mut impl Queryable) -> Result<i32, FireboltError> {
+        db.execute("SET timezone = 'UTC'").await?;
+        db.query("SELECT count(*) AS n FROM users").await?.rows[0].get("n")
+    }
+
+    struct FakeDb {
+        statements: Vec<String>,
+    }
+
+    impl Queryable for FakeDb {
+        fn query<'a>(
+            &'a mut self,
+            sql: &'a str,
+        ) -> BoxFuture<'a, Result<ResultSet, FireboltError>> {
+            self.statements.push(sql.to_string());
+            let json = serde_json::json!({
+                "meta": [{"name": "n", "type": "int"}],
+                "data": [[42]],
+            });
+            Box::pin(async move { crate::parser::parse_response_json(&json) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_queryable_fake() {
+        let mut db = FakeDb {
+            statements: Vec::new(),
+        };
+        assert_eq!(count_users(&mut db).await.unwrap(), 42);
+        assert_eq!(db.statements.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_queryable_client() {
+        let mock = MockFirebolt::new();
+        mock.on_query(
+            "SELECT count(*) AS n FROM users",
+            MockResponse::rows(&[("n", "int")], vec![vec![7.into()]]),
+        );
+        let mut client = mock.connect().await.unwrap();
+        assert_eq!(count_users(&mut client).await.unwrap(), 7);
+        assert_eq!(mock.queries()[0].sql, "SET timezone = 'UTC'");
+    }
+}
@@ -0,0 +1,399 @@
+use crate::error::FireboltError;
+use crate::result::{ResultSet, Row};
+use crate::types::{Column, Type, TypeConversion};
+use std::io::Write;
+
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    delimiter: char,
+    quote: char,
+    null_value: String,
+    header: bool,
+    line_terminator: String,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote: '"',
+            null_value: String::new(),
+            header: true,
+            line_terminator: "\n".to_string(),
+        }
+    }
+}
+
+impl CsvOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn with_quote(mut self, quote: char) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    pub fn with_null_value(mut self, null_value: String) -> Self {
+        self.null_value = null_value;
+        self
+    }
+
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    pub fn with_line_terminator(mut self, line_terminator: String) -> Self {
+        self.line_terminator = line_terminator;
+        self
+    }
+}
+
+// The `output_format` the server writes a response in, for
+// `FireboltClient::query_to_writer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Csv,
+    CsvWithNames,
+    TabSeparated,
+    TabSeparatedWithNames,
+    TabSeparatedWithNamesAndTypes,
+    JsonCompact,
+    JsonLinesCompact,
+}
+
+impl OutputFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::Csv => "CSV",
+            OutputFormat::CsvWithNames => "CSVWithNames",
+            OutputFormat::TabSeparated => "TabSeparated",
+            OutputFormat::TabSeparatedWithNames => "TabSeparatedWithNames",
+            OutputFormat::TabSeparatedWithNamesAndTypes => "TabSeparatedWithNamesAndTypes",
+            OutputFormat::JsonCompact => "JSON_Compact",
+            OutputFormat::JsonLinesCompact => "JSONLines_Compact",
+        }
+    }
+}
+
+impl ResultSet {
+    pub fn write_csv<W: Write>(&self, writer: W, options: CsvOptions) -> Result<(), FireboltError> {
+        write_csv_rows(writer, &self.columns, &self.rows, options)
+    }
+
+    pub fn write_ndjson<W: Write>(&self, writer: W) -> Result<(), FireboltError> {
+        write_ndjson_rows(writer, &self.columns, &self.rows)
+    }
+
+    // One object per row, keyed by column name, for returning from an HTTP
+    // API. Decimals become strings so no precision is lost and bytes become
+    // base64. Keys are sorted unless serde_json's `preserve_order` feature is
+    // enabled, in which case they follow the column order.
+    pub fn to_json_objects(
+        &self,
+    ) -> Result<Vec<serde_json::Map<String, serde_json::Value>>, FireboltError> {
+        self.rows
+            .iter()
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .zip(row.data())
+                    .map(|(column, value)| Ok((column.name.clone(), json_value(column, value)?)))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+pub fn write_csv_rows<'a, W: Write>(
+    mut writer: W,
+    columns: &[Column],
+    rows: impl IntoIterator<Item = &'a Row>,
+    options: CsvOptions,
+) -> Result<(), FireboltError> {
+    if options.header {
+        let names: Vec<Option<String>> = columns.iter().map(|c| Some(c.name.clone())).collect();
+        write_csv_record(&mut writer, &names, &options)?;
+    }
+
+    for row in rows {
+        let fields = columns
+            .iter()
+            .zip(row.data())
+            .map(|(column, value)| csv_field(column, value))
+            .collect::<Result<Vec<_>, _>>()?;
+        write_csv_record(&mut writer, &fields, &options)?;
+    }
+
+    writer.flush().map_err(write_error)
+}
+
+fn write_csv_record<W: Write>(
+    writer: &mut W,
+    fields: &[Option<String>],
+    options: &CsvOptions,
+) -> Result<(), FireboltError> {
+    let mut line = String::new();
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            line.push(options.delimiter);
+        }
+        match field {
+            None => line.push_str(&options.null_value),
+            Some(text) => push_quoted(&mut line, text, options),
+        }
+    }
+    line.push_str(&options.line_terminator);
+    writer.write_all(line.as_bytes()).map_err(write_error)
+}
+
+fn push_quoted(line: &mut String, text: &str, options: &CsvOptions) {
+    // A string equal to the null representation is quoted so it can't be read back as NULL.
+    let needs_quoting = text == options.null_value
+        || text.starts_with(' ')
+        || text.ends_with(' ')
+        || text
+            .chars()
+            .any(|c| c == options.delimiter || c == options.quote || c == '\n' || c == '\r');
+
+    if !needs_quoting {
+        line.push_str(text);
+        return;
+    }
+
+    line.push(options.quote);
+    for c in text.chars() {
+        if c == options.quote {
+            line.push(options.quote);
+        }
+        line.push(c);
+    }
+    line.push(options.quote);
+}
+
+fn csv_field(column: &Column, value: &serde_json::Value) -> Result<Option<String>, FireboltError> {
+    if value.is_null() {
+        return Ok(None);
+    }
+
+    let text = match (&column.r#type, value) {
+        (Type::Decimal, _) => decimal_text(column, value)?,
+        (_, serde_json::Value::String(s)) => s.clone(),
+        (_, other) => other.to_string(),
+    };
+
+    Ok(Some(text))
+}
+
+fn json_value(
+    column: &Column,
+    value: &serde_json::Value,
+) -> Result<serde_json::Value, FireboltError> {
+    use base64::Engine;
+
+    if value.is_null() {
+        return Ok(serde_json::Value::Null);
+    }
+    Ok(match column.r#type {
+        Type::Decimal => serde_json::Value::String(decimal_text(column, value)?),
+        Type::Bytes => {
+            let bytes = Vec::<u8>::convert_from_json(value, &column.r#type)?;
+            serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(bytes))
+        }
+        _ => value.clone(),
+    })
+}
+
+// Padded to the column's scale when the `decimal` feature is enabled,
+// otherwise the server's text.
+fn decimal_text(column: &Column, value: &serde_json::Value) -> Result<String, FireboltError> {
+    #[cfg(feature = "decimal")]
+    {
+        let mut decimal = rust_decimal::Decimal::convert_from_json(value, &column.r#type)?;
+        if let Some(scale) = column.scale {
+            decimal.rescale(scale as u32);
+        }
+        Ok(decimal.to_string())
+    }
+    #[cfg(not(feature = "decimal"))]
+    {
+        let _ = column;
+        Ok(match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+    }
+}
+
+pub fn write_ndjson_rows<'a, W: Write>(
+    mut writer: W,
+    columns: &[Column],
+    rows: impl IntoIterator<Item = &'a Row>,
+) -> Result<(), FireboltError> {
+    for row in rows {
+        // Written by hand rather than through serde_json::Map so keys keep the column order.
+        let mut line = String::from("{");
+        for (i, (column, value)) in columns.iter().zip(row.data()).enumerate() {
+            if i > 0 {
+                line.push(',');
+            }
+            line.push_str(&serde_json::Value::String(column.name.clone()).to_string());
+            line.push(':');
+            line.push_str(&value.to_string());
+        }
+        line.push_str("}\n");
+        writer.write_all(line.as_bytes()).map_err(write_error)?;
+    }
+
+    writer.flush().map_err(write_error)
+}
+
+fn write_error(e: std::io::Error) -> FireboltError {
+    FireboltError::Serialization(format!("Failed to write output: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Type;
+    use serde_json::json;
+
+    fn column(name: &str, r#type: Type, scale: Option<i32>) -> Column {
+        Column {
+            name: name.to_string(),
+            r#type,
+            precision: None,
+            scale,
+            is_nullable: true,
+        }
+    }
+
+    fn test_result_set() -> ResultSet {
+        let columns = vec![
+            column("id", Type::Int, None),
+            column("name", Type::Text, None),
+            column("price", Type::Decimal, Some(2)),
+            column("ts", Type::Timestamp, None),
+            column("tags", Type::Array, None),
+        ];
+        let rows = vec![
+            Row::new(
+                vec![
+                    json!(1),
+                    json!("plain"),
+                    json!("12.5"),
+                    json!("2024-02-29 10:11:12.5"),
+                    json!([1, 2]),
+                ],
+                columns.clone(),
+            ),
+            Row::new(
+                vec![
+                    json!(2),
+                    json!("say \"hi\", bye"),
+                    json!(3),
+                    json!(null),
+                    json!(null),
+                ],
+                columns.clone(),
+            ),
+            Row::new(
+                vec![json!(3), json!(""), json!(null), json!(null), json!([])],
+                columns.clone(),
+            ),
+        ];
+        ResultSet::new(columns, rows)
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_write_csv_defaults() {
+        let mut out = Vec::new();
+        test_result_set()
+            .write_csv(&mut out, CsvOptions::default())
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "id,name,price,ts,tags\n\
+             1,plain,12.50,2024-02-29 10:11:12.5,\"[1,2]\"\n\
+             2,\"say \"\"hi\"\", bye\",3.00,,\n\
+             3,\"\",,,[]\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_write_csv_options() {
+        let options = CsvOptions::new()
+            .with_delimiter(';')
+            .with_null_value("NULL".to_string())
+            .with_header(false)
+            .with_line_terminator("\r\n".to_string());
+        let mut out = Vec::new();
+        test_result_set().write_csv(&mut out, options).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "1;plain;12.50;2024-02-29 10:11:12.5;[1,2]\r\n\
+             2;\"say \"\"hi\"\", bye\";3.00;NULL;NULL\r\n\
+             3;;NULL;NULL;[]\r\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_to_json_objects() {
+        let mut result = test_result_set();
+        result.columns.push(column("blob", Type::Bytes, None));
+        for (row, blob) in result.rows.iter_mut().zip(["\\x68690a", "", "\\x"]) {
+            *row = Row::new(
+                row.data().iter().cloned().chain([json!(blob)]).collect(),
+                result.columns.clone(),
+            );
+        }
+
+        let objects = result.to_json_objects().unwrap();
+        assert_eq!(objects.len(), 3);
+        assert_eq!(
+            serde_json::Value::Object(objects[0].clone()),
+            json!({
+                "id": 1,
+                "name": "plain",
+                "price": "12.50",
+                "ts": "2024-02-29 10:11:12.5",
+                "tags": [1, 2],
+                "blob": "aGkK"
+            })
+        );
+        assert_eq!(objects[1]["price"], json!("3.00"));
+        assert_eq!(objects[1]["ts"], json!(null));
+        assert_eq!(objects[2]["price"], json!(null));
+        assert_eq!(objects[2]["blob"], json!(""));
+    }
+
+    #[test]
+    fn test_write_ndjson() {
+        let mut out = Vec::new();
+        test_result_set().write_ndjson(&mut out).unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            r#"{"id":1,"name":"plain","price":"12.5","ts":"2024-02-29 10:11:12.5","tags":[1,2]}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(lines[1]).unwrap(),
+            json!({"id": 2, "name": "say \"hi\", bye", "price": 3, "ts": null, "tags": null})
+        );
+    }
+}
@@ -0,0 +1,174 @@
+// Closure hooks for quick instrumentation without implementing `Transport`.
+// They see a sanitized view of each HTTP call: no headers, no bodies, and no
+// query string, which carries session settings.
+
+use crate::error::{ErrorKind, FireboltError};
+use crate::retry::RetryEvent;
+use crate::transport::{BoxFuture, HttpRequest, HttpResponse, Method, TransferTiming, Transport};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWrite;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestInfo {
+    pub method: Method,
+    pub url: String,
+    pub idempotent: bool,
+}
+
+// `status` is `None` and `error` is set when no response was received.
+// `timing` is set for responses from the built-in transports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseInfo {
+    pub method: Method,
+    pub url: String,
+    pub status: Option<u16>,
+    pub error: Option<ErrorKind>,
+    pub elapsed: Duration,
+    pub timing: Option<TransferTiming>,
+}
+
+type Hook<T> = Arc<dyn Fn(&T) + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    pub(crate) on_request: Option<Hook<RequestInfo>>,
+    pub(crate) on_response: Option<Hook<ResponseInfo>>,
+    pub(crate) on_retry: Option<Hook<RetryEvent>>,
+}
+
+impl Hooks {
+    pub(crate) fn observes_requests(&self) -> bool {
+        self.on_request.is_some() || self.on_response.is_some()
+    }
+
+    pub(crate) fn retry(&self, event: RetryEvent) {
+        event.emit();
+        if let Some(on_retry) = &self.on_retry {
+            on_retry(&event);
+        }
+    }
+}
+
+fn sanitize_url(url: &str) -> String {
+    url.split(['?', '#']).next().unwrap_or_default().to_string()
+}
+
+// Sits below the retry layer, so the hooks run once per attempt.
+pub(crate) struct HookTransport {
+    inner: Arc<dyn Transport>,
+    hooks: Hooks,
+}
+
+impl HookTransport {
+    pub(crate) fn new(inner: Arc<dyn Transport>, hooks: Hooks) -> Self {
+        Self { inner, hooks }
+    }
+
+    async fn observe(
+        &self,
+        request: RequestInfo,
+        future: BoxFuture<'_, Result<HttpResponse, FireboltError>>,
+    ) -> Result<HttpResponse, FireboltError> {
+        if let Some(on_request) = &self.hooks.on_request {
+            on_request(&request);
+        }
+        let started = Instant::now();
+        let result = future.await;
+        if let Some(on_response) = &self.hooks.on_response {
+            on_response(&ResponseInfo {
+                method: request.method,
+                url: request.url,
+                status: result.as_ref().ok().map(|response| response.status),
+                error: result.as_ref().err().map(FireboltError::kind),
+                elapsed: started.elapsed(),
+                timing: result.as_ref().ok().and_then(|response| response.timing),
+            });
+        }
+        result
+    }
+}
+
+fn request_info(request: &HttpRequest) -> RequestInfo {
+    RequestInfo {
+        method: request.method,
+        url: sanitize_url(&request.url),
+        idempotent: request.idempotent,
+    }
+}
+
+impl Transport for HookTransport {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+        let info = request_info(&request);
+        Box::pin(self.observe(info, self.inner.send(request)))
+    }
+
+    fn send_to<'a>(
+        &'a self,
+        request: HttpRequest,
+        sink: &'a mut (dyn AsyncWrite + Unpin + Send),
+    ) -> BoxFuture<'a, Result<HttpResponse, FireboltError>> {
+        let info = request_info(&request);
+        Box::pin(self.observe(info, self.inner.send_to(request, sink)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retry::{RetryCause, RetryPolicy};
+    use crate::testing::{MockFirebolt, MockResponse};
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_hooks() {
+        let mock = MockFirebolt::new();
+        mock.on_query("SELECT busy", MockResponse::error(503, "busy"));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let responses = Arc::new(Mutex::new(Vec::new()));
+        let retries = Arc::new(Mutex::new(Vec::new()));
+
+        let client = {
+            let (requests, responses, retries) =
+                (requests.clone(), responses.clone(), retries.clone());
+            mock.client_factory()
+                .with_retry_policy(
+                    RetryPolicy::new()
+                        .with_max_retries(2)
+                        .with_initial_backoff(Duration::from_millis(1)),
+                )
+                .with_database("db".to_string())
+                .on_request(move |request: &RequestInfo| {
+                    requests.lock().unwrap().push(request.clone())
+                })
+                .on_response(move |response: &ResponseInfo| {
+                    responses.lock().unwrap().push(response.status)
+                })
+                .on_retry(move |event: &RetryEvent| retries.lock().unwrap().push(event.clone()))
+                .build()
+                .await
+                .unwrap()
+        };
+        let before = requests.lock().unwrap().len();
+        assert!(before > 0);
+
+        client.query("SELECT 1").await.unwrap();
+        assert!(client.query("SELECT busy").await.is_err());
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), before + 4);
+        assert!(requests.iter().all(|request| !request.url.contains('?')));
+        assert_eq!(requests[before].method, Method::Post);
+        assert!(requests[before].idempotent);
+        assert_eq!(
+            responses.lock().unwrap()[before..],
+            [Some(200), Some(503), Some(503), Some(503)]
+        );
+        let retries = retries.lock().unwrap();
+        assert_eq!(retries.len(), 2);
+        assert_eq!(
+            (retries[1].attempt, &retries[1].cause),
+            (2, &RetryCause::Status(503))
+        );
+    }
+}
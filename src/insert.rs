@@ -0,0 +1,392 @@
+use crate::client::FireboltClient;
+use crate::error::FireboltError;
+use crate::management::quote_identifier;
+use crate::sql::SqlValue;
+use crate::types::Type;
+use futures_core::Stream;
+use futures_util::StreamExt;
+use std::time::{Duration, Instant};
+
+const DEFAULT_MAX_ROWS_PER_BATCH: usize = 1_000;
+// Keeps each request body well below the server's statement size limit.
+const DEFAULT_MAX_BATCH_BYTES: usize = 8 * 1024 * 1024;
+
+pub type RowValues = Vec<SqlValue>;
+
+#[derive(Debug)]
+pub struct BatchResult {
+    pub rows: usize,
+    pub elapsed: Duration,
+    pub outcome: Result<(), FireboltError>,
+}
+
+impl BatchResult {
+    pub fn is_ok(&self) -> bool {
+        self.outcome.is_ok()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InsertBuilder {
+    table: String,
+    columns: Vec<String>,
+    types: Option<Vec<Type>>,
+    rows: Vec<String>,
+    max_rows_per_batch: usize,
+    max_batch_bytes: usize,
+}
+
+impl InsertBuilder {
+    pub fn new<I, S>(table: &str, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            table: table.to_string(),
+            columns: columns.into_iter().map(Into::into).collect(),
+            types: None,
+            rows: Vec::new(),
+            max_rows_per_batch: DEFAULT_MAX_ROWS_PER_BATCH,
+            max_batch_bytes: DEFAULT_MAX_BATCH_BYTES,
+        }
+    }
+
+    pub fn with_column_types(mut self, types: Vec<Type>) -> Self {
+        self.types = Some(types);
+        self
+    }
+
+    pub fn with_max_rows_per_batch(mut self, max_rows_per_batch: usize) -> Self {
+        self.max_rows_per_batch = max_rows_per_batch.max(1);
+        self
+    }
+
+    pub fn with_max_batch_bytes(mut self, max_batch_bytes: usize) -> Self {
+        self.max_batch_bytes = max_batch_bytes;
+        self
+    }
+
+    pub fn add_row<I, V>(&mut self, row: I) -> Result<(), FireboltError>
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<SqlValue>,
+    {
+        let row = self.render_row(row.into_iter().map(Into::into).collect())?;
+        self.rows.push(row);
+        Ok(())
+    }
+
+    pub fn row<I, V>(mut self, row: I) -> Result<Self, FireboltError>
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<SqlValue>,
+    {
+        self.add_row(row)?;
+        Ok(self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn statements(&self) -> Vec<String> {
+        self.batches()
+            .into_iter()
+            .map(|rows| self.statement(rows))
+            .collect()
+    }
+
+    // Batches run in order and stop at the first failure; the returned list
+    // ends with the failed batch, so rows in later batches were not sent.
+    pub async fn execute(&self, client: &FireboltClient) -> Vec<BatchResult> {
+        let mut results = Vec::new();
+        for rows in self.batches() {
+            if !self.send_batch(client, rows, &mut results).await {
+                break;
+            }
+        }
+        results
+    }
+
+    // Rows already added to the builder are sent first. The next row is only
+    // pulled from the stream once the current batch is full and has been
+    // written, so producers are throttled to the server's pace. An invalid
+    // row aborts with an error; batches sent before it stay committed.
+    pub async fn execute_stream<S>(
+        &self,
+        client: &FireboltClient,
+        rows: S,
+    ) -> Result<Vec<BatchResult>, FireboltError>
+    where
+        S: Stream<Item = RowValues>,
+    {
+        let mut results = Vec::new();
+        for rows in self.batches() {
+            if !self.send_batch(client, rows, &mut results).await {
+                return Ok(results);
+            }
+        }
+
+        let mut rows = std::pin::pin!(rows);
+        let mut batcher = Batcher::new(self);
+        while let Some(row) = rows.next().await {
+            if let Some(batch) = batcher.push(self.render_row(row)?) {
+                if !self.send_batch(client, &batch, &mut results).await {
+                    return Ok(results);
+                }
+            }
+        }
+        let rest = batcher.finish();
+        if !rest.is_empty() {
+            self.send_batch(client, &rest, &mut results).await;
+        }
+        Ok(results)
+    }
+
+    async fn send_batch(
+        &self,
+        client: &FireboltClient,
+        rows: &[String],
+        results: &mut Vec<BatchResult>,
+    ) -> bool {
+        let started = Instant::now();
+        let outcome = client.query(&self.statement(rows)).await.map(|_| ());
+        let ok = outcome.is_ok();
+        results.push(BatchResult {
+            rows: rows.len(),
+            elapsed: started.elapsed(),
+            outcome,
+        });
+        ok
+    }
+
+    fn render_row(&self, values: RowValues) -> Result<String, FireboltError> {
+        if values.len() != self.columns.len() {
+            return Err(FireboltError::Serialization(format!(
+                "Row has {} values but {} columns were specified",
+                values.len(),
+                self.columns.len()
+            )));
+        }
+
+        let literals = match &self.types {
+            Some(types) => values
+                .iter()
+                .zip(types)
+                .zip(&self.columns)
+                .map(|((value, ty), column)| {
+                    value.to_literal_for(ty).map_err(|e| match e {
+                        FireboltError::Serialization(msg) => {
+                            FireboltError::Serialization(format!("Column '{column}': {msg}"))
+                        }
+                        other => other,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => values.iter().map(SqlValue::to_exact_literal).collect(),
+        };
+        Ok(format!("({})", literals.join(", ")))
+    }
+
+    fn header(&self) -> String {
+        let columns: Vec<String> = self.columns.iter().map(|c| quote_identifier(c)).collect();
+        format!(
+            "INSERT INTO {} ({}) VALUES ",
+            quote_identifier(&self.table),
+            columns.join(", ")
+        )
+    }
+
+    fn statement(&self, rows: &[String]) -> String {
+        format!("{}{}", self.header(), rows.join(", "))
+    }
+
+    fn batches(&self) -> Vec<&[String]> {
+        let mut batcher = Batcher::new(self);
+        let mut batches = Vec::new();
+        let mut start = 0;
+        for (index, row) in self.rows.iter().enumerate() {
+            if batcher.fits(row) {
+                batcher.count(row);
+            } else {
+                batches.push(&self.rows[start..index]);
+                start = index;
+                batcher.reset();
+                batcher.count(row);
+            }
+        }
+        if start < self.rows.len() {
+            batches.push(&self.rows[start..]);
+        }
+        batches
+    }
+}
+
+impl FireboltClient {
+    pub async fn insert_stream<S>(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: S,
+    ) -> Result<Vec<BatchResult>, FireboltError>
+    where
+        S: Stream<Item = RowValues>,
+    {
+        InsertBuilder::new(table, columns.iter().copied())
+            .execute_stream(self, rows)
+            .await
+    }
+}
+
+// Tracks the size of the batch being assembled against the row and byte limits.
+struct Batcher {
+    header_len: usize,
+    max_rows: usize,
+    max_bytes: usize,
+    rows: usize,
+    bytes: usize,
+    pending: Vec<String>,
+}
+
+impl Batcher {
+    fn new(builder: &InsertBuilder) -> Self {
+        let header_len = builder.header().len();
+        Self {
+            header_len,
+            max_rows: builder.max_rows_per_batch,
+            max_bytes: builder.max_batch_bytes,
+            rows: 0,
+            bytes: header_len,
+            pending: Vec::new(),
+        }
+    }
+
+    // A batch always takes at least one row, even if that row alone is over the byte limit.
+    fn fits(&self, row: &str) -> bool {
+        self.rows == 0
+            || (self.rows < self.max_rows && self.bytes + row.len() + 2 <= self.max_bytes)
+    }
+
+    fn count(&mut self, row: &str) {
+        self.rows += 1;
+        self.bytes += row.len() + 2;
+    }
+
+    fn reset(&mut self) {
+        self.rows = 0;
+        self.bytes = self.header_len;
+    }
+
+    // Returns the completed batch when `row` does not fit into the current one.
+    fn push(&mut self, row: String) -> Option<Vec<String>> {
+        let full = if self.fits(&row) {
+            None
+        } else {
+            self.reset();
+            Some(std::mem::take(&mut self.pending))
+        };
+        self.count(&row);
+        self.pending.push(row);
+        full
+    }
+
+    fn finish(self) -> Vec<String> {
+        self.pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_statements() {
+        let builder = InsertBuilder::new("orders", ["id", "note"])
+            .with_max_rows_per_batch(2)
+            .row([SqlValue::from(1), SqlValue::from("it's")])
+            .unwrap()
+            .row([SqlValue::from(2), SqlValue::Null])
+            .unwrap()
+            .row([SqlValue::from(3), SqlValue::from(Some("x"))])
+            .unwrap();
+
+        assert_eq!(
+            builder.statements(),
+            vec![
+                "INSERT INTO \"orders\" (\"id\", \"note\") VALUES (1, 'it''s'), (2, NULL)",
+                "INSERT INTO \"orders\" (\"id\", \"note\") VALUES (3, 'x')",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_insert_batches_by_size() {
+        let mut builder = InsertBuilder::new("t", ["v"]).with_max_batch_bytes(40);
+        for _ in 0..4 {
+            builder.add_row(["0123456789"]).unwrap();
+        }
+        let statements = builder.statements();
+        assert_eq!(statements.len(), 4);
+        assert!(statements.iter().all(|s| s.ends_with("('0123456789')")));
+    }
+
+    #[test]
+    fn test_batcher_push() {
+        let builder = InsertBuilder::new("t", ["v"]).with_max_rows_per_batch(2);
+        let mut batcher = Batcher::new(&builder);
+        assert_eq!(batcher.push("(1)".to_string()), None);
+        assert_eq!(batcher.push("(2)".to_string()), None);
+        assert_eq!(
+            batcher.push("(3)".to_string()),
+            Some(vec!["(1)".to_string(), "(2)".to_string()])
+        );
+        assert_eq!(batcher.finish(), vec!["(3)".to_string()]);
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_insert_exact_decimals() {
+        let amount: rust_decimal::Decimal = "1234567890123456789.0123".parse().unwrap();
+        let literal = "CAST('1234567890123456789.0123' AS decimal(38, 4))";
+
+        let untyped = InsertBuilder::new("t", ["amount"])
+            .row([SqlValue::from(amount)])
+            .unwrap();
+        assert_eq!(
+            untyped.statements()[0],
+            format!("INSERT INTO \"t\" (\"amount\") VALUES ({literal})")
+        );
+
+        let typed = InsertBuilder::new("t", ["amount"])
+            .with_column_types(vec![Type::Decimal])
+            .row([SqlValue::from(amount)])
+            .unwrap();
+        assert_eq!(typed.statements(), untyped.statements());
+    }
+
+    #[test]
+    fn test_insert_validates_rows() {
+        let mut builder =
+            InsertBuilder::new("t", ["id", "day"]).with_column_types(vec![Type::Int, Type::Date]);
+
+        builder
+            .add_row([SqlValue::from(1), SqlValue::from("2024-01-01")])
+            .unwrap();
+        assert_eq!(
+            builder.statements()[0],
+            "INSERT INTO \"t\" (\"id\", \"day\") VALUES (1, '2024-01-01'::date)"
+        );
+
+        assert!(builder.add_row([SqlValue::from(1)]).is_err());
+        match builder.add_row([SqlValue::from("x"), SqlValue::Null]) {
+            Err(FireboltError::Serialization(msg)) => assert!(msg.contains("Column 'id'")),
+            other => panic!("Expected serialization error, got {other:?}"),
+        }
+        assert_eq!(builder.len(), 1);
+    }
+}
@@ -1,13 +1,68 @@
 pub mod auth;
+#[cfg(feature = "avro")]
+pub mod avro;
+pub mod batch;
+pub mod catalog;
 pub mod client;
+pub mod config;
+pub mod copy;
+#[cfg(feature = "polars")]
+pub mod dataframe;
+pub mod de;
+#[cfg(feature = "diesel")]
+pub mod diesel;
+pub mod dns;
+mod engine_url_cache;
 pub mod error;
+pub mod executor;
+pub mod export;
+pub mod hooks;
+pub mod insert;
+pub mod management;
+pub mod paging;
 pub mod parser;
+pub mod progress;
+pub mod query_builder;
 pub mod result;
+mod result_cache;
+pub mod retry;
+mod runtime;
+pub mod session_state;
+mod shutdown;
+pub mod spill;
+pub mod sql;
+#[cfg(feature = "sqlx")]
+pub mod sqlx;
+pub mod statement;
+mod telemetry;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(any(feature = "reqwest", feature = "hyper"))]
+mod timing;
+pub mod transaction;
+pub mod transport;
 pub mod types;
 pub mod version;
 
+#[cfg(feature = "reqwest")]
 pub use auth::authenticate;
-pub use client::{FireboltClient, FireboltClientFactory};
-pub use error::FireboltError;
-pub use result::{ResultSet, Row};
-pub use types::{Column, ColumnRef, Type};
+pub use auth::authenticate_with_transport;
+pub use client::{
+    AccountInfo, FireboltClient, FireboltClientFactory, PingResult, QueryOptions, QueryTiming,
+    ResponseMetadata, ServerInfo,
+};
+pub use config::ClientConfig;
+pub use error::{ErrorKind, FireboltError};
+pub use executor::Queryable;
+pub use hooks::{RequestInfo, ResponseInfo};
+pub use progress::Progress;
+pub use result::{ResultSet, Row, ServerWarning};
+pub use retry::{RetryCause, RetryEvent, RetryPolicy, RetryStrategy};
+pub use secrecy::{ExposeSecret, SecretString};
+pub use session_state::SessionState;
+pub use sql::SqlValue;
+pub use statement::{ParameterBinding, Statement};
+pub use tokio_util::sync::CancellationToken;
+pub use transaction::Transaction;
+pub use transport::{TransferTiming, Transport};
+pub use types::{BytesEncoding, Column, ColumnRef, ConversionMode, FireboltValue, Type};
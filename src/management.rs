@@ -0,0 +1,488 @@
+use crate::client::FireboltClient;
+use crate::error::FireboltError;
+use secrecy::SecretString;
+use serde::Deserialize;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+const ENGINE_COLUMNS: &str = "engine_name, status, type, nodes, clusters";
+const METRICS_COLUMNS: &str = "event_time, cpu_used, memory_used, disk_used, cache_hit_ratio, \
+                               spilled_bytes, running_queries, suspended_queries";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineStatus {
+    Running,
+    Starting,
+    Stopping,
+    Stopped,
+    Resizing,
+    Draining,
+    Failed,
+    Other(String),
+}
+
+impl From<&str> for EngineStatus {
+    fn from(status: &str) -> Self {
+        match status.trim().to_ascii_uppercase().as_str() {
+            "RUNNING" => EngineStatus::Running,
+            "STARTING" => EngineStatus::Starting,
+            "STOPPING" => EngineStatus::Stopping,
+            "STOPPED" => EngineStatus::Stopped,
+            "RESIZING" => EngineStatus::Resizing,
+            "DRAINING" => EngineStatus::Draining,
+            "FAILED" => EngineStatus::Failed,
+            _ => EngineStatus::Other(status.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for EngineStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineStatus::Running => f.write_str("RUNNING"),
+            EngineStatus::Starting => f.write_str("STARTING"),
+            EngineStatus::Stopping => f.write_str("STOPPING"),
+            EngineStatus::Stopped => f.write_str("STOPPED"),
+            EngineStatus::Resizing => f.write_str("RESIZING"),
+            EngineStatus::Draining => f.write_str("DRAINING"),
+            EngineStatus::Failed => f.write_str("FAILED"),
+            EngineStatus::Other(status) => f.write_str(status),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineInfo {
+    pub name: String,
+    pub status: EngineStatus,
+    pub engine_type: Option<String>,
+    pub nodes: Option<i64>,
+    pub clusters: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct EngineRow {
+    engine_name: String,
+    status: String,
+    #[serde(rename = "type")]
+    engine_type: Option<String>,
+    nodes: Option<i64>,
+    clusters: Option<i64>,
+}
+
+impl From<EngineRow> for EngineInfo {
+    fn from(row: EngineRow) -> Self {
+        EngineInfo {
+            name: row.engine_name,
+            status: EngineStatus::from(row.status.as_str()),
+            engine_type: row.engine_type,
+            nodes: row.nodes,
+            clusters: row.clusters,
+        }
+    }
+}
+
+// One sample from `information_schema.engine_metrics_history`. Utilization is
+// a percentage of the engine's capacity. Queued queries are the ones the
+// engine has suspended until resources free up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineMetrics {
+    pub sampled_at: chrono::DateTime<chrono::Utc>,
+    pub cpu_percent: f64,
+    pub memory_percent: f64,
+    pub disk_percent: f64,
+    pub cache_hit_ratio: Option<f64>,
+    pub spilled_bytes: i64,
+    pub running_queries: i64,
+    pub queued_queries: i64,
+}
+
+impl EngineMetrics {
+    fn from_row(row: &crate::result::Row) -> Result<Self, FireboltError> {
+        Ok(EngineMetrics {
+            sampled_at: row.get("event_time")?,
+            cpu_percent: row.get("cpu_used")?,
+            memory_percent: row.get("memory_used")?,
+            disk_percent: row.get("disk_used")?,
+            cache_hit_ratio: row.get("cache_hit_ratio")?,
+            spilled_bytes: row.get("spilled_bytes")?,
+            running_queries: row.get("running_queries")?,
+            queued_queries: row.get("suspended_queries")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ServiceAccountInfo {
+    #[serde(rename = "service_account_name")]
+    pub name: String,
+    #[serde(rename = "service_account_description")]
+    pub description: Option<String>,
+    // The client ID used to authenticate; empty until a key is generated.
+    #[serde(rename = "service_account_id")]
+    pub client_id: Option<String>,
+}
+
+// Credentials returned by `rotate_service_account_secret`. The secret is only
+// shown once, so store it before dropping this.
+#[derive(Debug, Clone)]
+pub struct ServiceAccountKey {
+    pub name: String,
+    pub client_id: String,
+    pub client_secret: SecretString,
+}
+
+pub(crate) fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+impl FireboltClient {
+    pub async fn list_engines(&self) -> Result<Vec<EngineInfo>, FireboltError> {
+        let sql =
+            format!("SELECT {ENGINE_COLUMNS} FROM information_schema.engines ORDER BY engine_name");
+        self.query_engines(&sql).await
+    }
+
+    pub async fn engine_info(&self, name: &str) -> Result<EngineInfo, FireboltError> {
+        let sql = format!(
+            "SELECT {ENGINE_COLUMNS} FROM information_schema.engines WHERE engine_name = {}",
+            crate::sql::quote(name)
+        );
+        self.query_engines(&sql)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| FireboltError::Configuration(format!("Engine '{name}' not found")))
+    }
+
+    pub async fn engine_status(&self, name: &str) -> Result<EngineStatus, FireboltError> {
+        Ok(self.engine_info(name).await?.status)
+    }
+
+    pub async fn start_engine(&self, name: &str) -> Result<(), FireboltError> {
+        self.query(&format!("START ENGINE {}", quote_identifier(name)))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn stop_engine(&self, name: &str) -> Result<(), FireboltError> {
+        self.query(&format!("STOP ENGINE {}", quote_identifier(name)))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn wait_for_engine_status(
+        &self,
+        name: &str,
+        target: EngineStatus,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<EngineInfo, FireboltError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let info = self.engine_info(name).await?;
+            if info.status == target {
+                return Ok(info);
+            }
+            if info.status == EngineStatus::Failed {
+                return Err(FireboltError::Query(format!(
+                    "Engine '{name}' failed while waiting for {target}"
+                )));
+            }
+            if Instant::now() + poll_interval > deadline {
+                return Err(FireboltError::Timeout(format!(
+                    "Engine '{name}' did not reach {target} within {timeout:?}, last status {}",
+                    info.status
+                )));
+            }
+            crate::runtime::sleep(poll_interval).await;
+        }
+    }
+
+    pub async fn list_service_accounts(&self) -> Result<Vec<ServiceAccountInfo>, FireboltError> {
+        self.query(
+            "SELECT service_account_name, service_account_description, service_account_id \
+             FROM information_schema.service_accounts ORDER BY service_account_name",
+        )
+        .await?
+        .rows
+        .iter()
+        .map(|row| row.deserialize())
+        .collect()
+    }
+
+    pub async fn create_service_account(
+        &self,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<(), FireboltError> {
+        let mut sql = format!("CREATE SERVICE ACCOUNT {}", quote_identifier(name));
+        if let Some(description) = description {
+            sql.push_str(&format!(
+                " WITH DESCRIPTION = {}",
+                crate::sql::quote(description)
+            ));
+        }
+        self.query_non_idempotent(&sql).await.map(|_| ())
+    }
+
+    pub async fn drop_service_account(&self, name: &str) -> Result<(), FireboltError> {
+        self.query(&format!("DROP SERVICE ACCOUNT {}", quote_identifier(name)))
+            .await
+            .map(|_| ())
+    }
+
+    // Generates a new secret for the service account, which invalidates the
+    // previous one. Never retried, since a retry would rotate the secret again
+    // and lose the one generated first.
+    pub async fn rotate_service_account_secret(
+        &self,
+        name: &str,
+    ) -> Result<ServiceAccountKey, FireboltError> {
+        let result = self
+            .query_non_idempotent(&format!(
+                "CALL fb_GENERATESERVICEACCOUNTKEY({})",
+                crate::sql::quote(name)
+            ))
+            .await?;
+        let row = result.rows.first().ok_or_else(|| {
+            FireboltError::Query(format!("No key was returned for service account '{name}'"))
+        })?;
+        Ok(ServiceAccountKey {
+            name: row.get("service_account_name")?,
+            client_id: row.get("service_account_id")?,
+            client_secret: SecretString::from(row.get::<String>("secret")?),
+        })
+    }
+
+    // The most recent metrics sample of the engine the client is connected to.
+    // Unlike the other helpers here, this must run on the engine itself rather
+    // than on a system engine client.
+    pub async fn engine_metrics(&self) -> Result<EngineMetrics, FireboltError> {
+        let sql = format!(
+            "SELECT {METRICS_COLUMNS} FROM information_schema.engine_metrics_history \
+             ORDER BY event_time DESC LIMIT 1"
+        );
+        self.query_metrics(&sql).await?.pop().ok_or_else(|| {
+            FireboltError::Query("The engine has not reported any metrics yet".to_string())
+        })
+    }
+
+    // Samples from the last `window`, oldest first.
+    pub async fn engine_metrics_history(
+        &self,
+        window: Duration,
+    ) -> Result<Vec<EngineMetrics>, FireboltError> {
+        let sql = format!(
+            "SELECT {METRICS_COLUMNS} FROM information_schema.engine_metrics_history \
+             WHERE event_time > NOW() - INTERVAL '{} seconds' ORDER BY event_time",
+            window.as_secs().max(1)
+        );
+        self.query_metrics(&sql).await
+    }
+
+    async fn query_metrics(&self, sql: &str) -> Result<Vec<EngineMetrics>, FireboltError> {
+        self.query(sql)
+            .await?
+            .rows
+            .iter()
+            .map(EngineMetrics::from_row)
+            .collect()
+    }
+
+    async fn query_engines(&self, sql: &str) -> Result<Vec<EngineInfo>, FireboltError> {
+        self.query(sql)
+            .await?
+            .rows
+            .iter()
+            .map(|row| row.deserialize::<EngineRow>().map(EngineInfo::from))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::result::Row;
+    use crate::types::{Column, Type};
+
+    #[test]
+    fn test_engine_status_round_trip() {
+        for status in ["RUNNING", "STARTING", "STOPPED", "FAILED"] {
+            assert_eq!(EngineStatus::from(status).to_string(), status);
+        }
+        assert_eq!(EngineStatus::from("running"), EngineStatus::Running);
+        assert_eq!(
+            EngineStatus::from("HIBERNATING"),
+            EngineStatus::Other("HIBERNATING".to_string())
+        );
+    }
+
+    #[test]
+    fn test_quoting() {
+        assert_eq!(quote_identifier("my \"engine\""), "\"my \"\"engine\"\"\"");
+    }
+
+    #[test]
+    fn test_engine_row_deserialization() {
+        let column = |name: &str, r#type: Type| Column {
+            name: name.to_string(),
+            r#type,
+            precision: None,
+            scale: None,
+            is_nullable: true,
+        };
+        let columns = vec![
+            column("engine_name", Type::Text),
+            column("status", Type::Text),
+            column("type", Type::Text),
+            column("nodes", Type::Int),
+            column("clusters", Type::Int),
+        ];
+        let row = Row::new(
+            vec![
+                serde_json::json!("my_engine"),
+                serde_json::json!("RUNNING"),
+                serde_json::json!("S"),
+                serde_json::json!(2),
+                serde_json::Value::Null,
+            ],
+            columns,
+        );
+
+        let info = EngineInfo::from(row.deserialize::<EngineRow>().unwrap());
+        assert_eq!(
+            info,
+            EngineInfo {
+                name: "my_engine".to_string(),
+                status: EngineStatus::Running,
+                engine_type: Some("S".to_string()),
+                nodes: Some(2),
+                clusters: None,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_engine_metrics() {
+        let mock = crate::testing::MockFirebolt::new();
+        mock.on_query_containing(
+            "engine_metrics_history",
+            crate::testing::MockResponse::rows(
+                &[
+                    ("event_time", "timestamptz"),
+                    ("cpu_used", "double"),
+                    ("memory_used", "double"),
+                    ("disk_used", "double"),
+                    ("cache_hit_ratio", "double null"),
+                    ("spilled_bytes", "bigint"),
+                    ("running_queries", "bigint"),
+                    ("suspended_queries", "bigint"),
+                ],
+                vec![vec![
+                    "2024-05-01 12:00:00+00".into(),
+                    72.5.into(),
+                    40.into(),
+                    12.25.into(),
+                    serde_json::Value::Null,
+                    "1048576".into(),
+                    3.into(),
+                    1.into(),
+                ]],
+            ),
+        );
+        let client = mock.connect().await.unwrap();
+
+        let metrics = client.engine_metrics().await.unwrap();
+        assert_eq!(
+            metrics,
+            EngineMetrics {
+                sampled_at: "2024-05-01T12:00:00Z".parse().unwrap(),
+                cpu_percent: 72.5,
+                memory_percent: 40.0,
+                disk_percent: 12.25,
+                cache_hit_ratio: None,
+                spilled_bytes: 1_048_576,
+                running_queries: 3,
+                queued_queries: 1,
+            }
+        );
+
+        let history = client
+            .engine_metrics_history(Duration::from_secs(300))
+            .await
+            .unwrap();
+        assert_eq!(history, [metrics]);
+        let sql = mock.queries().last().unwrap().sql.clone();
+        assert!(
+            sql.contains("INTERVAL '300 seconds' ORDER BY event_time"),
+            "{sql}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_service_accounts() {
+        use secrecy::ExposeSecret;
+
+        let mock = crate::testing::MockFirebolt::new();
+        mock.on_query_containing(
+            "information_schema.service_accounts",
+            crate::testing::MockResponse::rows(
+                &[
+                    ("service_account_name", "text"),
+                    ("service_account_description", "text null"),
+                    ("service_account_id", "text null"),
+                ],
+                vec![vec!["loader".into(), serde_json::Value::Null, "id1".into()]],
+            ),
+        );
+        mock.on_query_containing(
+            "fb_GENERATESERVICEACCOUNTKEY",
+            crate::testing::MockResponse::rows(
+                &[
+                    ("service_account_name", "text"),
+                    ("service_account_id", "text"),
+                    ("secret", "text"),
+                ],
+                vec![vec!["loader".into(), "id2".into(), "s3cr3t".into()]],
+            ),
+        );
+        let client = mock.connect().await.unwrap();
+        let before = mock.queries().len();
+
+        client
+            .create_service_account("loader", Some("nightly 'etl'"))
+            .await
+            .unwrap();
+        let key = client
+            .rotate_service_account_secret("loader")
+            .await
+            .unwrap();
+        assert_eq!(
+            (key.name.as_str(), key.client_id.as_str()),
+            ("loader", "id2")
+        );
+        assert_eq!(key.client_secret.expose_secret(), "s3cr3t");
+        assert!(!format!("{key:?}").contains("s3cr3t"));
+        assert_eq!(
+            client.list_service_accounts().await.unwrap(),
+            [ServiceAccountInfo {
+                name: "loader".to_string(),
+                description: None,
+                client_id: Some("id1".to_string()),
+            }]
+        );
+        client.drop_service_account("loader").await.unwrap();
+
+        let statements: Vec<String> = mock.queries()[before..]
+            .iter()
+            .map(|query| query.sql.clone())
+            .collect();
+        assert_eq!(
+            statements[0],
+            r#"CREATE SERVICE ACCOUNT "loader" WITH DESCRIPTION = 'nightly ''etl'''"#
+        );
+        assert_eq!(statements[1], "CALL fb_GENERATESERVICEACCOUNTKEY('loader')");
+        assert_eq!(statements[3], r#"DROP SERVICE ACCOUNT "loader""#);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::client::FireboltClient;
+use crate::error::FireboltError;
+use crate::result::ResultSet;
+
+// Reads a query's result one page at a time by wrapping it in LIMIT/OFFSET.
+// Each page is a separate query, so the statement needs an ORDER BY that
+// gives a stable order for pages not to overlap or skip rows.
+#[derive(Debug, Clone)]
+pub struct QueryPages {
+    client: FireboltClient,
+    sql: String,
+    page_size: usize,
+    offset: usize,
+    done: bool,
+}
+
+impl QueryPages {
+    // Returns `None` once every row has been read. One extra row is requested
+    // per page to tell whether another page follows.
+    pub async fn next_page(&mut self) -> Result<Option<ResultSet>, FireboltError> {
+        if self.done {
+            return Ok(None);
+        }
+        let sql = page_statement(&self.sql, self.page_size + 1, self.offset);
+        let page = self
+            .client
+            .query_with_max_rows(&sql, self.page_size)
+            .await?;
+        self.done = !page.is_truncated();
+        self.offset += page.rows.len();
+        if page.rows.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(page.with_truncated(false)))
+    }
+
+    pub fn has_more(&self) -> bool {
+        !self.done
+    }
+
+    // The number of rows returned so far, which is also the offset of the
+    // next page.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+fn page_statement(sql: &str, limit: usize, offset: usize) -> String {
+    let sql = sql.trim().trim_end_matches(';').trim_end();
+    // The newline ends a trailing line comment before the closing parenthesis.
+    format!("SELECT * FROM (\n{sql}\n) AS page LIMIT {limit} OFFSET {offset}")
+}
+
+impl FireboltClient {
+    pub fn query_paged(&self, sql: &str, page_size: usize) -> Result<QueryPages, FireboltError> {
+        if page_size == 0 {
+            return Err(FireboltError::Configuration(
+                "Page size must be greater than zero".to_string(),
+            ));
+        }
+        Ok(QueryPages {
+            client: self.clone(),
+            sql: sql.to_string(),
+            page_size,
+            offset: 0,
+            done: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockFirebolt, MockResponse};
+
+    #[test]
+    fn test_page_statement() {
+        assert_eq!(
+            page_statement("SELECT id FROM t ORDER BY id; ", 11, 20),
+            "SELECT * FROM (\nSELECT id FROM t ORDER BY id\n) AS page LIMIT 11 OFFSET 20"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_paged() {
+        let mock = MockFirebolt::new();
+        let rows = |ids: &[i32]| {
+            MockResponse::rows(
+                &[("id", "int")],
+                ids.iter().map(|id| vec![(*id).into()]).collect(),
+            )
+        };
+        mock.on_query_containing("LIMIT 3 OFFSET 0", rows(&[1, 2, 3]));
+        mock.on_query_containing("LIMIT 3 OFFSET 2", rows(&[3, 4, 5]));
+        mock.on_query_containing("LIMIT 3 OFFSET 4", rows(&[5]));
+        let client = mock.connect().await.unwrap();
+
+        let mut pages = client
+            .query_paged("SELECT id FROM t ORDER BY id", 2)
+            .unwrap();
+        let mut ids = Vec::new();
+        while let Some(page) = pages.next_page().await.unwrap() {
+            assert!(!page.is_truncated());
+            for row in &page.rows {
+                ids.push(row.get::<i32>("id").unwrap());
+            }
+        }
+        assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+        assert_eq!(pages.offset(), 5);
+        assert!(!pages.has_more());
+        assert_eq!(mock.queries().len(), 3);
+
+        assert!(matches!(
+            client.query_paged("SELECT 1", 0),
+            Err(FireboltError::Configuration(_))
+        ));
+    }
+}
@@ -1,9 +1,10 @@
 use crate::error::FireboltError;
-use crate::result::ResultSet;
+use crate::result::{ResultSet, ServerWarning};
 use crate::types::{Column, Type};
-use regex::Regex;
 
-fn parse_type(type_str: &str) -> Result<(Type, bool, Option<i32>, Option<i32>), FireboltError> {
+pub(crate) fn parse_type(
+    type_str: &str,
+) -> Result<(Type, bool, Option<i32>, Option<i32>), FireboltError> {
     let is_nullable = type_str.ends_with(" null");
     let clean_type = if is_nullable {
         &type_str[..type_str.len() - 5]
@@ -11,16 +12,14 @@ fn parse_type(type_str: &str) -> Result<(Type, bool, Option<i32>, Option<i32>),
         type_str
     };
 
-    if let Ok(decimal_regex) = Regex::new(r"(?i)decimal\((\d+),\s*(\d+)\)") {
-        if let Some(captures) = decimal_regex.captures(clean_type) {
-            let precision = captures[1]
-                .parse()
-                .map_err(|_| FireboltError::Query("Invalid decimal precision".to_string()))?;
-            let scale = captures[2]
-                .parse()
-                .map_err(|_| FireboltError::Query("Invalid decimal scale".to_string()))?;
-            return Ok((Type::Decimal, is_nullable, Some(precision), Some(scale)));
-        }
+    if let Some((precision, scale)) = decimal_arguments(clean_type) {
+        let precision = precision
+            .parse()
+            .map_err(|_| FireboltError::Query("Invalid decimal precision".to_string()))?;
+        let scale = scale
+            .parse()
+            .map_err(|_| FireboltError::Query("Invalid decimal scale".to_string()))?;
+        return Ok((Type::Decimal, is_nullable, Some(precision), Some(scale)));
     }
 
     if clean_type.starts_with("array") {
@@ -40,6 +39,8 @@ fn parse_type(type_str: &str) -> Result<(Type, bool, Option<i32>, Option<i32>),
         "bool" | "boolean" => Type::Boolean,
         "bytea" => Type::Bytes,
         "geography" => Type::Geography,
+        "json" => Type::Json,
+        "interval" => Type::Interval,
         _ if clean_type.starts_with("struct") => Type::Struct,
         _ => {
             return Err(FireboltError::Query(format!(
@@ -51,11 +52,48 @@ fn parse_type(type_str: &str) -> Result<(Type, bool, Option<i32>, Option<i32>),
     Ok((base_type, is_nullable, None, None))
 }
 
+// Like `parse_type`, but also accepts DDL and information_schema spellings
+// such as `INTEGER`, `real`, `double precision` or `numeric(10, 2)`, mapping
+// them onto the names used in query result metadata first.
+pub(crate) fn parse_sql_type(
+    type_str: &str,
+) -> Result<(Type, bool, Option<i32>, Option<i32>), FireboltError> {
+    let type_str = type_str.trim().to_ascii_lowercase();
+    let (base, nullable) = match type_str.strip_suffix(" null") {
+        Some(base) => (base.trim_end(), " null"),
+        None => (type_str.as_str(), ""),
+    };
+    let base = match base {
+        "integer" => "int".to_string(),
+        "real" => "float4".to_string(),
+        "double precision" => "double".to_string(),
+        other => match other.strip_prefix("numeric") {
+            Some(rest) => format!("decimal{rest}"),
+            None => other.to_string(),
+        },
+    };
+    parse_type(&format!("{base}{nullable}"))
+}
+
+// Finds `decimal(<precision>, <scale>)`, ignoring case, and returns the two
+// digit strings.
+fn decimal_arguments(type_str: &str) -> Option<(&str, &str)> {
+    let start = type_str.to_ascii_lowercase().find("decimal(")? + "decimal(".len();
+    let (arguments, _) = type_str[start..].split_once(')')?;
+    let (precision, scale) = arguments.split_once(',')?;
+    let scale = scale.trim_start();
+    let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    (is_digits(precision) && is_digits(scale)).then_some((precision, scale))
+}
+
 pub fn parse_columns(json: &serde_json::Value) -> Result<Vec<Column>, FireboltError> {
     let meta = json.get("meta").and_then(|m| m.as_array()).ok_or_else(|| {
         FireboltError::Query("Missing or invalid 'meta' field in response".to_string())
     })?;
+    parse_column_list(meta)
+}
 
+pub(crate) fn parse_column_list(meta: &[serde_json::Value]) -> Result<Vec<Column>, FireboltError> {
     meta.iter()
         .map(|col| {
             let name = col
@@ -86,11 +124,23 @@ pub fn parse_data(
     json: &serde_json::Value,
     columns: &[Column],
 ) -> Result<Vec<crate::result::Row>, FireboltError> {
+    parse_data_limited(json, columns, None).map(|(rows, _)| rows)
+}
+
+// Stops after `max_rows` rows and reports whether any were left out.
+fn parse_data_limited(
+    json: &serde_json::Value,
+    columns: &[Column],
+    max_rows: Option<usize>,
+) -> Result<(Vec<crate::result::Row>, bool), FireboltError> {
     let data = json.get("data").and_then(|d| d.as_array()).ok_or_else(|| {
         FireboltError::Query("Missing or invalid 'data' field in response".to_string())
     })?;
+    let limit = max_rows.unwrap_or(usize::MAX);
 
-    data.iter()
+    let rows = data
+        .iter()
+        .take(limit)
         .map(|row_array| {
             let row_values: Vec<serde_json::Value> = row_array
                 .as_array()
@@ -99,27 +149,102 @@ pub fn parse_data(
 
             Ok(crate::result::Row::new(row_values, columns.to_vec()))
         })
-        .collect()
+        .collect::<Result<_, FireboltError>>()?;
+    Ok((rows, data.len() > limit))
 }
 
 pub fn parse_response(body: String) -> Result<ResultSet, FireboltError> {
-    let json: serde_json::Value = serde_json::from_str(&body)
-        .map_err(|e| FireboltError::Serialization(format!("Failed to parse JSON: {e}")))?;
+    let json = parse_json(&body)?;
+    parse_response_json(&json)
+}
+
+pub fn parse_json(body: &str) -> Result<serde_json::Value, FireboltError> {
+    serde_json::from_str(body)
+        .map_err(|e| FireboltError::Serialization(format!("Failed to parse JSON: {e}")))
+}
 
-    let columns = parse_columns(&json)?;
-    let rows = parse_data(&json, &columns)?;
+pub fn parse_response_json(json: &serde_json::Value) -> Result<ResultSet, FireboltError> {
+    parse_limited_response_json(json, None)
+}
 
-    Ok(ResultSet { columns, rows })
+pub(crate) fn parse_limited_response_json(
+    json: &serde_json::Value,
+    max_rows: Option<usize>,
+) -> Result<ResultSet, FireboltError> {
+    let columns = parse_columns(json)?;
+    let (rows, truncated) = parse_data_limited(json, &columns, max_rows)?;
+
+    Ok(ResultSet::new(columns, rows)
+        .with_warnings(parse_warnings(json))
+        .with_truncated(truncated))
+}
+
+// Warnings arrive either in a `warnings` array or as entries of `errors`
+// whose severity is below ERROR. Entries may be plain strings or objects.
+pub fn parse_warnings(json: &serde_json::Value) -> Vec<ServerWarning> {
+    let warnings = json
+        .get("warnings")
+        .and_then(|w| w.as_array())
+        .into_iter()
+        .flatten();
+    let notices = json
+        .get("errors")
+        .and_then(|e| e.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|entry| {
+            entry
+                .get("severity")
+                .and_then(|s| s.as_str())
+                .is_some_and(|s| matches!(s.to_ascii_uppercase().as_str(), "WARNING" | "NOTICE"))
+        });
+
+    warnings
+        .chain(notices)
+        .filter_map(|entry| match entry {
+            serde_json::Value::String(message) => Some(ServerWarning {
+                code: None,
+                message: message.clone(),
+            }),
+            serde_json::Value::Object(fields) => {
+                let message = ["description", "message"]
+                    .iter()
+                    .find_map(|key| fields.get(*key).and_then(|m| m.as_str()))?;
+                Some(ServerWarning {
+                    code: fields
+                        .get("code")
+                        .and_then(|c| c.as_str())
+                        .map(str::to_string),
+                    message: message.to_string(),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn parse_query_id(json: &serde_json::Value) -> Option<&str> {
+    json.get("query")
+        .and_then(|q| q.get("query_id"))
+        .and_then(|id| id.as_str())
 }
 
 pub fn parse_server_error(body: String) -> FireboltError {
     FireboltError::Query(format!("Server error: {body}"))
 }
 
+// Rate limiting and gateway or overload responses are transient; anything
+// else the server rejects is reported as a query error.
+pub(crate) fn parse_error_response(status: u16, body: String) -> FireboltError {
+    match status {
+        429 | 502 | 503 | 504 => FireboltError::Unavailable(format!("HTTP {status}: {body}")),
+        _ => parse_server_error(body),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::Type;
 
     #[test]
     fn test_parse_type_basic_types() {
@@ -175,12 +300,46 @@ mod tests {
             parse_type("geography").unwrap(),
             (Type::Geography, false, None, None)
         );
+        assert_eq!(parse_type("json").unwrap(), (Type::Json, false, None, None));
+        assert_eq!(
+            parse_type("interval").unwrap(),
+            (Type::Interval, false, None, None)
+        );
         assert_eq!(
             parse_type("array(int)").unwrap(),
             (Type::Array, false, None, None)
         );
     }
 
+    #[test]
+    fn test_type_display_round_trip() {
+        for type_str in [
+            "int",
+            "bigint",
+            "double",
+            "text null",
+            "decimal(38, 30)",
+            "bytea null",
+        ] {
+            let column = Column::from_sql_type("c", type_str).unwrap();
+            assert_eq!(column.to_string(), type_str);
+        }
+        assert_eq!(
+            Column::from_sql_type("c", "NUMERIC(10,2) NULL")
+                .unwrap()
+                .to_string(),
+            "decimal(10, 2) null"
+        );
+        assert_eq!(Type::from_sql_str("INTEGER").unwrap(), Type::Int);
+        assert_eq!(
+            Type::from_sql_str("decimal(38, 30) null").unwrap(),
+            Type::Decimal
+        );
+        assert_eq!(Type::Float.to_string(), "real");
+        assert_eq!(Type::from_sql_str("real").unwrap(), Type::Float);
+        assert!(Type::from_sql_str("varchar2").is_err());
+    }
+
     #[test]
     fn test_parse_type_nullable() {
         assert_eq!(
@@ -342,6 +501,48 @@ mod tests {
         assert!(matches!(result.unwrap_err(), FireboltError::Query(_)));
     }
 
+    #[test]
+    fn test_parse_warnings() {
+        let json = parse_json(
+            r#"{
+                "meta": [], "data": [],
+                "warnings": ["Function X is deprecated"],
+                "errors": [
+                    {"code": "TRUNC", "severity": "WARNING", "description": "Result truncated"},
+                    {"severity": "ERROR", "description": "ignored"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let result = parse_response_json(&json).unwrap();
+        assert_eq!(
+            result.warnings(),
+            [
+                ServerWarning {
+                    code: None,
+                    message: "Function X is deprecated".to_string(),
+                },
+                ServerWarning {
+                    code: Some("TRUNC".to_string()),
+                    message: "Result truncated".to_string(),
+                },
+            ]
+        );
+
+        let json = parse_json(r#"{"meta": [], "data": []}"#).unwrap();
+        assert!(parse_response_json(&json).unwrap().warnings().is_empty());
+    }
+
+    #[test]
+    fn test_parse_query_id() {
+        let json =
+            parse_json(r#"{"query": {"query_id": "17EC6A1A"}, "meta": [], "data": []}"#).unwrap();
+        assert_eq!(parse_query_id(&json), Some("17EC6A1A"));
+
+        let json = parse_json(r#"{"meta": [], "data": []}"#).unwrap();
+        assert_eq!(parse_query_id(&json), None);
+    }
+
     #[test]
     fn test_parse_server_error() {
         let error_body = "Internal Server Error".to_string();
@@ -349,4 +550,20 @@ mod tests {
         assert!(matches!(result, FireboltError::Query(_)));
         assert!(format!("{result:?}").contains("Server error: Internal Server Error"));
     }
+
+    #[test]
+    fn test_parse_error_response() {
+        assert!(matches!(
+            parse_error_response(503, "Engine is starting".to_string()),
+            FireboltError::Unavailable(msg) if msg == "HTTP 503: Engine is starting"
+        ));
+        assert!(matches!(
+            parse_error_response(429, String::new()),
+            FireboltError::Unavailable(_)
+        ));
+        assert!(matches!(
+            parse_error_response(400, "syntax error".to_string()),
+            FireboltError::Query(_)
+        ));
+    }
 }
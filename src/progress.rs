@@ -0,0 +1,148 @@
+// Live progress for long-running queries. The running statement is found in
+// `information_schema.engine_running_queries` by its `query_label`, the same
+// way `query_cancellable` finds it.
+
+use crate::client::{is_read_only, FireboltClient};
+use crate::error::FireboltError;
+use crate::result::ResultSet;
+use crate::telemetry::trace_event;
+use futures_util::future::{self, Either};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub rows_scanned: u64,
+    pub bytes_scanned: u64,
+    pub elapsed: Duration,
+}
+
+impl FireboltClient {
+    // Runs `sql` and calls `on_progress` every `interval` while it runs on the
+    // engine. Polling is best effort: a poll that fails or finds the query not
+    // yet listed is skipped, and never fails the query itself.
+    pub async fn query_with_progress<F>(
+        &self,
+        sql: &str,
+        interval: Duration,
+        mut on_progress: F,
+    ) -> Result<ResultSet, FireboltError>
+    where
+        F: FnMut(&Progress),
+    {
+        self.connected().await?;
+        let mut params = self.request_params();
+        let label = params
+            .entry("query_label".to_string())
+            .or_insert_with(|| uuid::Uuid::new_v4().to_string())
+            .clone();
+
+        let query =
+            Box::pin(self.run_query_with_params(sql, params, is_read_only(sql), self.max_rows()));
+        let poll = Box::pin(async {
+            loop {
+                crate::runtime::sleep(interval).await;
+                if let Some(progress) = self.poll_progress(&label).await {
+                    on_progress(&progress);
+                }
+            }
+        });
+        let result = match future::select(query, poll).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => unreachable!("progress polling runs until the query finishes"),
+        };
+        result.map(|(result, _)| result)
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    async fn poll_progress(&self, label: &str) -> Option<Progress> {
+        let progress = self.fetch_progress(label).await;
+        if let Err(e) = &progress {
+            trace_event!(debug, error = %e, "Failed to poll query progress");
+        }
+        progress.ok().flatten()
+    }
+
+    async fn fetch_progress(&self, label: &str) -> Result<Option<Progress>, FireboltError> {
+        let sql = format!(
+            "SELECT scanned_rows, scanned_bytes, duration_usec \
+             FROM information_schema.engine_running_queries WHERE query_label = {}",
+            crate::sql::quote(label)
+        );
+        // The poll must not carry the label itself, or it would find itself.
+        let mut params = self.request_params();
+        params.remove("query_label");
+        let (running, _) = self.run_query_with_params(&sql, params, true, None).await?;
+        let Some(row) = running.rows.first() else {
+            return Ok(None);
+        };
+        let rows_scanned: Option<i64> = row.get("scanned_rows")?;
+        let bytes_scanned: Option<i64> = row.get("scanned_bytes")?;
+        let duration_us: Option<i64> = row.get("duration_usec")?;
+        Ok(Some(Progress {
+            rows_scanned: rows_scanned.unwrap_or_default().max(0) as u64,
+            bytes_scanned: bytes_scanned.unwrap_or_default().max(0) as u64,
+            elapsed: Duration::from_micros(duration_us.unwrap_or_default().max(0) as u64),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockFirebolt, MockResponse};
+
+    #[tokio::test]
+    async fn test_query_with_progress() {
+        let mock = MockFirebolt::new();
+        mock.on_query(
+            "SELECT count(*) FROM events",
+            MockResponse::rows(&[("count", "bigint")], vec![vec![3.into()]])
+                .with_delay(Duration::from_millis(300)),
+        );
+        mock.on_query_containing(
+            "engine_running_queries",
+            MockResponse::rows(
+                &[
+                    ("scanned_rows", "bigint"),
+                    ("scanned_bytes", "bigint"),
+                    ("duration_usec", "bigint"),
+                ],
+                vec![vec![1000.into(), 65536.into(), 250_000.into()]],
+            ),
+        );
+        let client = mock.connect().await.unwrap();
+
+        let mut updates = Vec::new();
+        let result = client
+            .query_with_progress(
+                "SELECT count(*) FROM events",
+                Duration::from_millis(50),
+                |progress| updates.push(progress.clone()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.rows.len(), 1);
+        assert!(!updates.is_empty());
+        assert_eq!(
+            updates[0],
+            Progress {
+                rows_scanned: 1000,
+                bytes_scanned: 65536,
+                elapsed: Duration::from_millis(250),
+            }
+        );
+
+        let queries = mock.queries();
+        let label = queries
+            .iter()
+            .find(|query| query.sql == "SELECT count(*) FROM events")
+            .and_then(|query| query.parameters.get("query_label"))
+            .unwrap();
+        let poll = queries
+            .iter()
+            .find(|query| query.sql.contains("engine_running_queries"))
+            .unwrap();
+        assert!(poll.sql.contains(&format!("query_label = '{label}'")));
+        assert!(!poll.parameters.contains_key("query_label"));
+    }
+}
@@ -0,0 +1,245 @@
+use crate::client::FireboltClient;
+use crate::error::FireboltError;
+use crate::result::ResultSet;
+use crate::sql::{bind, SqlValue};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl fmt::Display for Order {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Order::Asc => f.write_str("ASC"),
+            Order::Desc => f.write_str("DESC"),
+        }
+    }
+}
+
+// Column expressions, table names and conditions are written as given; only
+// the values bound to `?` placeholders are rendered as literals. Binding
+// errors are kept until `build` so the chain does not need `?` at each step.
+#[derive(Debug, Clone, Default)]
+pub struct SelectBuilder {
+    columns: Vec<String>,
+    distinct: bool,
+    from: Option<String>,
+    filters: Vec<String>,
+    group_by: Vec<String>,
+    having: Vec<String>,
+    order_by: Vec<String>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+    error: Option<String>,
+}
+
+pub fn select<I, S>(columns: I) -> SelectBuilder
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    SelectBuilder {
+        columns: columns.into_iter().map(Into::into).collect(),
+        ..SelectBuilder::default()
+    }
+}
+
+impl SelectBuilder {
+    pub fn distinct(mut self) -> Self {
+        self.distinct = true;
+        self
+    }
+
+    pub fn from(mut self, table: &str) -> Self {
+        self.from = Some(table.to_string());
+        self
+    }
+
+    // Conditions added by repeated calls are combined with AND.
+    pub fn filter<I, V>(mut self, condition: &str, params: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<SqlValue>,
+    {
+        if let Some(condition) = self.bind(condition, params) {
+            self.filters.push(condition);
+        }
+        self
+    }
+
+    // For conditions without placeholders; the text is not scanned for `?`.
+    pub fn filter_raw(mut self, condition: &str) -> Self {
+        self.filters.push(condition.to_string());
+        self
+    }
+
+    pub fn group_by<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.group_by.extend(columns.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn having<I, V>(mut self, condition: &str, params: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<SqlValue>,
+    {
+        if let Some(condition) = self.bind(condition, params) {
+            self.having.push(condition);
+        }
+        self
+    }
+
+    pub fn order_by(mut self, expression: &str, order: Order) -> Self {
+        self.order_by.push(format!("{expression} {order}"));
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    pub fn build(&self) -> Result<String, FireboltError> {
+        if let Some(error) = &self.error {
+            return Err(FireboltError::Query(error.clone()));
+        }
+        if self.columns.is_empty() {
+            return Err(FireboltError::Query(
+                "SELECT requires at least one column".to_string(),
+            ));
+        }
+
+        let mut sql = String::from("SELECT ");
+        if self.distinct {
+            sql.push_str("DISTINCT ");
+        }
+        sql.push_str(&self.columns.join(", "));
+        if let Some(from) = &self.from {
+            sql.push_str(&format!(" FROM {from}"));
+        }
+        if !self.filters.is_empty() {
+            sql.push_str(&format!(" WHERE {}", join_conditions(&self.filters)));
+        }
+        if !self.group_by.is_empty() {
+            sql.push_str(&format!(" GROUP BY {}", self.group_by.join(", ")));
+        }
+        if !self.having.is_empty() {
+            sql.push_str(&format!(" HAVING {}", join_conditions(&self.having)));
+        }
+        if !self.order_by.is_empty() {
+            sql.push_str(&format!(" ORDER BY {}", self.order_by.join(", ")));
+        }
+        if let Some(limit) = self.limit {
+            sql.push_str(&format!(" LIMIT {limit}"));
+        }
+        if let Some(offset) = self.offset {
+            sql.push_str(&format!(" OFFSET {offset}"));
+        }
+        Ok(sql)
+    }
+
+    pub async fn fetch(&self, client: &FireboltClient) -> Result<ResultSet, FireboltError> {
+        client.query(&self.build()?).await
+    }
+
+    fn bind<I, V>(&mut self, condition: &str, params: I) -> Option<String>
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<SqlValue>,
+    {
+        let params: Vec<SqlValue> = params.into_iter().map(Into::into).collect();
+        match bind(condition, &params) {
+            Ok(condition) => Some(condition),
+            Err(e) => {
+                self.error.get_or_insert(match e {
+                    FireboltError::Query(msg) => msg,
+                    other => other.to_string(),
+                });
+                None
+            }
+        }
+    }
+}
+
+// Each condition is parenthesized so an OR inside one cannot leak into the others.
+fn join_conditions(conditions: &[String]) -> String {
+    if conditions.len() == 1 {
+        return conditions[0].clone();
+    }
+    conditions
+        .iter()
+        .map(|c| format!("({c})"))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_builder() {
+        let sql = select(["customer", "sum(amount) AS total"])
+            .from("orders")
+            .filter("status = ?", ["shipped"])
+            .filter_raw("amount IS NOT NULL")
+            .filter(
+                "created_at >= ? OR priority = ?",
+                [SqlValue::from("2024-01-01"), SqlValue::from(1)],
+            )
+            .group_by(["customer"])
+            .having("sum(amount) > ?", [100])
+            .order_by("total", Order::Desc)
+            .limit(10)
+            .offset(20)
+            .build()
+            .unwrap();
+        assert_eq!(
+            sql,
+            "SELECT customer, sum(amount) AS total FROM orders \
+             WHERE (status = 'shipped') AND (amount IS NOT NULL) AND (created_at >= '2024-01-01' OR priority = 1) \
+             GROUP BY customer HAVING sum(amount) > 100 \
+             ORDER BY total DESC LIMIT 10 OFFSET 20"
+        );
+    }
+
+    #[test]
+    fn test_select_builder_minimal() {
+        assert_eq!(
+            select(["1"]).distinct().build().unwrap(),
+            "SELECT DISTINCT 1"
+        );
+        assert_eq!(
+            select(["id"])
+                .from("t")
+                .filter("note = ?", [SqlValue::from("it's ?")])
+                .build()
+                .unwrap(),
+            "SELECT id FROM t WHERE note = 'it''s ?'"
+        );
+    }
+
+    #[test]
+    fn test_select_builder_errors() {
+        assert!(matches!(
+            select(["id"])
+                .from("t")
+                .filter("a = ? AND b = ?", [1])
+                .build(),
+            Err(FireboltError::Query(_))
+        ));
+        assert!(select(Vec::<String>::new()).from("t").build().is_err());
+    }
+}
@@ -1,52 +1,808 @@
+use crate::de::RowDeserializer;
 use crate::error::FireboltError;
-use crate::types::{Column, ColumnRef, TypeConversion};
+use crate::types::{
+    BytesEncoding, Column, ColumnRef, ConversionMode, FireboltValue, Type, TypeConversion,
+};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResultSet {
     pub columns: Vec<Column>,
     pub rows: Vec<Row>,
+    #[serde(default)]
+    warnings: Vec<ServerWarning>,
+    #[serde(default)]
+    truncated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerWarning {
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl ResultSet {
+    pub fn new(columns: Vec<Column>, rows: Vec<Row>) -> Self {
+        Self {
+            columns,
+            rows,
+            warnings: Vec::new(),
+            truncated: false,
+        }
+    }
+
+    pub(crate) fn with_truncated(mut self, truncated: bool) -> Self {
+        self.truncated = truncated;
+        self
+    }
+
+    // True when the result had more rows than the client's `max_rows` limit
+    // and only the first `max_rows` were kept.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub fn with_warnings(mut self, warnings: Vec<ServerWarning>) -> Self {
+        self.warnings = warnings;
+        self
+    }
+
+    // Non-fatal notices the server sent with the result, such as deprecation
+    // or truncation warnings.
+    pub fn warnings(&self) -> &[ServerWarning] {
+        &self.warnings
+    }
+
+    pub fn with_conversion_mode(mut self, mode: ConversionMode) -> Self {
+        for row in &mut self.rows {
+            row.mode = mode;
+        }
+        self
+    }
+
+    // Decodes `bytea` values sent in `encoding` up front, so they convert like
+    // any other bytes. With `BytesEncoding::Auto` the result is unchanged.
+    pub fn with_bytes_encoding(mut self, encoding: BytesEncoding) -> Result<Self, FireboltError> {
+        use base64::Engine;
+
+        if encoding == BytesEncoding::Auto {
+            return Ok(self);
+        }
+        let byte_columns: Vec<usize> = (0..self.columns.len())
+            .filter(|&i| self.columns[i].r#type == Type::Bytes)
+            .collect();
+        for (row_index, row) in self.rows.iter_mut().enumerate() {
+            for &index in &byte_columns {
+                let Some(serde_json::Value::String(text)) = row.data.get_mut(index) else {
+                    continue;
+                };
+                let bytes = base64::engine::general_purpose::STANDARD
+                    .decode(text.as_bytes())
+                    .map_err(|e| {
+                        FireboltError::Serialization(format!(
+                            "Failed to decode base64 in column '{}', row {row_index}: {e}",
+                            self.columns[index].name
+                        ))
+                    })?;
+                *text = format!("\\x{}", crate::types::encode_hex(&bytes));
+            }
+        }
+        Ok(self)
+    }
+
+    // Resolves the column once and converts every row's value in a single
+    // pass. A conversion error names the offending row.
+    pub fn column_values<T>(
+        &self,
+        column_ref: impl Into<ColumnRef>,
+    ) -> Result<Vec<T>, FireboltError>
+    where
+        T: TypeConversion,
+    {
+        let (index, column) = resolve_column(&self.columns, column_ref.into())?;
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(row_index, row)| {
+                let value = row.data.get(index).ok_or_else(|| {
+                    FireboltError::Query(format!("Column index {index} out of bounds"))
+                })?;
+                T::convert_from_column(value, column, row.mode)
+                    .map_err(|e| e.with_context(&format!("row {row_index}")))
+            })
+            .collect()
+    }
+
+    // Deserializes every row into `T`, stopping at the first row that fails.
+    // The error names the offending row.
+    pub fn rows_as<T>(&self) -> Result<Vec<T>, FireboltError>
+    where
+        T: DeserializeOwned,
+    {
+        self.try_collect()
+    }
+
+    // Like `rows_as`, but collects into any collection, such as a `HashMap`
+    // built from `(key, value)` tuples.
+    pub fn try_collect<C, T>(&self) -> Result<C, FireboltError>
+    where
+        C: FromIterator<T>,
+        T: DeserializeOwned,
+    {
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(row_index, row)| {
+                row.deserialize()
+                    .map_err(|e| e.with_context(&format!("row {row_index}")))
+            })
+            .collect()
+    }
+}
+
+fn error_message(error: FireboltError) -> String {
+    match error {
+        FireboltError::Query(message) | FireboltError::Serialization(message) => message,
+        other => other.to_string(),
+    }
+}
+
+fn resolve_column(
+    columns: &[Column],
+    column_ref: ColumnRef,
+) -> Result<(usize, &Column), FireboltError> {
+    match column_ref {
+        ColumnRef::Index(i) => {
+            let column = columns
+                .get(i)
+                .ok_or_else(|| FireboltError::Query(format!("Column index {i} out of bounds")))?;
+            Ok((i, column))
+        }
+        ColumnRef::Name(name) => columns
+            .iter()
+            .enumerate()
+            .find(|(_, col)| col.name == name)
+            .ok_or_else(|| FireboltError::Query(format!("Column '{name}' not found"))),
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Row {
     data: Vec<serde_json::Value>,
     columns: Vec<Column>,
+    #[serde(default)]
+    mode: ConversionMode,
 }
 
 impl Row {
     pub fn new(data: Vec<serde_json::Value>, columns: Vec<Column>) -> Self {
-        Self { data, columns }
+        Self {
+            data,
+            columns,
+            mode: ConversionMode::Strict,
+        }
+    }
+
+    pub fn with_conversion_mode(mut self, mode: ConversionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub(crate) fn data(&self) -> &[serde_json::Value] {
+        &self.data
+    }
+
+    pub(crate) fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    #[cfg(any(feature = "sqlx", feature = "diesel"))]
+    pub(crate) fn into_data(self) -> Vec<serde_json::Value> {
+        self.data
     }
 
     pub fn get<T>(&self, column_ref: impl Into<ColumnRef>) -> Result<T, FireboltError>
     where
         T: TypeConversion,
     {
-        let column_ref = column_ref.into();
-        let (index, column) = match column_ref {
-            ColumnRef::Index(i) => {
-                let column = self.columns.get(i).ok_or_else(|| {
-                    FireboltError::Query(format!("Column index {i} out of bounds"))
-                })?;
-                (i, column)
-            }
-            ColumnRef::Name(name) => {
-                let (index, column) = self
-                    .columns
-                    .iter()
-                    .enumerate()
-                    .find(|(_, col)| col.name == name)
-                    .ok_or_else(|| FireboltError::Query(format!("Column '{name}' not found")))?;
-                (index, column)
-            }
-        };
+        let (index, column) = resolve_column(&self.columns, column_ref.into())?;
 
         let value = self
             .data
             .get(index)
             .ok_or_else(|| FireboltError::Query(format!("Column index {index} out of bounds")))?;
 
-        T::convert_from_json(value, &column.r#type)
+        T::convert_from_column(value, column, self.mode)
+    }
+
+    // Visits the row column by column, converting each value according to
+    // its column type. A value that fails to convert yields an error naming
+    // its column, and the remaining columns are still visited.
+    pub fn values(
+        &self,
+    ) -> impl Iterator<Item = Result<(&Column, FireboltValue), FireboltError>> + '_ {
+        self.columns.iter().enumerate().map(|(index, column)| {
+            let value = self.data.get(index).unwrap_or(&serde_json::Value::Null);
+            FireboltValue::convert_from_json(value, &column.r#type)
+                .map(|value| (column, value))
+                .map_err(|e| e.with_context(&format!("column '{}'", column.name)))
+        })
+    }
+
+    // Like `get`, but reports a missing column, a NULL read into a
+    // non-optional type and an incompatible type as distinct errors.
+    pub fn try_get<T>(&self, column_ref: impl Into<ColumnRef>) -> Result<T, FireboltError>
+    where
+        T: TypeConversion,
+    {
+        let (value, column) = self.lookup(column_ref.into())?;
+        T::convert_from_column(value, column, self.mode).map_err(|e| {
+            let message = format!("column '{}': {}", column.name, error_message(e));
+            if value.is_null() {
+                FireboltError::NullValue(message)
+            } else {
+                FireboltError::TypeMismatch(message)
+            }
+        })
+    }
+
+    // `Ok(None)` for a NULL cell; a missing column is still an error.
+    pub fn get_opt<T>(&self, column_ref: impl Into<ColumnRef>) -> Result<Option<T>, FireboltError>
+    where
+        T: TypeConversion,
+    {
+        let column_ref = column_ref.into();
+        if self.lookup(column_ref.clone())?.0.is_null() {
+            return Ok(None);
+        }
+        self.try_get(column_ref).map(Some)
+    }
+
+    // Parses a JSON column, or any column holding JSON, into `T`. Use
+    // `Option<T>` for a nullable column.
+    pub fn get_json<T>(&self, column_ref: impl Into<ColumnRef>) -> Result<T, FireboltError>
+    where
+        T: DeserializeOwned,
+    {
+        let (value, column) = self.lookup(column_ref.into())?;
+        let context = |message: String| {
+            FireboltError::Serialization(format!("column '{}': {message}", column.name))
+        };
+        let document = serde_json::Value::convert_from_json(value, &column.r#type)
+            .map_err(|e| context(error_message(e)))?;
+        serde_json::from_value(document)
+            .map_err(|e| context(format!("Failed to deserialize JSON: {e}")))
+    }
+
+    fn lookup(
+        &self,
+        column_ref: ColumnRef,
+    ) -> Result<(&serde_json::Value, &Column), FireboltError> {
+        let (index, column) = resolve_column(&self.columns, column_ref)
+            .map_err(|e| FireboltError::ColumnNotFound(error_message(e)))?;
+        let value = self.data.get(index).ok_or_else(|| {
+            FireboltError::ColumnNotFound(format!("Column index {index} out of bounds"))
+        })?;
+        Ok((value, column))
+    }
+
+    pub fn deserialize<'de, T>(&'de self) -> Result<T, FireboltError>
+    where
+        T: Deserialize<'de>,
+    {
+        T::deserialize(RowDeserializer::new(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_column_values() {
+        let json = serde_json::json!({
+            "meta": [{"name": "id", "type": "int"}, {"name": "name", "type": "text null"}],
+            "data": [[1, "a"], [2, null], [3, "c"]],
+        });
+        let result = crate::parser::parse_response_json(&json).unwrap();
+
+        let ids: Vec<i32> = result.column_values("id").unwrap();
+        assert_eq!(ids, [1, 2, 3]);
+        let names: Vec<Option<String>> = result.column_values(1).unwrap();
+        assert_eq!(names, [Some("a".to_string()), None, Some("c".to_string())]);
+
+        let error = result.column_values::<String>("name").unwrap_err();
+        assert!(error.to_string().contains("row 1"), "{error}");
+        assert!(result.column_values::<i32>("missing").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_decimal_scale() {
+        use crate::types::ConversionMode;
+        use rust_decimal::Decimal;
+
+        let json = serde_json::json!({
+            "meta": [
+                {"name": "price", "type": "decimal(10, 2)"},
+                {"name": "rate", "type": "decimal(38, 30) null"},
+            ],
+            "data": [["12.5", "1.000000000000000000000000000000"],
+                     ["3", "0.123456789012345678901234567891"]],
+        });
+        let result = crate::parser::parse_response_json(&json).unwrap();
+
+        let price: Decimal = result.rows[0].get("price").unwrap();
+        assert_eq!(price.to_string(), "12.50");
+        let rate: Option<Decimal> = result.rows[0].get("rate").unwrap();
+        assert_eq!(rate, Some(Decimal::ONE));
+
+        let error = result.rows[1].get::<Decimal>("rate").unwrap_err();
+        assert!(error.to_string().contains("Lenient"), "{error}");
+        assert!(result.column_values::<Decimal>("rate").is_err());
+
+        let lenient = result.with_conversion_mode(ConversionMode::Lenient);
+        let rate: Decimal = lenient.rows[1].get("rate").unwrap();
+        assert_eq!(rate.to_string(), "0.1234567890123456789012345679");
+    }
+
+    #[test]
+    fn test_bytes_encodings() {
+        use crate::types::BytesEncoding;
+
+        let json = serde_json::json!({
+            "meta": [{"name": "b", "type": "bytea null"}],
+            "data": [["\\x6869"], ["h\\151\\\\"], ["plain \\ text"], ["aGk="], [null]],
+        });
+        let result = crate::parser::parse_response_json(&json).unwrap();
+        let values: Vec<Option<Vec<u8>>> = result.column_values("b").unwrap();
+        assert_eq!(
+            values,
+            [
+                Some(b"hi".to_vec()),
+                Some(b"hi\\".to_vec()),
+                Some(b"plain \\ text".to_vec()),
+                Some(b"aGk=".to_vec()),
+                None,
+            ]
+        );
+
+        let json = serde_json::json!({
+            "meta": [{"name": "b", "type": "bytea null"}],
+            "data": [["aGk="], [null]],
+        });
+        let result = crate::parser::parse_response_json(&json)
+            .unwrap()
+            .with_bytes_encoding(BytesEncoding::Base64)
+            .unwrap();
+        assert_eq!(result.rows[0].get::<Vec<u8>>("b").unwrap(), b"hi");
+        assert_eq!(result.rows[1].get_opt::<Vec<u8>>("b").unwrap(), None);
+
+        let json = serde_json::json!({
+            "meta": [{"name": "b", "type": "bytea"}],
+            "data": [["not base64!"]],
+        });
+        let error = crate::parser::parse_response_json(&json)
+            .unwrap()
+            .with_bytes_encoding(BytesEncoding::Base64)
+            .unwrap_err();
+        assert!(error.to_string().contains("row 0"), "{error}");
+    }
+
+    #[test]
+    fn test_rows_as_bytes() {
+        use crate::types::BytesEncoding;
+
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Blob {
+            b: Vec<u8>,
+        }
+
+        let json = serde_json::json!({
+            "meta": [{"name": "b", "type": "bytea"}],
+            "data": [["\\x6869"], ["h\\151\\\\"], ["plain \\ text"]],
+        });
+        let result = crate::parser::parse_response_json(&json).unwrap();
+        let blobs: Vec<Blob> = result.rows_as().unwrap();
+        let values: Vec<Vec<u8>> = result.column_values("b").unwrap();
+        assert_eq!(
+            blobs.into_iter().map(|blob| blob.b).collect::<Vec<_>>(),
+            values
+        );
+        assert_eq!(values[1], b"hi\\");
+
+        let json = serde_json::json!({
+            "meta": [{"name": "b", "type": "bytea"}],
+            "data": [["aGk="]],
+        });
+        let result = crate::parser::parse_response_json(&json)
+            .unwrap()
+            .with_bytes_encoding(BytesEncoding::Base64)
+            .unwrap();
+        assert_eq!(result.rows_as::<Blob>().unwrap()[0].b, b"hi");
+    }
+
+    #[test]
+    fn test_rows_as() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Item {
+            id: i32,
+            name: String,
+        }
+
+        let json = serde_json::json!({
+            "meta": [{"name": "id", "type": "int"}, {"name": "name", "type": "text null"}],
+            "data": [[1, "a"], [2, "b"], [3, null]],
+        });
+        let mut result = crate::parser::parse_response_json(&json).unwrap();
+
+        let error = result.rows_as::<Item>().unwrap_err();
+        assert!(error.to_string().contains("row 2"), "{error}");
+
+        result.rows.pop();
+        let items: Vec<Item> = result.rows_as().unwrap();
+        assert_eq!(
+            items[1],
+            Item {
+                id: 2,
+                name: "b".to_string()
+            }
+        );
+        let names: std::collections::HashMap<i32, String> =
+            result.try_collect::<_, (i32, String)>().unwrap();
+        assert_eq!(names[&1], "a");
+    }
+
+    #[test]
+    fn test_try_get_errors() {
+        use crate::error::FireboltError;
+
+        let json = serde_json::json!({
+            "meta": [{"name": "id", "type": "int"}, {"name": "note", "type": "text null"}],
+            "data": [[1, null]],
+        });
+        let result = crate::parser::parse_response_json(&json).unwrap();
+        let row = &result.rows[0];
+
+        assert_eq!(row.try_get::<i32>("id").unwrap(), 1);
+        assert!(matches!(
+            row.try_get::<i32>("idd"),
+            Err(FireboltError::ColumnNotFound(_))
+        ));
+        assert!(matches!(
+            row.try_get::<i32>(5),
+            Err(FireboltError::ColumnNotFound(_))
+        ));
+        assert!(matches!(
+            row.try_get::<String>("note"),
+            Err(FireboltError::NullValue(_))
+        ));
+        assert!(matches!(
+            row.try_get::<String>("id"),
+            Err(FireboltError::TypeMismatch(_))
+        ));
+
+        assert_eq!(row.get_opt::<String>("note").unwrap(), None);
+        assert_eq!(row.get_opt::<i32>("id").unwrap(), Some(1));
+        assert!(matches!(
+            row.get_opt::<String>("nope"),
+            Err(FireboltError::ColumnNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_widening_conversion() {
+        let json = serde_json::json!({
+            "meta": [
+                {"name": "small", "type": "int"},
+                {"name": "big", "type": "bigint"},
+                {"name": "ratio", "type": "float"},
+            ],
+            "data": [[7, "30000000000", 0.5]],
+        });
+        let result = crate::parser::parse_response_json(&json).unwrap();
+        let row = &result.rows[0];
+        assert_eq!(row.get::<i64>("small").unwrap(), 7);
+        assert_eq!(row.get::<i64>("big").unwrap(), 30_000_000_000);
+        assert_eq!(row.get::<f64>("small").unwrap(), 7.0);
+        assert_eq!(row.get::<f64>("ratio").unwrap(), 0.5);
+        #[cfg(feature = "bigint")]
+        assert_eq!(
+            row.get::<num_bigint::BigInt>("small").unwrap(),
+            num_bigint::BigInt::from(7)
+        );
+        assert!(row.get::<i32>("big").is_err());
+        assert!(row.get::<f32>("small").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "uuid")]
+    fn test_uuid_conversion() {
+        let json = serde_json::json!({
+            "meta": [
+                {"name": "id", "type": "text"},
+                {"name": "missing", "type": "text null"},
+                {"name": "bad", "type": "text"},
+            ],
+            "data": [["67e55044-10b1-426f-9247-bb680e5fe0c8", null, "not-a-uuid"]],
+        });
+        let result = crate::parser::parse_response_json(&json).unwrap();
+        let row = &result.rows[0];
+        assert_eq!(
+            row.get::<uuid::Uuid>("id").unwrap(),
+            uuid::Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap()
+        );
+        assert_eq!(row.get::<Option<uuid::Uuid>>("missing").unwrap(), None);
+        assert!(row.get::<uuid::Uuid>("bad").is_err());
+    }
+
+    #[test]
+    fn test_get_json() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Settings {
+            theme: String,
+            sizes: Vec<i32>,
+        }
+
+        let json = serde_json::json!({
+            "meta": [
+                {"name": "settings", "type": "json"},
+                {"name": "missing", "type": "json null"},
+                {"name": "broken", "type": "json"},
+            ],
+            "data": [[r#"{"theme": "dark", "sizes": [1, 2]}"#, null, "{"]],
+        });
+        let result = crate::parser::parse_response_json(&json).unwrap();
+        let row = &result.rows[0];
+        assert_eq!(
+            row.get_json::<Settings>("settings").unwrap(),
+            Settings {
+                theme: "dark".to_string(),
+                sizes: vec![1, 2],
+            }
+        );
+        assert_eq!(
+            row.get::<serde_json::Value>("settings").unwrap()["theme"],
+            "dark"
+        );
+        assert_eq!(
+            row.get::<String>("settings").unwrap(),
+            r#"{"theme": "dark", "sizes": [1, 2]}"#
+        );
+        assert_eq!(row.get_json::<Option<Settings>>("missing").unwrap(), None);
+        assert!(row.get_json::<Settings>("missing").is_err());
+        assert!(row.get_json::<serde_json::Value>("broken").is_err());
+    }
+
+    #[test]
+    fn test_interval_conversion() {
+        let json = serde_json::json!({
+            "meta": [
+                {"name": "elapsed", "type": "interval"},
+                {"name": "back", "type": "interval"},
+                {"name": "mixed", "type": "interval"},
+                {"name": "months", "type": "interval"},
+                {"name": "missing", "type": "interval null"},
+            ],
+            "data": [[
+                "3 days 04:05:06.5",
+                "-00:00:01.25",
+                "-1 days +02:00:00",
+                "1 mon 2 days",
+                null,
+            ]],
+        });
+        let result = crate::parser::parse_response_json(&json).unwrap();
+        let row = &result.rows[0];
+        let elapsed = chrono::Duration::days(3)
+            + chrono::Duration::seconds(4 * 3600 + 5 * 60 + 6)
+            + chrono::Duration::milliseconds(500);
+        assert_eq!(row.get::<chrono::Duration>("elapsed").unwrap(), elapsed);
+        assert_eq!(
+            row.get::<std::time::Duration>("elapsed").unwrap(),
+            elapsed.to_std().unwrap()
+        );
+        assert_eq!(
+            row.get::<chrono::Duration>("back").unwrap(),
+            chrono::Duration::milliseconds(-1250)
+        );
+        assert!(row.get::<std::time::Duration>("back").is_err());
+        assert_eq!(
+            row.get::<chrono::Duration>("mixed").unwrap(),
+            chrono::Duration::hours(-22)
+        );
+        assert!(row.get::<chrono::Duration>("months").is_err());
+        assert_eq!(row.get::<String>("months").unwrap(), "1 mon 2 days");
+        assert_eq!(
+            row.get::<Option<std::time::Duration>>("missing").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_timestamptz_conversion() {
+        use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+
+        let json = serde_json::json!({
+            "meta": [
+                {"name": "utc", "type": "timestamptz"},
+                {"name": "berlin", "type": "timestamptz"},
+                {"name": "kolkata", "type": "timestamptz"},
+                {"name": "nanos", "type": "timestamptz"},
+                {"name": "huge", "type": "timestamptz"},
+                {"name": "broken", "type": "timestamptz"},
+                {"name": "missing", "type": "timestamptz null"},
+            ],
+            "data": [[
+                "1111-01-05 17:04:42.123456+00",
+                "1111-01-05 17:58:10.123456+00:53:28",
+                "2024-01-31 18:00:00.000001+0530",
+                "2024-01-31 12:30:00.000000001-08",
+                "300000-01-01 00:00:00+00",
+                "2024-02-30 00:00:00+00",
+                null,
+            ]],
+        });
+        let result = crate::parser::parse_response_json(&json).unwrap();
+        let row = &result.rows[0];
+        let expected = NaiveDate::from_ymd_opt(1111, 1, 5)
+            .unwrap()
+            .and_hms_micro_opt(17, 4, 42, 123456)
+            .unwrap()
+            .and_utc();
+
+        let utc: DateTime<FixedOffset> = row.get("utc").unwrap();
+        assert_eq!(utc, expected);
+        assert_eq!(utc.timestamp_subsec_micros(), 123456);
+        let berlin: DateTime<FixedOffset> = row.get("berlin").unwrap();
+        assert_eq!(berlin.offset().local_minus_utc(), 53 * 60 + 28);
+        assert_eq!(row.get::<DateTime<Utc>>("berlin").unwrap(), expected);
+
+        let kolkata: DateTime<FixedOffset> = row.get("kolkata").unwrap();
+        assert_eq!(kolkata.offset().local_minus_utc(), 5 * 3600 + 30 * 60);
+        assert_eq!(
+            kolkata.to_rfc3339_opts(chrono::SecondsFormat::Micros, false),
+            "2024-01-31T18:00:00.000001+05:30"
+        );
+        let nanos: DateTime<FixedOffset> = row.get("nanos").unwrap();
+        assert_eq!(nanos.offset().local_minus_utc(), -8 * 3600);
+        assert_eq!(nanos.timestamp_subsec_nanos(), 1);
+
+        assert!(matches!(
+            row.get::<DateTime<Utc>>("huge"),
+            Err(crate::error::FireboltError::Serialization(message)) if message.contains("outside the range")
+        ));
+        assert!(matches!(
+            row.get::<DateTime<Utc>>("broken"),
+            Err(crate::error::FireboltError::Serialization(message)) if message.contains("Failed to parse")
+        ));
+        assert_eq!(row.get::<Option<DateTime<Utc>>>("missing").unwrap(), None);
+        assert!(row.get::<DateTime<FixedOffset>>("missing").is_err());
+        assert!(row.get::<DateTime<Utc>>("utc").is_ok());
+    }
+
+    #[test]
+    fn test_datetime_targets() {
+        use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+        let json = serde_json::json!({
+            "meta": [
+                {"name": "day", "type": "date"},
+                {"name": "local", "type": "timestamp"},
+                {"name": "instant", "type": "timestamptz"},
+                {"name": "missing", "type": "timestamp null"},
+            ],
+            "data": [[
+                "2021-03-28",
+                "2019-07-31 01:01:01.5",
+                "2019-07-31 01:01:01.5+02",
+                null,
+            ]],
+        });
+        let result = crate::parser::parse_response_json(&json).unwrap();
+        let row = &result.rows[0];
+        let wall_clock = NaiveDate::from_ymd_opt(2019, 7, 31)
+            .unwrap()
+            .and_hms_milli_opt(1, 1, 1, 500)
+            .unwrap();
+
+        assert_eq!(
+            row.get::<NaiveDate>("day").unwrap(),
+            NaiveDate::from_ymd_opt(2021, 3, 28).unwrap()
+        );
+        assert_eq!(row.get::<NaiveDateTime>("local").unwrap(), wall_clock);
+        assert_eq!(
+            row.get::<DateTime<Utc>>("local").unwrap(),
+            wall_clock.and_utc()
+        );
+        assert_eq!(
+            row.get::<DateTime<Utc>>("instant").unwrap(),
+            wall_clock.and_utc() - chrono::Duration::hours(2)
+        );
+        assert!(matches!(
+            row.get::<NaiveDateTime>("instant"),
+            Err(crate::error::FireboltError::Serialization(message))
+                if message.contains("DateTime<Utc>")
+        ));
+        assert!(row.get::<NaiveDate>("local").is_err());
+        assert_eq!(row.get::<Option<NaiveDateTime>>("missing").unwrap(), None);
+        assert_eq!(row.get::<Option<DateTime<Utc>>>("missing").unwrap(), None);
+        assert!(row.get::<NaiveDateTime>("missing").is_err());
+    }
+
+    #[test]
+    fn test_wide_values_without_features() {
+        let json = serde_json::json!({
+            "meta": [
+                {"name": "big", "type": "bigint"},
+                {"name": "price", "type": "decimal(38, 2)"},
+                {"name": "missing", "type": "decimal(38, 2) null"},
+            ],
+            "data": [["9223372036854775807", "12345678901234567890.25", null]],
+        });
+        let result = crate::parser::parse_response_json(&json).unwrap();
+        let row = &result.rows[0];
+        assert_eq!(row.get::<i64>("big").unwrap(), i64::MAX);
+        assert_eq!(
+            row.get::<String>("price").unwrap(),
+            "12345678901234567890.25"
+        );
+        assert_eq!(row.get::<Option<String>>("missing").unwrap(), None);
+        assert!(row.get::<String>("missing").is_err());
+    }
+
+    #[test]
+    fn test_lenient_conversion() {
+        use crate::types::ConversionMode;
+
+        let json = serde_json::json!({
+            "meta": [
+                {"name": "amount", "type": "text"},
+                {"name": "count", "type": "int"},
+                {"name": "label", "type": "text"},
+            ],
+            "data": [["12.5", 3, "n/a"]],
+        });
+        let result = crate::parser::parse_response_json(&json).unwrap();
+        assert!(result.rows[0].get::<f64>("amount").is_err());
+
+        let result = result.with_conversion_mode(ConversionMode::Lenient);
+        let row = &result.rows[0];
+        assert_eq!(row.get::<f64>("amount").unwrap(), 12.5);
+        assert_eq!(row.get::<f64>("count").unwrap(), 3.0);
+        assert_eq!(row.get::<String>("count").unwrap(), "3");
+        assert_eq!(row.get::<Option<i32>>("count").unwrap(), Some(3));
+        assert!(row.get::<i32>("label").is_err());
+        assert!(row.get::<i32>("amount").is_err());
+    }
+
+    #[test]
+    fn test_row_values() {
+        use crate::types::FireboltValue;
+
+        let json = serde_json::json!({
+            "meta": [
+                {"name": "id", "type": "int"},
+                {"name": "day", "type": "date null"},
+                {"name": "tags", "type": "array(text)"},
+            ],
+            "data": [[1, null, ["a"]]],
+        });
+        let result = crate::parser::parse_response_json(&json).unwrap();
+        let values: Vec<(String, FireboltValue)> = result.rows[0]
+            .values()
+            .map(|item| item.map(|(column, value)| (column.name.clone(), value)))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            values,
+            [
+                ("id".to_string(), FireboltValue::Int(1)),
+                ("day".to_string(), FireboltValue::Null),
+                (
+                    "tags".to_string(),
+                    FireboltValue::Array(serde_json::json!(["a"]))
+                ),
+            ]
+        );
     }
 }
@@ -0,0 +1,214 @@
+use crate::client::FireboltClient;
+use crate::result::ResultSet;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Results of read-only queries, keyed by the engine URL, the statement with
+// its whitespace normalized, and the request parameters. Entries expire after
+// `ttl`; when the cache is full the least recently used entry is evicted.
+#[derive(Debug)]
+pub(crate) struct ResultCache {
+    capacity: usize,
+    ttl: Duration,
+    entries: Mutex<CacheEntries>,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    clock: u64,
+    results: HashMap<String, CachedResult>,
+}
+
+#[derive(Debug)]
+struct CachedResult {
+    last_used: u64,
+    expires: Instant,
+    result: ResultSet,
+}
+
+impl ResultCache {
+    pub(crate) fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: Mutex::default(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheEntries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn key(engine_url: &str, sql: &str, params: &HashMap<String, String>) -> String {
+        let mut params: Vec<_> = params.iter().collect();
+        params.sort();
+        let mut key = format!("{}\n{}", engine_url.trim_end_matches('/'), normalize(sql));
+        for (name, value) in params {
+            key.push_str(&format!("\n{name}={value}"));
+        }
+        key
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<ResultSet> {
+        let mut entries = self.lock();
+        entries.clock += 1;
+        let now = entries.clock;
+        match entries.results.get_mut(key) {
+            Some(entry) if entry.expires > Instant::now() => {
+                entry.last_used = now;
+                Some(entry.result.clone())
+            }
+            Some(_) => {
+                entries.results.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, key: String, result: &ResultSet) {
+        if self.capacity == 0 || self.ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.lock();
+        entries.clock += 1;
+        let now = entries.clock;
+        if !entries.results.contains_key(&key) && entries.results.len() >= self.capacity {
+            let instant = Instant::now();
+            entries.results.retain(|_, entry| entry.expires > instant);
+            if entries.results.len() >= self.capacity {
+                let oldest = entries
+                    .results
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.results.remove(&oldest);
+                }
+            }
+        }
+        entries.results.insert(
+            key,
+            CachedResult {
+                last_used: now,
+                expires: Instant::now() + self.ttl,
+                result: result.clone(),
+            },
+        );
+    }
+
+    pub(crate) fn clear(&self) {
+        self.lock().results.clear();
+    }
+}
+
+// Collapses runs of whitespace outside quoted text and drops a trailing
+// semicolon, so formatting differences do not defeat the cache.
+fn normalize(sql: &str) -> String {
+    let mut normalized = String::with_capacity(sql.len());
+    let mut quote = None;
+    let mut pending_space = false;
+    for c in sql.trim().trim_end_matches(';').trim_end().chars() {
+        match quote {
+            Some(q) => {
+                normalized.push(c);
+                if c == q {
+                    quote = None;
+                }
+            }
+            None if c.is_whitespace() => pending_space = true,
+            None => {
+                if pending_space && !normalized.is_empty() {
+                    normalized.push(' ');
+                }
+                pending_space = false;
+                if c == '\'' || c == '"' {
+                    quote = Some(c);
+                }
+                normalized.push(c);
+            }
+        }
+    }
+    normalized
+}
+
+impl FireboltClient {
+    // Drops every cached result. Does nothing without `with_result_cache`.
+    pub fn clear_result_cache(&self) {
+        if let Some(cache) = self.result_cache() {
+            cache.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockFirebolt, MockResponse};
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("  SELECT  *\n\tFROM t WHERE s = 'a  b' ;"),
+            "SELECT * FROM t WHERE s = 'a  b'"
+        );
+        assert_eq!(
+            normalize("SELECT \"x  y\"   FROM t"),
+            "SELECT \"x  y\" FROM t"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_result_cache() {
+        let mock = MockFirebolt::new();
+        mock.on_query_containing(
+            "FROM tables",
+            MockResponse::rows(&[("n", "int")], vec![vec![1.into()]]),
+        );
+        let client = mock
+            .client_factory()
+            .with_result_cache(2, Duration::from_secs(60))
+            .build()
+            .await
+            .unwrap();
+        let before = mock.queries().len();
+
+        for sql in ["SELECT n FROM tables", "SELECT n\n  FROM tables;"] {
+            let result = client.query(sql).await.unwrap();
+            assert_eq!(result.rows[0].get::<i32>("n").unwrap(), 1);
+        }
+        assert_eq!(mock.queries().len(), before + 1);
+
+        client.query("INSERT INTO tables VALUES (2)").await.unwrap();
+        client.query("INSERT INTO tables VALUES (2)").await.unwrap();
+        assert_eq!(mock.queries().len(), before + 3);
+
+        client
+            .set_parameter("time_zone".to_string(), "UTC".to_string())
+            .unwrap();
+        client.query("SELECT n FROM tables").await.unwrap();
+        assert_eq!(mock.queries().len(), before + 4);
+
+        client.clear_result_cache();
+        client.query("SELECT n FROM tables").await.unwrap();
+        assert_eq!(mock.queries().len(), before + 5);
+    }
+
+    #[test]
+    fn test_eviction_and_expiry() {
+        let result: ResultSet = serde_json::from_str(r#"{"columns": [], "rows": []}"#).unwrap();
+        let cache = ResultCache::new(2, Duration::from_secs(60));
+        cache.insert("a".to_string(), &result);
+        cache.insert("b".to_string(), &result);
+        assert!(cache.get("a").is_some());
+        cache.insert("c".to_string(), &result);
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some() && cache.get("c").is_some());
+
+        let cache = ResultCache::new(2, Duration::from_millis(1));
+        cache.insert("a".to_string(), &result);
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(cache.get("a").is_none());
+    }
+}
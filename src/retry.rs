@@ -0,0 +1,275 @@
+use crate::error::{ErrorKind, FireboltError};
+use crate::telemetry::trace_event;
+use serde::Deserialize;
+use std::fmt;
+use std::time::Duration;
+
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(200);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+// Decides whether a failed request is sent again and how long to wait first.
+// `attempt` is the retry about to be made, starting at 1, and returning `None`
+// gives up. The client only offers failures that are safe to resend: network
+// errors for idempotent requests, and `429` or `503` responses, which the
+// server did not process. A strategy can narrow these down or change the
+// delays, but cannot retry anything else.
+pub trait RetryStrategy: Send + Sync {
+    fn next_delay(&self, attempt: u32, error: &FireboltError) -> Option<Duration>;
+
+    // Called instead of `next_delay` when the response carried a `Retry-After`
+    // header. By default the server's delay is used whenever `next_delay`
+    // would retry.
+    fn next_delay_after(
+        &self,
+        attempt: u32,
+        error: &FireboltError,
+        retry_after: Duration,
+    ) -> Option<Duration> {
+        self.next_delay(attempt, error).map(|_| retry_after)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    #[serde(deserialize_with = "crate::config::deserialize_duration")]
+    initial_backoff: Duration,
+    #[serde(deserialize_with = "crate::config::deserialize_duration")]
+    max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn none() -> Self {
+        Self::default().with_max_retries(0)
+    }
+
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    // Exponential backoff: the delay doubles after every attempt, capped at `max_backoff`.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    // A server-provided Retry-After wins over the computed backoff, within the same cap.
+    pub(crate) fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        retry_after
+            .map(|delay| delay.min(self.max_backoff))
+            .unwrap_or_else(|| self.backoff(attempt))
+    }
+}
+
+// Why a request is sent again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RetryCause {
+    Network(String),
+    // 429 or 503: the server did not process the request.
+    Status(u16),
+    // 401: the access token was refreshed.
+    Unauthorized,
+    EngineStarting,
+    // The engine stopped answering at its URL and was found at a new one.
+    EngineMoved,
+}
+
+impl RetryCause {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RetryCause::Network(_) => "network",
+            RetryCause::Status(_) => "status",
+            RetryCause::Unauthorized => "unauthorized",
+            RetryCause::EngineStarting => "engine_starting",
+            RetryCause::EngineMoved => "engine_moved",
+        }
+    }
+}
+
+impl fmt::Display for RetryCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RetryCause::Network(message) => write!(f, "network error: {message}"),
+            RetryCause::Status(status) => write!(f, "HTTP {status}"),
+            RetryCause::Unauthorized => f.write_str("access token rejected"),
+            RetryCause::EngineStarting => f.write_str("engine is starting"),
+            RetryCause::EngineMoved => f.write_str("engine moved to a new URL"),
+        }
+    }
+}
+
+// One retry, emitted before the client waits `backoff` and sends the request
+// again. `attempt` counts retries, starting at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryEvent {
+    pub attempt: u32,
+    pub cause: RetryCause,
+    pub backoff: Duration,
+}
+
+impl RetryEvent {
+    pub(crate) fn new(attempt: u32, cause: RetryCause, backoff: Duration) -> Self {
+        Self {
+            attempt,
+            cause,
+            backoff,
+        }
+    }
+
+    // Logged under the `firebolt::retry` target so retry rates can be filtered
+    // and counted apart from other events.
+    pub(crate) fn emit(&self) {
+        trace_event!(
+            info,
+            target: "firebolt::retry",
+            attempt = self.attempt,
+            cause = self.cause.kind(),
+            http.status_code = match self.cause {
+                RetryCause::Status(status) => Some(status),
+                _ => None,
+            },
+            backoff_ms = self.backoff.as_millis() as u64,
+            error = %self.cause,
+            "Retrying request"
+        );
+    }
+}
+
+// The final outcome of a request that was retried at least once.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn emit_outcome(retries: u32, succeeded: bool) {
+    if retries == 0 {
+        return;
+    }
+    if succeeded {
+        trace_event!(
+            info,
+            target: "firebolt::retry",
+            retries,
+            outcome = "success",
+            "Request succeeded after retrying"
+        );
+    } else {
+        trace_event!(
+            warn,
+            target: "firebolt::retry",
+            retries,
+            outcome = "failure",
+            "Request failed after retrying"
+        );
+    }
+}
+
+impl RetryStrategy for RetryPolicy {
+    fn next_delay(&self, attempt: u32, error: &FireboltError) -> Option<Duration> {
+        let retryable = matches!(error.kind(), ErrorKind::Network | ErrorKind::Unavailable);
+        (retryable && attempt <= self.max_retries).then(|| self.backoff(attempt))
+    }
+
+    fn next_delay_after(
+        &self,
+        attempt: u32,
+        error: &FireboltError,
+        retry_after: Duration,
+    ) -> Option<Duration> {
+        self.next_delay(attempt, error)
+            .map(|_| self.delay(attempt, Some(retry_after)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy::new()
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(350));
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+        assert_eq!(policy.backoff(40), Duration::from_millis(350));
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(1))),
+            Duration::from_millis(350)
+        );
+        assert_eq!(RetryPolicy::none().max_retries(), 0);
+    }
+
+    #[test]
+    fn test_policy_as_strategy() {
+        let policy = RetryPolicy::new()
+            .with_max_retries(2)
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(350));
+        let busy = FireboltError::Unavailable("HTTP 503".to_string());
+
+        assert_eq!(
+            policy.next_delay(2, &busy),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(policy.next_delay(3, &busy), None);
+        assert_eq!(
+            policy.next_delay(1, &FireboltError::Query("syntax error".to_string())),
+            None
+        );
+        assert_eq!(
+            policy.next_delay_after(1, &busy, Duration::from_secs(1)),
+            Some(Duration::from_millis(350))
+        );
+    }
+
+    #[test]
+    fn test_retry_cause() {
+        let causes = [
+            (RetryCause::Network("reset".to_string()), "network"),
+            (RetryCause::Status(503), "status"),
+            (RetryCause::Unauthorized, "unauthorized"),
+            (RetryCause::EngineStarting, "engine_starting"),
+            (RetryCause::EngineMoved, "engine_moved"),
+        ];
+        for (cause, kind) in causes {
+            assert_eq!(cause.kind(), kind);
+        }
+        assert_eq!(RetryCause::Status(429).to_string(), "HTTP 429");
+
+        let event = RetryEvent::new(2, RetryCause::Unauthorized, Duration::ZERO);
+        event.emit();
+        emit_outcome(event.attempt, true);
+    }
+}
@@ -0,0 +1,75 @@
+// Timers used for retries, deadlines and polling. With the `async-io`
+// feature they come from `async-io`, which drives its own reactor and so works
+// under async-std, smol or any other executor. Otherwise Tokio's timers are
+// used, which need to run inside a Tokio runtime.
+
+#[cfg(any(feature = "tokio", feature = "async-io"))]
+use std::future::Future;
+#[cfg(any(feature = "tokio", feature = "async-io"))]
+use std::time::Duration;
+
+#[cfg(not(any(feature = "tokio", feature = "async-io")))]
+compile_error!("firebolt-sdk needs a timer: enable the `tokio` or the `async-io` feature");
+
+pub(crate) struct Elapsed;
+
+#[cfg(feature = "async-io")]
+pub(crate) async fn sleep(duration: Duration) {
+    async_io::Timer::after(duration).await;
+}
+
+#[cfg(all(feature = "tokio", not(feature = "async-io")))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+#[cfg(feature = "async-io")]
+pub(crate) async fn timeout<F: Future>(
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, Elapsed> {
+    use futures_util::future::{select, Either};
+
+    let future = std::pin::pin!(future);
+    match select(future, async_io::Timer::after(duration)).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(_) => Err(Elapsed),
+    }
+}
+
+#[cfg(all(feature = "tokio", not(feature = "async-io")))]
+pub(crate) async fn timeout<F: Future>(
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, Elapsed> {
+    tokio::time::timeout(duration, future)
+        .await
+        .map_err(|_| Elapsed)
+}
+
+#[cfg(all(test, feature = "async-io"))]
+mod tests {
+    use super::*;
+    use crate::testing::{MockFirebolt, MockResponse};
+
+    #[test]
+    fn test_runs_without_tokio_runtime() {
+        async_io::block_on(async {
+            assert!(
+                timeout(Duration::from_millis(10), sleep(Duration::from_secs(5)))
+                    .await
+                    .is_err()
+            );
+
+            let mock = MockFirebolt::new();
+            mock.on_query(
+                "SELECT 1 AS n",
+                MockResponse::rows(&[("n", "int")], vec![vec![1.into()]])
+                    .with_delay(Duration::from_millis(5)),
+            );
+            let client = mock.connect().await.unwrap();
+            let result = client.query("SELECT 1 AS n").await.unwrap();
+            assert_eq!(result.rows[0].get::<i32>("n").unwrap(), 1);
+        });
+    }
+}
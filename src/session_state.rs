@@ -0,0 +1,189 @@
+// Snapshots of a client's session, so short-lived processes such as
+// serverless functions can pick up where a previous one left off instead of
+// authenticating and resolving the engine again.
+
+use crate::auth::token_cache;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+
+// Everything `with_session_state` needs to restore a session. It contains the
+// access token, so keep it as private as the client secret.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SessionState {
+    pub client_id: String,
+    pub account_name: String,
+    pub api_endpoint: String,
+    pub engine_url: String,
+    pub system_engine_url: String,
+    pub parameters: HashMap<String, String>,
+    #[serde(
+        serialize_with = "serialize_token",
+        deserialize_with = "deserialize_token"
+    )]
+    pub token: SecretString,
+    // Seconds since the Unix epoch; `None` when the client does not know,
+    // e.g. after `set_token`.
+    pub token_expires_at: Option<u64>,
+}
+
+impl std::fmt::Debug for SessionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionState")
+            .field("client_id", &self.client_id)
+            .field("account_name", &self.account_name)
+            .field("api_endpoint", &self.api_endpoint)
+            .field("engine_url", &self.engine_url)
+            .field("system_engine_url", &self.system_engine_url)
+            .field("parameters", &self.parameters)
+            .field("token", &crate::telemetry::REDACTED)
+            .field("token_expires_at", &self.token_expires_at)
+            .finish()
+    }
+}
+
+impl SessionState {
+    // A token of unknown age is tried, since a rejected token is refreshed
+    // on the first request anyway.
+    pub fn token_is_fresh(&self) -> bool {
+        self.token_expires_at.is_none_or(|expires_at| {
+            expires_at > token_cache::now() + token_cache::EXPIRY_MARGIN_SECS
+        })
+    }
+}
+
+fn serialize_token<S: Serializer>(token: &SecretString, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(token.expose_secret())
+}
+
+fn deserialize_token<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SecretString, D::Error> {
+    Ok(SecretString::from(String::deserialize(deserializer)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::FireboltError;
+    use crate::testing::{MockFirebolt, MockResponse, MOCK_ENGINE_URL};
+
+    #[tokio::test]
+    async fn test_resume_session() {
+        let mock = MockFirebolt::new();
+        mock.on_query(
+            "USE DATABASE \"sales\"",
+            MockResponse::empty().with_updated_parameters(&[("database", "sales")]),
+        );
+        mock.on_query(
+            "USE ENGINE \"etl\"",
+            MockResponse::empty().with_updated_endpoint("https://etl.mock.firebolt.io"),
+        );
+        let client = mock
+            .client_factory()
+            .with_database("sales".to_string())
+            .with_engine("etl".to_string())
+            .build()
+            .await
+            .unwrap();
+        client
+            .set_parameter("time_zone".to_string(), "UTC".to_string())
+            .unwrap();
+
+        let state = client.session_state().await.unwrap();
+        let saved = serde_json::to_string(&state).unwrap();
+        assert!(!format!("{state:?}").contains(crate::testing::MOCK_TOKEN));
+        let state: SessionState = serde_json::from_str(&saved).unwrap();
+        assert!(state.token_is_fresh());
+        assert_eq!(state.system_engine_url, MOCK_ENGINE_URL);
+
+        let before = mock.queries().len();
+        let resumed = mock
+            .client_factory()
+            .with_database("ignored".to_string())
+            .with_session_state(state)
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(mock.queries().len(), before);
+        assert_eq!(resumed.engine_url(), "https://etl.mock.firebolt.io");
+        assert!(!resumed.is_system_engine());
+        assert_eq!(resumed.parameters(), client.parameters());
+
+        resumed.query("SELECT 1").await.unwrap();
+        let query = mock.queries().pop().unwrap();
+        assert_eq!(query.parameters["database"], "sales");
+        assert_eq!(query.parameters["time_zone"], "UTC");
+    }
+
+    #[tokio::test]
+    async fn test_resume_lazy_session() {
+        let mock = MockFirebolt::new();
+        mock.on_query(
+            "USE DATABASE \"sales\"",
+            MockResponse::empty().with_updated_parameters(&[("database", "sales")]),
+        );
+        mock.on_query(
+            "USE ENGINE \"etl\"",
+            MockResponse::empty().with_updated_endpoint("https://etl.mock.firebolt.io"),
+        );
+        let client = mock
+            .client_factory()
+            .with_database("sales".to_string())
+            .with_engine("etl".to_string())
+            .build_lazy()
+            .unwrap();
+
+        let state = client.session_state().await.unwrap();
+        assert!(client.is_connected());
+        assert_eq!(state.engine_url, "https://etl.mock.firebolt.io");
+        assert_eq!(state.parameters["database"], "sales");
+
+        let before = mock.queries().len();
+        let resumed = mock
+            .client_factory()
+            .with_session_state(state)
+            .build_lazy()
+            .unwrap();
+        resumed.query("SELECT 1").await.unwrap();
+        let queries = mock.queries();
+        assert_eq!(queries.len(), before + 1);
+        let query = queries.last().unwrap();
+        assert_eq!(query.parameters["database"], "sales");
+        assert_eq!(resumed.engine_url(), "https://etl.mock.firebolt.io");
+    }
+
+    #[tokio::test]
+    async fn test_resume_session_without_engine() {
+        let mock = MockFirebolt::new();
+        mock.on_query(
+            "USE DATABASE \"sales\"",
+            MockResponse::empty().with_updated_parameters(&[("database", "sales")]),
+        );
+        let mut state = mock.connect().await.unwrap().session_state().await.unwrap();
+        state.engine_url = String::new();
+
+        let resumed = mock
+            .client_factory()
+            .with_database("sales".to_string())
+            .with_session_state(state)
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(resumed.engine_url(), MOCK_ENGINE_URL);
+        assert_eq!(resumed.parameters()["database"], "sales");
+    }
+
+    #[tokio::test]
+    async fn test_resume_session_for_another_account() {
+        let mock = MockFirebolt::new();
+        let mut state = mock.connect().await.unwrap().session_state().await.unwrap();
+        state.account_name = "other".to_string();
+        state.token_expires_at = Some(0);
+        assert!(!state.token_is_fresh());
+
+        let result = mock.client_factory().with_session_state(state).validate();
+        assert!(matches!(
+            result,
+            Err(FireboltError::Configuration(message)) if message.contains("account_name 'other'")
+        ));
+    }
+}
@@ -0,0 +1,184 @@
+// Graceful shutdown. A client and all clients derived from it share one
+// `Shutdown`: closing any of them abandons their requests in flight and
+// fails new ones with `FireboltError::Cancelled`.
+
+use crate::catalog::RunningQueryFilter;
+use crate::client::FireboltClient;
+use crate::error::FireboltError;
+use crate::telemetry::trace_event;
+use futures_util::future::{self, Either};
+use std::collections::{BTreeSet, HashMap};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, Default)]
+pub(crate) struct Shutdown {
+    token: CancellationToken,
+    next_id: AtomicU64,
+    // Labels of the labelled queries in flight, which are the ones that can
+    // be found on the server to cancel.
+    labels: Mutex<HashMap<u64, String>>,
+}
+
+struct InFlight<'a> {
+    shutdown: &'a Shutdown,
+    id: u64,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.shutdown.lock().remove(&self.id);
+    }
+}
+
+impl Shutdown {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u64, String>> {
+        self.labels.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    // Runs `request` unless the client is closed, and abandons it if the
+    // client is closed before it completes.
+    pub(crate) async fn guard<T>(
+        &self,
+        params: &HashMap<String, String>,
+        request: impl Future<Output = Result<T, FireboltError>>,
+    ) -> Result<T, FireboltError> {
+        if self.is_closed() {
+            return Err(closed());
+        }
+        let _in_flight = params.get("query_label").map(|label| {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            self.lock().insert(id, label.clone());
+            InFlight { shutdown: self, id }
+        });
+        match future::select(Box::pin(request), Box::pin(self.token.cancelled())).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(closed()),
+        }
+    }
+
+    // Returns the labels of the queries that were in flight.
+    fn close(&self) -> BTreeSet<String> {
+        self.token.cancel();
+        self.lock().values().cloned().collect()
+    }
+}
+
+fn closed() -> FireboltError {
+    FireboltError::Cancelled("Client is closed".to_string())
+}
+
+impl FireboltClient {
+    // Closes this client and every clone of it. Requests in flight stop
+    // waiting and fail, as do any sent afterwards. Queries already running on
+    // the server are left to finish; see `close_and_cancel_queries`.
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub fn close(&self) {
+        let labels = self.shutdown().close();
+        trace_event!(info, in_flight = labels.len(), "Client closed");
+    }
+
+    // Closes the client like `close`, then cancels on the server the queries
+    // that were in flight with a `query_label`: those run while the session
+    // has a label, and those from `query_cancellable` and
+    // `query_with_progress`. Unlabelled queries cannot be found and are left
+    // to finish. Returns the first error from the cancellations, after
+    // attempting all of them.
+    pub async fn close_and_cancel_queries(&self) -> Result<(), FireboltError> {
+        let labels = self.shutdown().close();
+        trace_event!(info, in_flight = labels.len(), "Client closed");
+        let cancelling = self.reopened();
+        let mut first_error = None;
+        for label in labels {
+            let filter = RunningQueryFilter::new().with_label(&label);
+            if let Err(e) = cancelling.kill_queries(&filter).await {
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.shutdown().is_closed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::FireboltError;
+    use crate::testing::{MockFirebolt, MockResponse};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_close() {
+        let mock = MockFirebolt::new();
+        mock.on_query(
+            "SELECT slow",
+            MockResponse::empty().with_delay(Duration::from_secs(10)),
+        );
+        let client = mock.connect().await.unwrap();
+        let clone = client.clone();
+
+        let slow = tokio::spawn(async move { clone.query("SELECT slow").await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        client.close();
+
+        let result = tokio::time::timeout(Duration::from_secs(1), slow)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(result, Err(FireboltError::Cancelled(_))));
+        assert!(client.is_closed());
+        let before = mock.queries().len();
+        assert!(matches!(
+            client.query("SELECT 1").await,
+            Err(FireboltError::Cancelled(_))
+        ));
+        assert_eq!(mock.queries().len(), before);
+    }
+
+    #[tokio::test]
+    async fn test_close_and_cancel_queries() {
+        let mock = MockFirebolt::new();
+        mock.on_query(
+            "SELECT slow",
+            MockResponse::empty().with_delay(Duration::from_secs(10)),
+        );
+        mock.on_query_containing(
+            "engine_running_queries",
+            MockResponse::rows(&[("query_id", "text")], vec![vec!["q1".into()]]),
+        );
+        let client = mock.connect().await.unwrap();
+        client
+            .set_parameter("query_label".to_string(), "nightly".to_string())
+            .unwrap();
+        let clone = client.clone();
+
+        let slow = tokio::spawn(async move { clone.query("SELECT slow").await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        client.close_and_cancel_queries().await.unwrap();
+
+        assert!(matches!(
+            slow.await.unwrap(),
+            Err(FireboltError::Cancelled(_))
+        ));
+        let queries = mock.queries();
+        let lookup = queries
+            .iter()
+            .find(|query| query.sql.contains("engine_running_queries"))
+            .unwrap();
+        assert!(lookup.sql.contains("query_label = 'nightly'"));
+        assert!(!lookup.parameters.contains_key("query_label"));
+        assert_eq!(
+            queries.last().unwrap().sql,
+            "CANCEL QUERY WHERE query_id = 'q1'"
+        );
+        assert!(client.is_closed());
+    }
+}
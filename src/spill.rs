@@ -0,0 +1,359 @@
+// Results streamed to a temporary file and read back one row at a time, for
+// exports too large to hold in memory. The server sends them as
+// JSONLines_Compact: a START message with the columns, DATA messages with
+// batches of rows, and a closing FINISH_SUCCESSFULLY or FINISH_WITH_ERRORS.
+
+use crate::error::FireboltError;
+use crate::result::Row;
+use crate::types::{Column, ConversionMode};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tempfile::{NamedTempFile, TempPath};
+use tokio::io::AsyncWrite;
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::PollSender;
+
+pub(crate) const OUTPUT_FORMAT: &str = "JSONLines_Compact";
+
+// Chunks waiting for the spill thread to write them.
+const SPILL_QUEUE: usize = 16;
+
+// The file is removed when the result set is dropped.
+#[derive(Debug)]
+pub struct SpilledResultSet {
+    path: TempPath,
+    columns: Vec<Column>,
+    conversion_mode: ConversionMode,
+}
+
+impl SpilledResultSet {
+    // Readable only by the current user, and removed when dropped, so a
+    // failed or abandoned download leaves nothing behind.
+    pub(crate) fn temp_file() -> Result<NamedTempFile, FireboltError> {
+        tempfile::Builder::new()
+            .prefix("firebolt-")
+            .suffix(".jsonl")
+            .tempfile()
+            .map_err(|e| {
+                FireboltError::Serialization(format!("Failed to create temporary file: {e}"))
+            })
+    }
+
+    pub(crate) fn open(
+        path: TempPath,
+        conversion_mode: ConversionMode,
+    ) -> Result<Self, FireboltError> {
+        let mut result = Self {
+            path,
+            columns: Vec::new(),
+            conversion_mode,
+        };
+        let mut lines = result.lines()?;
+        loop {
+            let Some(message) = next_message(&mut lines)? else {
+                return Err(truncated());
+            };
+            match message_type(&message) {
+                "START" => {
+                    let columns = message
+                        .get("result_columns")
+                        .and_then(|c| c.as_array())
+                        .ok_or_else(|| {
+                            FireboltError::Query(
+                                "Missing or invalid 'result_columns' field in response".to_string(),
+                            )
+                        })?;
+                    result.columns = crate::parser::parse_column_list(columns)?;
+                    return Ok(result);
+                }
+                "FINISH_WITH_ERRORS" => return Err(finish_error(&message)),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Each call reads the file again from the start. A query that fails part
+    // way through yields the rows sent before the failure and then the error.
+    pub fn rows(&self) -> Result<SpilledRows<'_>, FireboltError> {
+        Ok(SpilledRows {
+            lines: self.lines()?,
+            columns: &self.columns,
+            conversion_mode: self.conversion_mode,
+            pending: Vec::new().into_iter(),
+            done: false,
+        })
+    }
+
+    fn lines(&self) -> Result<Lines<BufReader<File>>, FireboltError> {
+        let file = File::open(&self.path).map_err(|e| {
+            FireboltError::Serialization(format!("Failed to open {}: {e}", self.path.display()))
+        })?;
+        Ok(BufReader::new(file).lines())
+    }
+}
+
+// Writes a spilled response on a thread of its own, so a large download does
+// not block the async executor on disk writes. Few chunks are queued, so a
+// slow disk slows the download instead of filling memory.
+pub(crate) struct SpillWriter {
+    chunks: PollSender<Vec<u8>>,
+    done: oneshot::Receiver<std::io::Result<()>>,
+}
+
+impl SpillWriter {
+    pub(crate) fn new(file: File) -> Self {
+        let (sender, mut receiver) = mpsc::channel::<Vec<u8>>(SPILL_QUEUE);
+        let (finished, done) = oneshot::channel();
+        std::thread::spawn(move || {
+            let mut writer = BufWriter::new(file);
+            let mut write = || {
+                while let Some(chunk) = receiver.blocking_recv() {
+                    writer.write_all(&chunk)?;
+                }
+                writer.flush()
+            };
+            let _ = finished.send(write());
+        });
+        Self {
+            chunks: PollSender::new(sender),
+            done,
+        }
+    }
+
+    // Waits until everything written so far is on disk.
+    pub(crate) async fn finish(mut self) -> std::io::Result<()> {
+        self.chunks.close();
+        (&mut self.done)
+            .await
+            .unwrap_or_else(|_| Err(std::io::ErrorKind::BrokenPipe.into()))
+    }
+
+    // The thread stops taking chunks only once a write has failed.
+    fn failure(&mut self) -> std::io::Error {
+        match self.done.try_recv() {
+            Ok(Err(e)) => e,
+            _ => std::io::ErrorKind::BrokenPipe.into(),
+        }
+    }
+}
+
+impl AsyncWrite for SpillWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        if ready!(self.chunks.poll_reserve(cx)).is_err()
+            || self.chunks.send_item(buf.to_vec()).is_err()
+        {
+            return Poll::Ready(Err(self.failure()));
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    // Chunks reach the file in the background; `finish` waits for them.
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+pub struct SpilledRows<'a> {
+    lines: Lines<BufReader<File>>,
+    columns: &'a [Column],
+    conversion_mode: ConversionMode,
+    pending: std::vec::IntoIter<serde_json::Value>,
+    done: bool,
+}
+
+impl Iterator for SpilledRows<'_> {
+    type Item = Result<Row, FireboltError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(values) = self.pending.next() {
+                let serde_json::Value::Array(values) = values else {
+                    return Some(Err(FireboltError::Query(
+                        "Row data is not an array".to_string(),
+                    )));
+                };
+                return Some(Ok(Row::new(values, self.columns.to_vec())
+                    .with_conversion_mode(self.conversion_mode)));
+            }
+            if self.done {
+                return None;
+            }
+
+            let mut message = match next_message(&mut self.lines) {
+                Ok(Some(message)) => message,
+                Ok(None) => {
+                    self.done = true;
+                    return Some(Err(truncated()));
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+            match message_type(&message).to_string().as_str() {
+                "DATA" => match message.get_mut("data").map(serde_json::Value::take) {
+                    Some(serde_json::Value::Array(rows)) => self.pending = rows.into_iter(),
+                    _ => {
+                        self.done = true;
+                        return Some(Err(FireboltError::Query(
+                            "Missing or invalid 'data' field in response".to_string(),
+                        )));
+                    }
+                },
+                "FINISH_SUCCESSFULLY" => self.done = true,
+                "FINISH_WITH_ERRORS" => {
+                    self.done = true;
+                    return Some(Err(finish_error(&message)));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn next_message(
+    lines: &mut Lines<BufReader<File>>,
+) -> Result<Option<serde_json::Value>, FireboltError> {
+    for line in lines {
+        let line = line.map_err(|e| {
+            FireboltError::Serialization(format!("Failed to read spilled result: {e}"))
+        })?;
+        if !line.trim().is_empty() {
+            return crate::parser::parse_json(&line).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+fn message_type(message: &serde_json::Value) -> &str {
+    message
+        .get("message_type")
+        .and_then(|t| t.as_str())
+        .unwrap_or_default()
+}
+
+fn finish_error(message: &serde_json::Value) -> FireboltError {
+    let descriptions: Vec<&str> = message
+        .get("errors")
+        .and_then(|e| e.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|error| error.get("description").and_then(|d| d.as_str()))
+        .collect();
+    if descriptions.is_empty() {
+        crate::parser::parse_server_error(message.to_string())
+    } else {
+        crate::parser::parse_server_error(descriptions.join("; "))
+    }
+}
+
+fn truncated() -> FireboltError {
+    FireboltError::Query("Result stream ended before the query finished".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spill(lines: &[&str]) -> TempPath {
+        let mut file = SpilledResultSet::temp_file().unwrap();
+        file.write_all(lines.join("\n").as_bytes()).unwrap();
+        file.into_temp_path()
+    }
+
+    #[tokio::test]
+    async fn test_spill_writer() {
+        use tokio::io::AsyncWriteExt;
+
+        let (file, path) = SpilledResultSet::temp_file().unwrap().into_parts();
+        let mut writer = SpillWriter::new(file);
+        for _ in 0..100 {
+            writer.write_all(b"0123456789").await.unwrap();
+        }
+        writer.finish().await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap().len(), 1000);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let kept = path.to_path_buf();
+        drop(path);
+        assert!(!kept.exists());
+    }
+
+    #[test]
+    fn test_rows_are_read_lazily_from_disk() {
+        let path = spill(&[
+            r#"{"message_type":"START","result_columns":[{"name":"n","type":"int"},{"name":"s","type":"text null"}]}"#,
+            r#"{"message_type":"DATA","data":[[1,"a"],[2,null]]}"#,
+            r#"{"message_type":"DATA","data":[[3,"c"]]}"#,
+            r#"{"message_type":"FINISH_SUCCESSFULLY","statistics":{}}"#,
+        ]);
+        let kept = path.to_path_buf();
+        let result = SpilledResultSet::open(path, ConversionMode::default()).unwrap();
+        assert_eq!(result.columns().len(), 2);
+        assert!(result.columns()[1].is_nullable);
+
+        let rows: Vec<Row> = result.rows().unwrap().collect::<Result<_, _>>().unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2].get::<i32>("n").unwrap(), 3);
+        assert_eq!(rows[1].get_opt::<String>("s").unwrap(), None);
+        assert_eq!(result.rows().unwrap().count(), 3);
+
+        drop(result);
+        assert!(!kept.exists());
+    }
+
+    #[test]
+    fn test_errors_surface_while_iterating() {
+        let path = spill(&[
+            r#"{"message_type":"START","result_columns":[{"name":"n","type":"int"}]}"#,
+            r#"{"message_type":"DATA","data":[[1]]}"#,
+            r#"{"message_type":"FINISH_WITH_ERRORS","errors":[{"description":"division by zero"}]}"#,
+        ]);
+        let result = SpilledResultSet::open(path, ConversionMode::default()).unwrap();
+        let mut rows = result.rows().unwrap();
+        assert!(rows.next().unwrap().is_ok());
+        assert!(matches!(
+            rows.next(),
+            Some(Err(FireboltError::Query(message))) if message.contains("division by zero")
+        ));
+        assert!(rows.next().is_none());
+
+        let path = spill(&[r#"{"message_type":"START","result_columns":[]}"#]);
+        let result = SpilledResultSet::open(path, ConversionMode::default()).unwrap();
+        assert!(matches!(
+            result.rows().unwrap().next(),
+            Some(Err(FireboltError::Query(_)))
+        ));
+
+        let path = spill(&[
+            r#"{"message_type":"FINISH_WITH_ERRORS","errors":[{"description":"no such table"}]}"#,
+        ]);
+        let kept = path.to_path_buf();
+        assert!(SpilledResultSet::open(path, ConversionMode::default()).is_err());
+        assert!(!kept.exists());
+    }
+}
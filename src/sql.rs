@@ -0,0 +1,529 @@
+use crate::error::FireboltError;
+use crate::types::Type;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    #[cfg(feature = "decimal")]
+    Decimal(Decimal),
+    Text(String),
+    Bytes(Vec<u8>),
+    Date(NaiveDate),
+    Timestamp(NaiveDateTime),
+    TimestampTz(DateTime<Utc>),
+    Array(Vec<SqlValue>),
+}
+
+impl SqlValue {
+    // Renders the value as a Firebolt SQL literal. Text uses standard
+    // string syntax where only the quote character needs escaping.
+    pub fn to_literal(&self) -> String {
+        match self {
+            SqlValue::Null => "NULL".to_string(),
+            SqlValue::Bool(value) => if *value { "TRUE" } else { "FALSE" }.to_string(),
+            SqlValue::Int(value) => value.to_string(),
+            SqlValue::Float(value) if value.is_nan() => "'nan'::double".to_string(),
+            SqlValue::Float(value) if value.is_infinite() => {
+                let sign = if *value < 0.0 { "-" } else { "" };
+                format!("'{sign}inf'::double")
+            }
+            SqlValue::Float(value) => format!("{value:?}"),
+            #[cfg(feature = "decimal")]
+            SqlValue::Decimal(value) => value.to_string(),
+            SqlValue::Text(value) => quote(value),
+            SqlValue::Bytes(value) => format!("'\\x{}'::bytea", crate::types::encode_hex(value)),
+            SqlValue::Date(value) => format!("'{}'::date", value.format("%Y-%m-%d")),
+            SqlValue::Timestamp(value) => {
+                format!("'{}'::timestamp", value.format("%Y-%m-%d %H:%M:%S%.f"))
+            }
+            SqlValue::TimestampTz(value) => {
+                format!("'{}'::timestamptz", value.format("%Y-%m-%d %H:%M:%S%.f+00"))
+            }
+            SqlValue::Array(values) => {
+                let items: Vec<String> = values.iter().map(SqlValue::to_literal).collect();
+                format!("[{}]", items.join(", "))
+            }
+        }
+    }
+
+    // Like `to_literal`, but decimals are cast from text, since a bare
+    // numeric literal with a fraction is read as a double and would lose
+    // digits. Used wherever the SDK writes values into SQL itself.
+    pub(crate) fn to_exact_literal(&self) -> String {
+        match self {
+            #[cfg(feature = "decimal")]
+            SqlValue::Decimal(value) => {
+                format!("CAST('{value}' AS decimal(38, {}))", value.scale())
+            }
+            SqlValue::Array(values) => {
+                let items: Vec<String> = values.iter().map(SqlValue::to_exact_literal).collect();
+                format!("[{}]", items.join(", "))
+            }
+            _ => self.to_literal(),
+        }
+    }
+
+    // Like `to_exact_literal`, but checks the value fits a column of type `ty`
+    // and casts text to temporal, geography, JSON and interval columns.
+    pub fn to_literal_for(&self, ty: &Type) -> Result<String, FireboltError> {
+        let compatible = match (self, ty) {
+            (SqlValue::Null, _) => true,
+            (
+                SqlValue::Text(value),
+                Type::Date
+                | Type::Timestamp
+                | Type::TimestampTZ
+                | Type::Geography
+                | Type::Json
+                | Type::Interval,
+            ) => {
+                return Ok(format!("{}::{}", quote(value), sql_type_name(ty)));
+            }
+            (SqlValue::Bool(_), Type::Boolean) => true,
+            (
+                SqlValue::Int(_),
+                Type::Int | Type::Long | Type::Float | Type::Double | Type::Decimal,
+            ) => true,
+            (SqlValue::Float(_), Type::Float | Type::Double) => true,
+            #[cfg(feature = "decimal")]
+            (SqlValue::Decimal(_), Type::Decimal | Type::Float | Type::Double) => true,
+            (SqlValue::Text(_), Type::Text) => true,
+            (SqlValue::Bytes(_), Type::Bytes) => true,
+            (SqlValue::Date(_), Type::Date) => true,
+            (SqlValue::Timestamp(_), Type::Timestamp) => true,
+            (SqlValue::TimestampTz(_), Type::TimestampTZ) => true,
+            (SqlValue::Array(_), Type::Array) => true,
+            _ => false,
+        };
+        if !compatible {
+            return Err(FireboltError::Serialization(format!(
+                "Cannot use {} value as {}",
+                self.kind(),
+                sql_type_name(ty)
+            )));
+        }
+        Ok(self.to_exact_literal())
+    }
+
+    // The value as sent in Firebolt's `query_parameters`. The server casts it
+    // to the type its placeholder needs, so anything JSON cannot represent
+    // exactly is sent as text.
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        match self {
+            SqlValue::Null => serde_json::Value::Null,
+            SqlValue::Bool(value) => (*value).into(),
+            SqlValue::Int(value) => (*value).into(),
+            SqlValue::Float(value) if value.is_nan() => "nan".into(),
+            SqlValue::Float(value) if value.is_infinite() => {
+                if *value < 0.0 { "-inf" } else { "inf" }.into()
+            }
+            SqlValue::Float(value) => (*value).into(),
+            #[cfg(feature = "decimal")]
+            SqlValue::Decimal(value) => value.to_string().into(),
+            SqlValue::Text(value) => value.as_str().into(),
+            SqlValue::Bytes(value) => format!("\\x{}", crate::types::encode_hex(value)).into(),
+            SqlValue::Date(value) => value.format("%Y-%m-%d").to_string().into(),
+            SqlValue::Timestamp(value) => value.format("%Y-%m-%d %H:%M:%S%.f").to_string().into(),
+            SqlValue::TimestampTz(value) => {
+                value.format("%Y-%m-%d %H:%M:%S%.f+00").to_string().into()
+            }
+            SqlValue::Array(values) => values.iter().map(SqlValue::to_json).collect(),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            SqlValue::Null => "null",
+            SqlValue::Bool(_) => "boolean",
+            SqlValue::Int(_) => "integer",
+            SqlValue::Float(_) => "float",
+            #[cfg(feature = "decimal")]
+            SqlValue::Decimal(_) => "decimal",
+            SqlValue::Text(_) => "text",
+            SqlValue::Bytes(_) => "bytes",
+            SqlValue::Date(_) => "date",
+            SqlValue::Timestamp(_) => "timestamp",
+            SqlValue::TimestampTz(_) => "timestamptz",
+            SqlValue::Array(_) => "array",
+        }
+    }
+}
+
+// Replaces each `?` placeholder with the exact literal of the matching value.
+pub(crate) fn bind(sql: &str, params: &[SqlValue]) -> Result<String, FireboltError> {
+    let segments = split_placeholders(sql, false).segments;
+    check_parameter_count(segments.len() - 1, params.len())?;
+    let mut output = String::with_capacity(sql.len());
+    for (segment, value) in segments.iter().zip(params) {
+        output.push_str(segment);
+        output.push_str(&value.to_exact_literal());
+    }
+    output.push_str(&segments[segments.len() - 1]);
+    Ok(output)
+}
+
+pub(crate) fn check_parameter_count(
+    placeholders: usize,
+    params: usize,
+) -> Result<(), FireboltError> {
+    if params < placeholders {
+        return Err(FireboltError::Query(format!(
+            "Not enough parameters for placeholder {}",
+            params + 1
+        )));
+    }
+    if params > placeholders {
+        return Err(FireboltError::Query(format!(
+            "{} parameter(s) left over after binding {placeholders} placeholder(s)",
+            params - placeholders
+        )));
+    }
+    Ok(())
+}
+
+// A statement split around its placeholders. There is always one more
+// segment than placeholders; `names` holds `None` for each `?` and the name
+// of each `:name`.
+pub(crate) struct Placeholders {
+    pub(crate) segments: Vec<String>,
+    pub(crate) names: Vec<Option<String>>,
+}
+
+// Question marks and colons inside quoted strings, quoted identifiers and
+// comments are left alone, as are `::` casts. `:name` placeholders are only
+// recognised when `named` is set.
+pub(crate) fn split_placeholders(sql: &str, named: bool) -> Placeholders {
+    let mut placeholders = Placeholders {
+        segments: Vec::new(),
+        names: Vec::new(),
+    };
+    let mut output = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' => {
+                output.push(c);
+                for next in chars.by_ref() {
+                    output.push(next);
+                    if next == c {
+                        break;
+                    }
+                }
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                output.push(c);
+                for next in chars.by_ref() {
+                    output.push(next);
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                output.push(c);
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    output.push(next);
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            ':' if chars.peek() == Some(&':') => {
+                output.push(c);
+                output.push(chars.next().unwrap_or(':'));
+            }
+            ':' if named
+                && chars
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphabetic() || *c == '_') =>
+            {
+                let mut name = String::new();
+                while let Some(next) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    name.push(next);
+                }
+                placeholders.segments.push(std::mem::take(&mut output));
+                placeholders.names.push(Some(name));
+            }
+            '?' => {
+                placeholders.segments.push(std::mem::take(&mut output));
+                placeholders.names.push(None);
+            }
+            _ => output.push(c),
+        }
+    }
+    placeholders.segments.push(output);
+    placeholders
+}
+
+// Counts the `?` placeholders in a statement with the same rules as binding.
+// Being a `const fn`, it lets a statement fixed at compile time be checked
+// against its parameters at compile time too:
+// `const _: () = assert!(placeholder_count(SQL) == 2);`
+pub const fn placeholder_count(sql: &str) -> usize {
+    let bytes = sql.as_bytes();
+    let mut count = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let next = if i + 1 < bytes.len() { bytes[i + 1] } else { 0 };
+        if c == b'\'' || c == b'"' {
+            i += 1;
+            while i < bytes.len() && bytes[i] != c {
+                i += 1;
+            }
+        } else if c == b'-' && next == b'-' {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+        } else if c == b'/' && next == b'*' {
+            i += 2;
+            while i < bytes.len() && !(bytes[i - 1] == b'*' && bytes[i] == b'/') {
+                i += 1;
+            }
+        } else if c == b'?' {
+            count += 1;
+        }
+        i += 1;
+    }
+    count
+}
+
+pub(crate) fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+pub(crate) fn sql_type_name(ty: &Type) -> &'static str {
+    match ty {
+        Type::Int => "int",
+        Type::Long => "bigint",
+        Type::Float => "real",
+        Type::Double => "double",
+        Type::Decimal => "decimal",
+        Type::Text => "text",
+        Type::Date => "date",
+        Type::Timestamp => "timestamp",
+        Type::TimestampTZ => "timestamptz",
+        Type::Boolean => "boolean",
+        Type::Array => "array",
+        Type::Struct => "struct",
+        Type::Geography => "geography",
+        Type::Bytes => "bytea",
+        Type::Json => "json",
+        Type::Interval => "interval",
+    }
+}
+
+macro_rules! impl_from_int {
+    ($($int:ty),+) => {
+        $(impl From<$int> for SqlValue {
+            fn from(value: $int) -> Self {
+                SqlValue::Int(value.into())
+            }
+        })+
+    };
+}
+
+impl_from_int!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<bool> for SqlValue {
+    fn from(value: bool) -> Self {
+        SqlValue::Bool(value)
+    }
+}
+
+impl From<f32> for SqlValue {
+    fn from(value: f32) -> Self {
+        SqlValue::Float(value.into())
+    }
+}
+
+impl From<f64> for SqlValue {
+    fn from(value: f64) -> Self {
+        SqlValue::Float(value)
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl From<Decimal> for SqlValue {
+    fn from(value: Decimal) -> Self {
+        SqlValue::Decimal(value)
+    }
+}
+
+// Firebolt has no UUID type, so UUIDs are bound as their hyphenated text.
+#[cfg(feature = "uuid")]
+impl From<uuid::Uuid> for SqlValue {
+    fn from(value: uuid::Uuid) -> Self {
+        SqlValue::Text(value.hyphenated().to_string())
+    }
+}
+
+impl From<&str> for SqlValue {
+    fn from(value: &str) -> Self {
+        SqlValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for SqlValue {
+    fn from(value: String) -> Self {
+        SqlValue::Text(value)
+    }
+}
+
+impl From<&[u8]> for SqlValue {
+    fn from(value: &[u8]) -> Self {
+        SqlValue::Bytes(value.to_vec())
+    }
+}
+
+impl From<NaiveDate> for SqlValue {
+    fn from(value: NaiveDate) -> Self {
+        SqlValue::Date(value)
+    }
+}
+
+impl From<NaiveDateTime> for SqlValue {
+    fn from(value: NaiveDateTime) -> Self {
+        SqlValue::Timestamp(value)
+    }
+}
+
+impl From<DateTime<Utc>> for SqlValue {
+    fn from(value: DateTime<Utc>) -> Self {
+        SqlValue::TimestampTz(value)
+    }
+}
+
+impl<T: Into<SqlValue>> From<Option<T>> for SqlValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(SqlValue::Null, Into::into)
+    }
+}
+
+impl<T: Into<SqlValue>> From<Vec<T>> for SqlValue {
+    fn from(values: Vec<T>) -> Self {
+        SqlValue::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_literal() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        let timestamp = date.and_hms_micro_opt(12, 30, 0, 500).unwrap();
+        let cases: Vec<(SqlValue, &str)> = vec![
+            (SqlValue::Null, "NULL"),
+            (true.into(), "TRUE"),
+            ((-42i32).into(), "-42"),
+            (1.0f64.into(), "1.0"),
+            (f64::NEG_INFINITY.into(), "'-inf'::double"),
+            ("it's \\n".into(), "'it''s \\n'"),
+            (b"\x01\xff".as_slice().into(), "'\\x01ff'::bytea"),
+            (date.into(), "'2024-01-31'::date"),
+            (timestamp.into(), "'2024-01-31 12:30:00.000500'::timestamp"),
+            (
+                timestamp.and_utc().into(),
+                "'2024-01-31 12:30:00.000500+00'::timestamptz",
+            ),
+            (vec![Some(1), None].into(), "[1, NULL]"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(value.to_literal(), expected);
+        }
+        #[cfg(feature = "decimal")]
+        assert_eq!(
+            SqlValue::from(Decimal::new(12345, 2)).to_literal(),
+            "123.45"
+        );
+        #[cfg(feature = "uuid")]
+        assert_eq!(
+            SqlValue::from(uuid::Uuid::nil()).to_literal(),
+            "'00000000-0000-0000-0000-000000000000'"
+        );
+    }
+
+    #[test]
+    fn test_bind() {
+        assert_eq!(
+            bind(
+                "SELECT '?', \"a?\" FROM t WHERE x = ? -- why?\nAND y = ? /* ? */",
+                &[SqlValue::from("it's"), SqlValue::from(2)]
+            )
+            .unwrap(),
+            "SELECT '?', \"a?\" FROM t WHERE x = 'it''s' -- why?\nAND y = 2 /* ? */"
+        );
+        // Escaped quotes split into two quoted runs, so the placeholder after them still binds.
+        assert_eq!(
+            bind("SELECT 'a''b', ?", &[SqlValue::Null]).unwrap(),
+            "SELECT 'a''b', NULL"
+        );
+        assert!(matches!(
+            bind("SELECT ?, ?", &[SqlValue::from(1)]),
+            Err(FireboltError::Query(_))
+        ));
+        assert!(bind("SELECT 1", &[SqlValue::from(1)]).is_err());
+        #[cfg(feature = "decimal")]
+        assert_eq!(
+            bind(
+                "SELECT ?",
+                &[SqlValue::from(
+                    "12345678901234567890.123".parse::<Decimal>().unwrap()
+                )]
+            )
+            .unwrap(),
+            "SELECT CAST('12345678901234567890.123' AS decimal(38, 3))"
+        );
+    }
+
+    #[test]
+    fn test_split_placeholders() {
+        let sql = "SELECT x::int, ':skip', ? -- :also ?\nFROM t WHERE a = :a_1 AND b = :b /* ? */";
+        let positional = split_placeholders(sql, false);
+        assert_eq!(positional.names, [None]);
+        assert_eq!(placeholder_count(sql), 1);
+
+        let named = split_placeholders(sql, true);
+        assert_eq!(
+            named.names,
+            [None, Some("a_1".to_string()), Some("b".to_string())]
+        );
+        assert_eq!(named.segments[2], " AND b = ");
+        assert_eq!(named.segments[3], " /* ? */");
+
+        const SQL: &str = "SELECT '?', ? /* ? */, ?";
+        const _: () = assert!(placeholder_count(SQL) == 2);
+        assert_eq!(placeholder_count("SELECT 'a''b', ?"), 1);
+        assert_eq!(placeholder_count("SELECT 1 /* ? */ -- ?"), 0);
+    }
+
+    #[test]
+    fn test_to_literal_for_type() {
+        assert_eq!(
+            SqlValue::from("2024-01-31")
+                .to_literal_for(&Type::Date)
+                .unwrap(),
+            "'2024-01-31'::date"
+        );
+        assert_eq!(
+            SqlValue::from(5).to_literal_for(&Type::Double).unwrap(),
+            "5"
+        );
+        assert_eq!(SqlValue::Null.to_literal_for(&Type::Int).unwrap(), "NULL");
+        assert!(matches!(
+            SqlValue::from("5").to_literal_for(&Type::Int),
+            Err(FireboltError::Serialization(_))
+        ));
+        assert!(SqlValue::from(1.5).to_literal_for(&Type::Int).is_err());
+    }
+}
@@ -0,0 +1,531 @@
+// Statements prepared once and run many times with different parameters. The
+// `?` or `:name` placeholders are found when the statement is prepared and
+// rewritten to Firebolt's `$1, $2, ...` form, and each execution only sends the values in
+// the `query_parameters` request parameter, so the SQL text is never rebuilt
+// or re-escaped.
+
+use crate::client::FireboltClient;
+use crate::error::FireboltError;
+use crate::result::ResultSet;
+use crate::sql::SqlValue;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+const QUERY_PARAMETERS: &str = "query_parameters";
+
+// How parameter values reach the server. `Server` sends them separately in
+// `query_parameters`. `Client` is the fallback for servers or statements that
+// do not accept them: each value is written into the SQL text as an escaped
+// literal of its type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParameterBinding {
+    #[default]
+    Server,
+    Client,
+}
+
+#[derive(Debug, Clone)]
+pub struct Statement {
+    client: FireboltClient,
+    prepared: Arc<Prepared>,
+    binding: ParameterBinding,
+}
+
+// The parts of a statement worked out once from its SQL text.
+#[derive(Debug)]
+struct Prepared {
+    sql: String,
+    native_sql: String,
+    // The text around the placeholders and, for each placeholder, the index
+    // of the value it takes, for client-side binding.
+    segments: Vec<String>,
+    slots: Vec<usize>,
+    placeholders: usize,
+    // Distinct `:name` placeholders in order of first use; empty for a
+    // statement with `?` placeholders.
+    names: Vec<String>,
+    read_only: bool,
+}
+
+impl Prepared {
+    // A statement with any `?` is positional and its colons are left alone,
+    // since a colon can also appear in array slices. Otherwise each `:name`
+    // becomes `$n` for the n-th distinct name, so a repeated name is bound once.
+    fn new(sql: &str) -> Self {
+        let mut split = crate::sql::split_placeholders(sql, true);
+        if split.names.iter().any(Option::is_none) {
+            split = crate::sql::split_placeholders(sql, false);
+        }
+        let mut names: Vec<String> = Vec::new();
+        let mut slots = Vec::with_capacity(split.names.len());
+        for (i, name) in split.names.iter().enumerate() {
+            slots.push(match name {
+                Some(name) => names.iter().position(|n| n == name).unwrap_or_else(|| {
+                    names.push(name.clone());
+                    names.len() - 1
+                }),
+                None => i,
+            });
+        }
+        let native_sql = render(&split.segments, &slots, |slot| format!("${}", slot + 1));
+        Self {
+            sql: sql.to_string(),
+            native_sql,
+            segments: split.segments,
+            slots,
+            placeholders: if names.is_empty() {
+                split.names.len()
+            } else {
+                names.len()
+            },
+            names,
+            read_only: crate::client::is_read_only(sql),
+        }
+    }
+}
+
+fn render(segments: &[String], slots: &[usize], value: impl Fn(usize) -> String) -> String {
+    let mut sql = segments[0].clone();
+    for (slot, segment) in slots.iter().zip(&segments[1..]) {
+        sql.push_str(&value(*slot));
+        sql.push_str(segment);
+    }
+    sql
+}
+
+// A least-recently-used map from SQL text to its prepared form.
+#[derive(Debug)]
+pub(crate) struct StatementCache {
+    capacity: usize,
+    entries: Mutex<CacheEntries>,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    clock: u64,
+    statements: HashMap<String, (u64, Arc<Prepared>)>,
+}
+
+impl StatementCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::default(),
+        }
+    }
+
+    fn get_or_prepare(&self, sql: &str) -> Arc<Prepared> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.clock += 1;
+        let now = entries.clock;
+        if let Some((last_used, prepared)) = entries.statements.get_mut(sql) {
+            *last_used = now;
+            return prepared.clone();
+        }
+
+        let prepared = Arc::new(Prepared::new(sql));
+        if self.capacity == 0 {
+            return prepared;
+        }
+        if entries.statements.len() >= self.capacity {
+            let oldest = entries
+                .statements
+                .iter()
+                .min_by_key(|(_, (last_used, _))| *last_used)
+                .map(|(sql, _)| sql.clone());
+            if let Some(oldest) = oldest {
+                entries.statements.remove(&oldest);
+            }
+        }
+        entries
+            .statements
+            .insert(sql.to_string(), (now, prepared.clone()));
+        prepared
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .statements
+            .len()
+    }
+}
+
+impl Statement {
+    pub fn sql(&self) -> &str {
+        &self.prepared.sql
+    }
+
+    // For a statement with named placeholders, the number of distinct names.
+    pub fn parameter_count(&self) -> usize {
+        self.prepared.placeholders
+    }
+
+    pub fn parameter_names(&self) -> &[String] {
+        &self.prepared.names
+    }
+
+    // Overrides the client's `with_parameter_binding` for this statement.
+    pub fn with_parameter_binding(mut self, binding: ParameterBinding) -> Self {
+        self.binding = binding;
+        self
+    }
+
+    // Binds `?` placeholders in order.
+    pub async fn query<I, V>(&self, params: I) -> Result<ResultSet, FireboltError>
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<SqlValue>,
+    {
+        if !self.prepared.names.is_empty() {
+            return Err(FireboltError::Query(
+                "Statement uses named placeholders; bind them with query_named".to_string(),
+            ));
+        }
+        let values: Vec<SqlValue> = params.into_iter().map(Into::into).collect();
+        crate::sql::check_parameter_count(self.prepared.placeholders, values.len())?;
+        self.execute(&values).await
+    }
+
+    // Binds `:name` placeholders by name. Every name must be given exactly
+    // once, and names the statement does not use are rejected.
+    pub async fn query_named<I, K, V>(&self, params: I) -> Result<ResultSet, FireboltError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Into<SqlValue>,
+    {
+        if self.prepared.names.is_empty() && self.prepared.placeholders > 0 {
+            return Err(FireboltError::Query(
+                "Statement uses ? placeholders; bind them with query".to_string(),
+            ));
+        }
+        let mut values: Vec<Option<SqlValue>> = vec![None; self.prepared.names.len()];
+        for (name, value) in params {
+            let name = name.as_ref().strip_prefix(':').unwrap_or(name.as_ref());
+            let index = self
+                .prepared
+                .names
+                .iter()
+                .position(|n| n == name)
+                .ok_or_else(|| {
+                    FireboltError::Query(format!("Statement has no placeholder ':{name}'"))
+                })?;
+            if values[index].replace(value.into()).is_some() {
+                return Err(FireboltError::Query(format!(
+                    "Placeholder ':{name}' is bound more than once"
+                )));
+            }
+        }
+        let values = values
+            .into_iter()
+            .zip(&self.prepared.names)
+            .map(|(value, name)| {
+                value.ok_or_else(|| {
+                    FireboltError::Query(format!("No value for placeholder ':{name}'"))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.execute(&values).await
+    }
+
+    async fn execute(&self, values: &[SqlValue]) -> Result<ResultSet, FireboltError> {
+        self.client.connected().await?;
+        let mut request_params = self.client.request_params();
+        let sql = match self.binding {
+            ParameterBinding::Client => {
+                render(&self.prepared.segments, &self.prepared.slots, |slot| {
+                    values[slot].to_exact_literal()
+                })
+            }
+            ParameterBinding::Server => {
+                if !values.is_empty() {
+                    request_params.insert(QUERY_PARAMETERS.to_string(), query_parameters(values));
+                }
+                self.prepared.native_sql.clone()
+            }
+        };
+        self.client
+            .run_query_with_params(
+                &sql,
+                request_params,
+                self.prepared.read_only,
+                self.client.max_rows(),
+            )
+            .await
+            .map(|(result, _)| result)
+    }
+}
+
+// `[{"name": "$1", "value": ...}, ...]`
+fn query_parameters(values: &[SqlValue]) -> String {
+    let params: Vec<serde_json::Value> = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            serde_json::json!({
+                "name": format!("${}", i + 1),
+                "value": value.to_json(),
+            })
+        })
+        .collect();
+    serde_json::Value::Array(params).to_string()
+}
+
+impl FireboltClient {
+    pub fn prepare(&self, sql: &str) -> Result<Statement, FireboltError> {
+        if sql.trim().is_empty() {
+            return Err(FireboltError::Query(
+                "Cannot prepare an empty query".to_string(),
+            ));
+        }
+        let prepared = match self.statement_cache() {
+            Some(cache) => cache.get_or_prepare(sql),
+            None => Arc::new(Prepared::new(sql)),
+        };
+        Ok(Statement {
+            client: self.clone(),
+            prepared,
+            binding: self.parameter_binding(),
+        })
+    }
+
+    // Prepares and runs the statement once. Prefer `prepare` for statements
+    // run repeatedly.
+    pub async fn query_with_params<I, V>(
+        &self,
+        sql: &str,
+        params: I,
+    ) -> Result<ResultSet, FireboltError>
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<SqlValue>,
+    {
+        self.prepare(sql)?.query(params).await
+    }
+
+    pub async fn query_with_named_params<I, K, V>(
+        &self,
+        sql: &str,
+        params: I,
+    ) -> Result<ResultSet, FireboltError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Into<SqlValue>,
+    {
+        self.prepare(sql)?.query_named(params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockFirebolt, MockResponse};
+
+    #[tokio::test]
+    async fn test_prepared_statement() {
+        let mock = MockFirebolt::new();
+        mock.set_default_response(MockResponse::rows(&[("id", "int")], vec![vec![1.into()]]));
+        let client = mock.connect().await.unwrap();
+
+        let statement = client
+            .prepare("SELECT id FROM t WHERE name = ? AND note <> '?' AND day >= ? -- why?")
+            .unwrap();
+        assert_eq!(statement.parameter_count(), 2);
+        statement
+            .query([SqlValue::from("a'b"), SqlValue::from(3)])
+            .await
+            .unwrap();
+        statement
+            .query([SqlValue::Null, SqlValue::from(vec![1.5, f64::NAN])])
+            .await
+            .unwrap();
+
+        let queries = mock.queries();
+        let sent = &queries[queries.len() - 2..];
+        for query in sent {
+            assert_eq!(
+                query.sql,
+                "SELECT id FROM t WHERE name = $1 AND note <> '?' AND day >= $2 -- why?"
+            );
+        }
+        let values = |i: usize| -> serde_json::Value {
+            serde_json::from_str(&sent[i].parameters[QUERY_PARAMETERS]).unwrap()
+        };
+        assert_eq!(
+            values(0),
+            serde_json::json!([{"name": "$1", "value": "a'b"}, {"name": "$2", "value": 3}])
+        );
+        assert_eq!(
+            values(1),
+            serde_json::json!([{"name": "$1", "value": null}, {"name": "$2", "value": [1.5, "nan"]}])
+        );
+
+        assert!(matches!(
+            statement.query([1]).await,
+            Err(FireboltError::Query(message)) if message.contains("placeholder 2")
+        ));
+        assert!(client.prepare("  ").is_err());
+
+        client
+            .query_with_params("SELECT 1", Vec::<SqlValue>::new())
+            .await
+            .unwrap();
+        assert!(!mock
+            .queries()
+            .last()
+            .unwrap()
+            .parameters
+            .contains_key(QUERY_PARAMETERS));
+    }
+
+    #[tokio::test]
+    async fn test_named_placeholders() {
+        let mock = MockFirebolt::new();
+        let client = mock.connect().await.unwrap();
+
+        let statement = client
+            .prepare("SELECT * FROM t WHERE a = :id::int OR b = :id AND c = ':x' AND d > :min")
+            .unwrap();
+        assert_eq!(statement.parameter_names(), ["id", "min"]);
+        assert_eq!(statement.parameter_count(), 2);
+        statement
+            .query_named([(":min", SqlValue::from(5)), ("id", SqlValue::from("a"))])
+            .await
+            .unwrap();
+        let query = mock.queries().pop().unwrap();
+        assert_eq!(
+            query.sql,
+            "SELECT * FROM t WHERE a = $1::int OR b = $1 AND c = ':x' AND d > $2"
+        );
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&query.parameters[QUERY_PARAMETERS]).unwrap(),
+            serde_json::json!([{"name": "$1", "value": "a"}, {"name": "$2", "value": 5}])
+        );
+
+        let error = |result: Result<ResultSet, FireboltError>| match result {
+            Err(FireboltError::Query(message)) => message,
+            other => panic!("unexpected {other:?}"),
+        };
+        assert!(error(statement.query_named([("id", 1)]).await).contains(":min"));
+        assert!(error(
+            statement
+                .query_named([("id", 1), ("min", 2), ("max", 3)])
+                .await
+        )
+        .contains(":max"));
+        assert!(error(
+            statement
+                .query_named([("id", 1), ("id", 2), ("min", 3)])
+                .await
+        )
+        .contains("more than once"));
+        assert!(error(statement.query([1, 2]).await).contains("query_named"));
+
+        // A `?` makes the statement positional, so the slice bound is not a name.
+        let positional = client
+            .prepare("SELECT arr[1:n] FROM t WHERE id = ?")
+            .unwrap();
+        assert!(positional.parameter_names().is_empty());
+        assert!(error(positional.query_named([("n", 1)]).await).contains("? placeholders"));
+        client
+            .query_with_named_params("SELECT :a", [("a", true)])
+            .await
+            .unwrap();
+        assert_eq!(mock.queries().pop().unwrap().sql, "SELECT $1");
+    }
+
+    #[tokio::test]
+    async fn test_client_side_binding() {
+        let mock = MockFirebolt::new();
+        let client = mock
+            .client_factory()
+            .with_parameter_binding(ParameterBinding::Client)
+            .build()
+            .await
+            .unwrap();
+        let day = chrono::NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+
+        client
+            .query_with_named_params(
+                "SELECT * FROM t WHERE name = :name AND day = :day AND raw = :raw OR alias = :name",
+                [
+                    ("name", SqlValue::from("it's")),
+                    ("day", SqlValue::from(day)),
+                    ("raw", SqlValue::from(b"\x00".as_slice())),
+                ],
+            )
+            .await
+            .unwrap();
+        let query = mock.queries().pop().unwrap();
+        assert_eq!(
+            query.sql,
+            "SELECT * FROM t WHERE name = 'it''s' AND day = '2024-01-31'::date \
+             AND raw = '\\x00'::bytea OR alias = 'it''s'"
+        );
+        assert!(!query.parameters.contains_key(QUERY_PARAMETERS));
+
+        client
+            .prepare("SELECT ?")
+            .unwrap()
+            .with_parameter_binding(ParameterBinding::Server)
+            .query([vec![1, 2]])
+            .await
+            .unwrap();
+        let query = mock.queries().pop().unwrap();
+        assert_eq!(query.sql, "SELECT $1");
+        assert!(query.parameters.contains_key(QUERY_PARAMETERS));
+
+        #[cfg(feature = "decimal")]
+        assert_eq!(
+            SqlValue::from(vec![rust_decimal::Decimal::new(-12345, 3)]).to_exact_literal(),
+            "[CAST('-12.345' AS decimal(38, 3))]"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_statement_cache() {
+        let mock = MockFirebolt::new();
+        let client = mock
+            .client_factory()
+            .with_statement_cache(2)
+            .build()
+            .await
+            .unwrap();
+        let cache = client.statement_cache().unwrap();
+
+        let first = client.prepare("SELECT ?").unwrap();
+        let again = client.clone().prepare("SELECT ?").unwrap();
+        assert!(Arc::ptr_eq(&first.prepared, &again.prepared));
+        client
+            .query_with_params("SELECT ?, ?", [1, 2])
+            .await
+            .unwrap();
+        let pair = client.prepare("SELECT ?, ?").unwrap();
+        assert_eq!(cache.len(), 2);
+
+        // "SELECT ?" is used again, so "SELECT ?, ?" is the one evicted.
+        client.prepare("SELECT ?").unwrap();
+        client.prepare("SELECT 1 + ?").unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(Arc::ptr_eq(
+            &client.prepare("SELECT ?").unwrap().prepared,
+            &first.prepared
+        ));
+        assert!(!Arc::ptr_eq(
+            &client.prepare("SELECT ?, ?").unwrap().prepared,
+            &pair.prepared
+        ));
+
+        let uncached = mock.connect().await.unwrap();
+        assert!(uncached.statement_cache().is_none());
+        assert!(!Arc::ptr_eq(
+            &uncached.prepare("SELECT ?").unwrap().prepared,
+            &uncached.prepare("SELECT ?").unwrap().prepared
+        ));
+    }
+}
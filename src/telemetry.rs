@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+
+pub(crate) const REDACTED: &str = "<redacted>";
+
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)+);
+    };
+}
+
+pub(crate) use trace_event;
+
+#[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+pub(crate) enum Operation<'a> {
+    Authenticate { api_endpoint: &'a str },
+    ResolveEngine { account_name: &'a str },
+    Query { sql: &'a str },
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) async fn instrument<T, E: Display>(
+    operation: Operation<'_>,
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    use tracing::field::{display, Empty};
+    use tracing::Instrument;
+
+    let span = match operation {
+        Operation::Authenticate { api_endpoint } => tracing::info_span!(
+            "firebolt.authenticate",
+            api_endpoint,
+            http.status_code = Empty,
+            latency_ms = Empty,
+            error = Empty,
+        ),
+        Operation::ResolveEngine { account_name } => tracing::info_span!(
+            "firebolt.resolve_engine",
+            account_name,
+            http.status_code = Empty,
+            latency_ms = Empty,
+            error = Empty,
+        ),
+        Operation::Query { sql } => tracing::info_span!(
+            "firebolt.query",
+            db.system = "firebolt",
+            db.statement.fingerprint = %fingerprint(sql),
+            firebolt.query_id = Empty,
+            http.status_code = Empty,
+            latency_ms = Empty,
+            error = Empty,
+        ),
+    };
+
+    let start = std::time::Instant::now();
+    let result = future.instrument(span.clone()).await;
+    span.record("latency_ms", start.elapsed().as_millis() as u64);
+    if let Err(e) = &result {
+        span.record("error", display(e));
+    }
+    result
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) async fn instrument<T, E: Display>(
+    _operation: Operation<'_>,
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    future.await
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn record_status(status: u16) {
+    tracing::Span::current().record("http.status_code", status);
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn record_status(_status: u16) {}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn record_query_id(query_id: Option<&str>) {
+    if let Some(query_id) = query_id {
+        tracing::Span::current().record("firebolt.query_id", query_id);
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn record_query_id(_query_id: Option<&str>) {}
+
+#[cfg(feature = "otel")]
+pub(crate) fn trace_headers() -> HashMap<String, String> {
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    let context = tracing::Span::current().context();
+    let mut headers = HashMap::new();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut headers)
+    });
+    headers
+}
+
+#[cfg(not(feature = "otel"))]
+pub(crate) fn trace_headers() -> HashMap<String, String> {
+    HashMap::new()
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) fn redact_params(params: &HashMap<String, String>) -> Vec<(&str, &str)> {
+    let mut redacted: Vec<(&str, &str)> = params
+        .iter()
+        .map(|(key, value)| {
+            let lower = key.to_ascii_lowercase();
+            if ["secret", "token", "password"]
+                .iter()
+                .any(|s| lower.contains(s))
+            {
+                (key.as_str(), REDACTED)
+            } else {
+                (key.as_str(), value.as_str())
+            }
+        })
+        .collect();
+    redacted.sort();
+    redacted
+}
+
+// Literals are replaced and whitespace collapsed before hashing, so the same
+// statement with different values gets the same fingerprint.
+#[cfg(feature = "tracing")]
+pub(crate) fn fingerprint(sql: &str) -> String {
+    // FNV-1a, chosen because it is stable across Rust versions and platforms.
+    let hash = normalize_statement(sql)
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+    format!("{hash:016x}")
+}
+
+// Shortened form of a statement for error messages. Literals are replaced
+// with `?`, so values such as customer data never end up in logs.
+pub(crate) fn statement_summary(sql: &str) -> String {
+    const MAX_CHARS: usize = 120;
+    let normalized = normalize_statement(sql);
+    match normalized.char_indices().nth(MAX_CHARS) {
+        Some((end, _)) => format!("{}...", &normalized[..end]),
+        None => normalized,
+    }
+}
+
+fn normalize_statement(sql: &str) -> String {
+    let mut normalized = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                while let Some(c) = chars.next() {
+                    if c == '\'' {
+                        if chars.peek() == Some(&'\'') {
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                normalized.push('?');
+            }
+            c if c.is_ascii_digit() && !ends_with_identifier(&normalized) => {
+                while chars
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '.')
+                {
+                    chars.next();
+                }
+                normalized.push('?');
+            }
+            c if c.is_whitespace() => {
+                while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                    chars.next();
+                }
+                if !normalized.is_empty() {
+                    normalized.push(' ');
+                }
+            }
+            c => normalized.push(c),
+        }
+    }
+
+    normalized.trim_end().to_string()
+}
+
+fn ends_with_identifier(normalized: &str) -> bool {
+    normalized
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '"')
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_ignores_literals_and_whitespace() {
+        assert_eq!(
+            fingerprint("SELECT * FROM t WHERE id = 1 AND name = 'a'"),
+            fingerprint("SELECT *  FROM t\nWHERE id = 42 AND name = 'it''s'")
+        );
+        assert_eq!(fingerprint("SELECT 1.5e3"), fingerprint(" SELECT 7 "));
+    }
+
+    #[test]
+    fn test_redact_params() {
+        let params = HashMap::from([
+            ("database".to_string(), "db".to_string()),
+            ("client_secret".to_string(), "s3cret".to_string()),
+            ("Access_Token".to_string(), "abc".to_string()),
+        ]);
+        assert_eq!(
+            redact_params(&params),
+            vec![
+                ("Access_Token", REDACTED),
+                ("client_secret", REDACTED),
+                ("database", "db"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_distinguishes_statements() {
+        assert_ne!(
+            fingerprint("SELECT a FROM t"),
+            fingerprint("SELECT b FROM t")
+        );
+        assert_ne!(
+            fingerprint("SELECT col1 FROM t"),
+            fingerprint("SELECT col2 FROM t")
+        );
+        assert_eq!(fingerprint("SELECT 1").len(), 16);
+    }
+}
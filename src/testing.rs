@@ -0,0 +1,582 @@
+use crate::client::{FireboltClient, FireboltClientFactory};
+use crate::error::FireboltError;
+use crate::transport::{BoxFuture, HttpRequest, HttpResponse, Method, Transport};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use url::Url;
+
+pub const MOCK_API_ENDPOINT: &str = "https://api.mock.firebolt.io";
+pub const MOCK_ENGINE_URL: &str = "https://engine.mock.firebolt.io/";
+pub const MOCK_ACCOUNT: &str = "mock_account";
+pub const MOCK_TOKEN: &str = "mock_token";
+
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    delay: Option<Duration>,
+}
+
+impl MockResponse {
+    // `columns` are (name, Firebolt type) pairs, e.g. ("id", "int") or ("tags", "array(text null)").
+    pub fn rows(columns: &[(&str, &str)], rows: Vec<Vec<serde_json::Value>>) -> Self {
+        let meta: Vec<serde_json::Value> = columns
+            .iter()
+            .map(|(name, r#type)| serde_json::json!({"name": name, "type": r#type}))
+            .collect();
+        let body = serde_json::json!({
+            "meta": meta,
+            "rows": rows.len(),
+            "data": rows,
+        });
+        Self::json(200, &body)
+    }
+
+    // The response of a statement that returns no result set, such as DDL or INSERT.
+    pub fn empty() -> Self {
+        Self::rows(&[], Vec::new())
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: message.as_bytes().to_vec(),
+            delay: None,
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_updated_parameters(self, parameters: &[(&str, &str)]) -> Self {
+        let value: Vec<String> = parameters.iter().map(|(k, v)| format!("{k}={v}")).collect();
+        self.with_header("Firebolt-Update-Parameters", &value.join(","))
+    }
+
+    pub fn with_removed_parameters(self, names: &[&str]) -> Self {
+        self.with_header("Firebolt-Remove-Parameters", &names.join(","))
+    }
+
+    pub fn with_updated_endpoint(self, endpoint: &str) -> Self {
+        self.with_header("Firebolt-Update-Endpoint", endpoint)
+    }
+
+    pub fn with_reset_session(self) -> Self {
+        self.with_header("Firebolt-Reset-Session", "")
+    }
+
+    // Holds the response back, e.g. to exercise timeouts and cancellation.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    fn json(status: u16, body: &serde_json::Value) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: body.to_string().into_bytes(),
+            delay: None,
+        }
+    }
+
+    fn into_http(self) -> HttpResponse {
+        HttpResponse::new(self.status, self.headers, self.body)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedQuery {
+    pub sql: String,
+    pub parameters: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Exact(String),
+    Contains(String),
+}
+
+impl Matcher {
+    fn matches(&self, sql: &str) -> bool {
+        match self {
+            Matcher::Exact(expected) => sql.trim() == expected.trim(),
+            Matcher::Contains(fragment) => sql.contains(fragment.as_str()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct MockState {
+    rules: Vec<(Matcher, MockResponse)>,
+    default_response: MockResponse,
+    queries: Vec<RecordedQuery>,
+    auth_requests: usize,
+    reject_auth: bool,
+    expired_token: bool,
+}
+
+// An in-process stand-in for the Firebolt API, identity service and engine.
+// It plugs in as the client's `Transport`, so nothing listens on a socket and
+// no credentials are needed. Clones share the same state.
+#[derive(Debug, Clone)]
+pub struct MockFirebolt {
+    state: Arc<Mutex<MockState>>,
+    // Unique per mock so the process-wide token cache never hands one mock's
+    // token to another.
+    client_id: String,
+}
+
+impl Default for MockFirebolt {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockFirebolt {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MockState {
+                rules: Vec::new(),
+                default_response: MockResponse::empty(),
+                queries: Vec::new(),
+                auth_requests: 0,
+                reject_auth: false,
+                expired_token: false,
+            })),
+            client_id: format!("mock_client_{}", uuid::Uuid::new_v4().simple()),
+        }
+    }
+
+    // Rules are checked in the order they were added; the first match wins.
+    pub fn on_query(&self, sql: &str, response: MockResponse) -> &Self {
+        self.lock()
+            .rules
+            .push((Matcher::Exact(sql.to_string()), response));
+        self
+    }
+
+    pub fn on_query_containing(&self, fragment: &str, response: MockResponse) -> &Self {
+        self.lock()
+            .rules
+            .push((Matcher::Contains(fragment.to_string()), response));
+        self
+    }
+
+    // Served for queries that match no rule. Defaults to an empty result.
+    pub fn set_default_response(&self, response: MockResponse) -> &Self {
+        self.lock().default_response = response;
+        self
+    }
+
+    pub fn reject_auth(&self, reject: bool) -> &Self {
+        self.lock().reject_auth = reject;
+        self
+    }
+
+    // The next query is answered with 401, as if the access token had expired.
+    pub fn expire_token(&self) -> &Self {
+        self.lock().expired_token = true;
+        self
+    }
+
+    pub fn queries(&self) -> Vec<RecordedQuery> {
+        self.lock().queries.clone()
+    }
+
+    pub fn auth_requests(&self) -> usize {
+        self.lock().auth_requests
+    }
+
+    pub fn client_factory(&self) -> FireboltClientFactory {
+        FireboltClient::builder()
+            .with_credentials(self.client_id.clone(), "mock_secret".to_string())
+            .with_account(MOCK_ACCOUNT.to_string())
+            .with_api_endpoint(MOCK_API_ENDPOINT.to_string())
+            .with_transport(self.clone())
+    }
+
+    pub async fn connect(&self) -> Result<FireboltClient, FireboltError> {
+        self.client_factory().build().await
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn handle(&self, request: HttpRequest) -> Result<MockResponse, FireboltError> {
+        let url = Url::parse(&request.url)
+            .map_err(|e| FireboltError::Network(format!("Invalid URL {}: {e}", request.url)))?;
+        let path = url.path().to_string();
+        let mut state = self.lock();
+
+        let response = match request.method {
+            Method::Post if path.ends_with("/oauth/token") => {
+                state.auth_requests += 1;
+                state.expired_token = false;
+                if state.reject_auth {
+                    MockResponse::json(
+                        401,
+                        &serde_json::json!({
+                            "error": "access_denied",
+                            "error_description": "Invalid client credentials",
+                        }),
+                    )
+                } else {
+                    MockResponse::json(
+                        200,
+                        &serde_json::json!({"access_token": MOCK_TOKEN, "expires_in": 3600}),
+                    )
+                }
+            }
+            Method::Get if path.ends_with("/engineUrl") => MockResponse::json(
+                200,
+                &serde_json::json!({"engineUrl": MOCK_ENGINE_URL.trim_start_matches("https://")}),
+            ),
+            Method::Get if path.ends_with("/resolve") => MockResponse::json(
+                200,
+                &serde_json::json!({"id": "mock_account_id", "region": "us-east-1"}),
+            ),
+            Method::Post => {
+                let sql = request
+                    .body
+                    .as_deref()
+                    .map(|body| String::from_utf8_lossy(body).into_owned())
+                    .unwrap_or_default();
+                state.queries.push(RecordedQuery {
+                    sql: sql.clone(),
+                    parameters: url.query_pairs().into_owned().collect(),
+                });
+                if state.expired_token {
+                    MockResponse::error(401, "Token expired")
+                } else {
+                    state
+                        .rules
+                        .iter()
+                        .find(|(matcher, _)| matcher.matches(&sql))
+                        .map(|(_, response)| response.clone())
+                        .unwrap_or_else(|| state.default_response.clone())
+                }
+            }
+            Method::Get => MockResponse::error(404, &format!("No mock route for GET {path}")),
+        };
+        Ok(response)
+    }
+}
+
+impl Transport for MockFirebolt {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+        let response = self.handle(request);
+        Box::pin(async move {
+            let response = response?;
+            if let Some(delay) = response.delay {
+                crate::runtime::sleep(delay).await;
+            }
+            Ok(response.into_http())
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    Record,
+    Replay,
+}
+
+// Records request/response pairs to JSON files in record mode and serves
+// them back in replay mode. Each request is keyed by method, path, query
+// parameters and body, so the host and access token do not matter, and
+// repeated identical requests are numbered to replay in order. Credentials
+// are never written: the token request body is dropped and the issued token
+// is replaced.
+pub struct FixtureTransport {
+    mode: FixtureMode,
+    dir: PathBuf,
+    inner: Option<Arc<dyn Transport>>,
+    seen: Mutex<HashMap<String, usize>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Fixture {
+    method: String,
+    path: String,
+    parameters: Vec<(String, String)>,
+    body: Option<String>,
+    response: FixtureResponse,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FixtureResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl FixtureTransport {
+    pub fn record<T: Transport + 'static>(dir: impl Into<PathBuf>, inner: T) -> Self {
+        Self::new(FixtureMode::Record, dir.into(), Some(Arc::new(inner)))
+    }
+
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self::new(FixtureMode::Replay, dir.into(), None)
+    }
+
+    // Records through a `ReqwestTransport` when `FIREBOLT_FIXTURES=record`
+    // and replays otherwise, so CI runs need no credentials. Recording needs
+    // the `tokio` feature.
+    pub fn from_env(dir: impl Into<PathBuf>) -> Self {
+        match std::env::var("FIREBOLT_FIXTURES").as_deref() {
+            #[cfg(feature = "reqwest")]
+            Ok("record") => Self::record(dir, crate::transport::ReqwestTransport::new()),
+            _ => Self::replay(dir),
+        }
+    }
+
+    pub fn mode(&self) -> FixtureMode {
+        self.mode
+    }
+
+    fn new(mode: FixtureMode, dir: PathBuf, inner: Option<Arc<dyn Transport>>) -> Self {
+        Self {
+            mode,
+            dir,
+            inner,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn fixture_path(&self, key: &str) -> PathBuf {
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        let count = seen.entry(key.to_string()).or_insert(0);
+        *count += 1;
+        self.dir
+            .join(format!("{:016x}-{}.json", fnv1a(key.as_bytes()), count))
+    }
+
+    async fn send_request(&self, request: HttpRequest) -> Result<HttpResponse, FireboltError> {
+        let url = Url::parse(&request.url)
+            .map_err(|e| FireboltError::Network(format!("Invalid URL {}: {e}", request.url)))?;
+        let is_auth = url.path().ends_with("/oauth/token");
+        let mut parameters: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        parameters.sort();
+        let body = if is_auth {
+            None
+        } else {
+            request
+                .body
+                .as_deref()
+                .map(|body| String::from_utf8_lossy(body).into_owned())
+        };
+        let method = match request.method {
+            Method::Get => "GET",
+            Method::Post => "POST",
+        };
+        let key = format!(
+            "{method} {} {parameters:?} {}",
+            url.path(),
+            body.as_deref().unwrap_or_default()
+        );
+        let path = self.fixture_path(&key);
+
+        match &self.inner {
+            None => {
+                let contents = std::fs::read_to_string(&path).map_err(|e| {
+                    FireboltError::Configuration(format!(
+                        "No fixture for {method} {} at {}: {e}",
+                        url.path(),
+                        path.display()
+                    ))
+                })?;
+                let fixture: Fixture = serde_json::from_str(&contents).map_err(|e| {
+                    FireboltError::Serialization(format!("Invalid fixture {}: {e}", path.display()))
+                })?;
+                Ok(HttpResponse::new(
+                    fixture.response.status,
+                    fixture.response.headers,
+                    fixture.response.body.into_bytes(),
+                ))
+            }
+            Some(inner) => {
+                let response = inner.send(request).await?;
+                let mut response_body = String::from_utf8_lossy(&response.body).into_owned();
+                if is_auth && response.is_success() {
+                    response_body = redact_token(&response_body);
+                }
+                let fixture = Fixture {
+                    method: method.to_string(),
+                    path: url.path().to_string(),
+                    parameters,
+                    body,
+                    response: FixtureResponse {
+                        status: response.status,
+                        headers: response.headers.clone(),
+                        body: response_body,
+                    },
+                };
+                write_fixture(&path, &fixture)?;
+                Ok(response)
+            }
+        }
+    }
+}
+
+impl Transport for FixtureTransport {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+        Box::pin(self.send_request(request))
+    }
+}
+
+fn write_fixture(path: &Path, fixture: &Fixture) -> Result<(), FireboltError> {
+    let write_error =
+        |e: std::io::Error| FireboltError::Network(format!("Failed to write fixture: {e}"));
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(write_error)?;
+    }
+    let contents = serde_json::to_string_pretty(fixture)
+        .map_err(|e| FireboltError::Serialization(format!("Failed to encode fixture: {e}")))?;
+    std::fs::write(path, contents).map_err(write_error)
+}
+
+fn redact_token(body: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(mut json) => {
+            if let Some(token) = json.get_mut("access_token") {
+                *token = serde_json::Value::String("fixture_token".to_string());
+            }
+            json.to_string()
+        }
+        Err(_) => String::new(),
+    }
+}
+
+// File names must be stable across Rust releases, which rules out `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_query_and_headers() {
+        let mock = MockFirebolt::new();
+        mock.on_query(
+            "SELECT id, name FROM users",
+            MockResponse::rows(
+                &[("id", "int"), ("name", "text")],
+                vec![vec![1.into(), "alice".into()]],
+            ),
+        )
+        .on_query_containing(
+            "USE ENGINE",
+            MockResponse::empty()
+                .with_updated_endpoint("https://other.mock.firebolt.io/?engine=e1")
+                .with_updated_parameters(&[("database", "db1")]),
+        );
+
+        let client = mock.connect().await.unwrap();
+        assert_eq!(mock.auth_requests(), 1);
+
+        let result = client.query("SELECT id, name FROM users").await.unwrap();
+        assert_eq!(result.rows.len(), 1);
+        assert_eq!(result.rows[0].get::<String>("name").unwrap(), "alice");
+
+        client.query("USE ENGINE e1").await.unwrap();
+        client.query("SELECT 1").await.unwrap();
+
+        let queries = mock.queries();
+        assert_eq!(queries.len(), 3);
+        assert_eq!(queries[2].parameters.get("engine").unwrap(), "e1");
+        assert_eq!(queries[2].parameters.get("database").unwrap(), "db1");
+    }
+
+    #[tokio::test]
+    async fn test_mock_auth() {
+        let mock = MockFirebolt::new();
+        mock.reject_auth(true);
+        assert!(matches!(
+            mock.connect().await,
+            Err(FireboltError::Authentication(_))
+        ));
+
+        mock.reject_auth(false);
+        let client = mock.connect().await.unwrap();
+        mock.expire_token();
+        client.query("SELECT 1").await.unwrap();
+        assert_eq!(mock.auth_requests(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_mock_error_response() {
+        let mock = MockFirebolt::new();
+        mock.set_default_response(MockResponse::error(500, "boom"));
+        let client = mock
+            .client_factory()
+            .with_retry_policy(crate::RetryPolicy::none())
+            .build()
+            .await
+            .unwrap();
+        match client.query("SELECT 1").await {
+            Err(FireboltError::Query(msg)) => assert!(msg.contains("boom")),
+            other => panic!("Expected query error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fixture_record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("firebolt-fixtures-{}", uuid::Uuid::new_v4()));
+        let mock = MockFirebolt::new();
+        mock.on_query(
+            "SELECT 1 AS one",
+            MockResponse::rows(&[("one", "int")], vec![vec![1.into()]]),
+        );
+
+        let client = mock
+            .client_factory()
+            .with_transport(FixtureTransport::record(&dir, mock.clone()))
+            .build()
+            .await
+            .unwrap();
+        client.query("SELECT 1 AS one").await.unwrap();
+        client.query("SELECT 1 AS one").await.unwrap();
+
+        let written: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect();
+        assert_eq!(written.len(), 4);
+        assert!(written
+            .iter()
+            .all(|f| !f.contains("mock_secret") && !f.contains(MOCK_TOKEN)));
+
+        let replay = FixtureTransport::replay(&dir);
+        assert_eq!(replay.mode(), FixtureMode::Replay);
+        let client = mock
+            .client_factory()
+            .with_transport(replay)
+            .build()
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            let result = client.query("SELECT 1 AS one").await.unwrap();
+            assert_eq!(result.rows[0].get::<i32>("one").unwrap(), 1);
+        }
+        assert!(matches!(
+            client.query("SELECT 1 AS one").await,
+            Err(FireboltError::Configuration(_))
+        ));
+        assert_eq!(mock.queries().len(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
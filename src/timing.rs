@@ -0,0 +1,85 @@
+// Connection setup timing for the built-in transports. Host lookups and new
+// connections happen inside the HTTP client, out of reach of the request, so
+// they report their durations to a task-local slot that the transport opens
+// around each request. Work the client moves to a background task, such as a
+// connection that finishes after the request was given a pooled one, is not
+// attributed to any request.
+
+use crate::transport::BoxFuture;
+use std::cell::Cell;
+use std::future::Future;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ConnectionTiming {
+    pub(crate) dns: Option<Duration>,
+    pub(crate) connect: Option<Duration>,
+}
+
+tokio::task_local! {
+    static CONNECTION: Cell<ConnectionTiming>;
+}
+
+pub(crate) async fn measure<F: Future>(future: F) -> (F::Output, ConnectionTiming) {
+    CONNECTION
+        .scope(Cell::default(), async {
+            let output = future.await;
+            (output, CONNECTION.with(Cell::get))
+        })
+        .await
+}
+
+fn record(update: impl FnOnce(&mut ConnectionTiming)) {
+    let _ = CONNECTION.try_with(|slot| {
+        let mut timing = slot.get();
+        update(&mut timing);
+        slot.set(timing);
+    });
+}
+
+pub(crate) fn record_dns(elapsed: Duration) {
+    record(|timing| *timing.dns.get_or_insert(Duration::ZERO) += elapsed);
+}
+
+// Wraps a connector to time each new connection, including the host lookup
+// and TLS handshake.
+#[derive(Debug, Clone)]
+pub(crate) struct TimedConnector<S>(pub(crate) S);
+
+impl<S, R> tower_service::Service<R> for TimedConnector<S>
+where
+    S: tower_service::Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<S::Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        let started = Instant::now();
+        let connecting = self.0.call(request);
+        Box::pin(async move {
+            let connection = connecting.await;
+            record(|timing| timing.connect = Some(started.elapsed()));
+            connection
+        })
+    }
+}
+
+#[cfg(feature = "reqwest")]
+#[derive(Debug, Clone)]
+pub(crate) struct TimedConnectorLayer;
+
+#[cfg(feature = "reqwest")]
+impl<S> tower_layer::Layer<S> for TimedConnectorLayer {
+    type Service = TimedConnector<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimedConnector(inner)
+    }
+}
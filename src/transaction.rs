@@ -0,0 +1,216 @@
+// Explicit transactions. Firebolt tracks an open transaction through a
+// session parameter that the server sets on `BEGIN TRANSACTION` and removes on
+// `COMMIT` or `ROLLBACK`, so each transaction runs on its own copy of the
+// session and other clones of the client stay outside it.
+
+use crate::client::FireboltClient;
+use crate::error::FireboltError;
+use crate::result::ResultSet;
+use crate::telemetry::trace_event;
+use crate::transport::BoxFuture;
+use std::sync::Mutex;
+
+// Rolls back when dropped without `commit` or `rollback`, if a Tokio runtime
+// is available to send the statement from.
+#[derive(Debug)]
+pub struct Transaction {
+    client: FireboltClient,
+    statements: Mutex<Vec<String>>,
+    finished: bool,
+}
+
+impl Transaction {
+    pub async fn query(&self, sql: &str) -> Result<ResultSet, FireboltError> {
+        self.statements
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(sql.to_string());
+        self.client.query(sql).await
+    }
+
+    // The statements run in the transaction so far, in order.
+    pub fn statements(&self) -> Vec<String> {
+        self.statements
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub async fn commit(mut self) -> Result<(), FireboltError> {
+        self.finished = true;
+        self.client.query_non_idempotent("COMMIT").await.map(|_| ())
+    }
+
+    pub async fn rollback(mut self) -> Result<(), FireboltError> {
+        self.finished = true;
+        self.client
+            .query_non_idempotent("ROLLBACK")
+            .await
+            .map(|_| ())
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        trace_event!(
+            warn,
+            statements = self.statements().len(),
+            "Transaction dropped without commit or rollback, rolling back"
+        );
+        #[cfg(feature = "tokio")]
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let client = self.client.clone();
+            runtime.spawn(async move { rollback_quietly(&client).await });
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+async fn rollback_quietly(client: &FireboltClient) {
+    if let Err(e) = client.query_non_idempotent("ROLLBACK").await {
+        trace_event!(warn, error = %e, "Failed to roll back transaction");
+    }
+}
+
+impl FireboltClient {
+    pub async fn begin(&self) -> Result<Transaction, FireboltError> {
+        self.connected().await?;
+        let client = self.detached();
+        client.query_non_idempotent("BEGIN TRANSACTION").await?;
+        Ok(Transaction {
+            client,
+            statements: Mutex::new(Vec::new()),
+            finished: false,
+        })
+    }
+
+    // Runs `f` in a transaction that is committed if it returns `Ok` and
+    // rolled back if it returns an error. The error from `f` is returned even
+    // if the rollback fails too.
+    pub async fn with_transaction<T, F>(&self, f: F) -> Result<T, FireboltError>
+    where
+        F: for<'t> FnOnce(&'t Transaction) -> BoxFuture<'t, Result<T, FireboltError>>,
+    {
+        let mut transaction = self.begin().await?;
+        match f(&transaction).await {
+            Ok(value) => {
+                transaction.commit().await?;
+                Ok(value)
+            }
+            Err(e) => {
+                transaction.finished = true;
+                rollback_quietly(&transaction.client).await;
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockFirebolt, MockResponse};
+
+    fn transaction_ids(mock: &MockFirebolt) -> Vec<(String, Option<String>)> {
+        mock.queries()
+            .into_iter()
+            .map(|query| (query.sql, query.parameters.get("transaction_id").cloned()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_transaction() {
+        let mock = MockFirebolt::new();
+        mock.on_query(
+            "BEGIN TRANSACTION",
+            MockResponse::empty().with_updated_parameters(&[("transaction_id", "42")]),
+        );
+        mock.on_query(
+            "COMMIT",
+            MockResponse::empty().with_removed_parameters(&["transaction_id"]),
+        );
+        let client = mock.connect().await.unwrap();
+        let before = mock.queries().len();
+
+        let transaction = client.begin().await.unwrap();
+        transaction.query("INSERT INTO t VALUES (1)").await.unwrap();
+        client.query("SELECT 1").await.unwrap();
+        assert_eq!(transaction.statements(), ["INSERT INTO t VALUES (1)"]);
+        transaction.commit().await.unwrap();
+
+        let id = Some("42".to_string());
+        assert_eq!(
+            transaction_ids(&mock)[before..],
+            [
+                ("BEGIN TRANSACTION".to_string(), None),
+                ("INSERT INTO t VALUES (1)".to_string(), id.clone()),
+                ("SELECT 1".to_string(), None),
+                ("COMMIT".to_string(), id),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rollback() {
+        let mock = MockFirebolt::new();
+        mock.on_query(
+            "INSERT INTO missing VALUES (1)",
+            MockResponse::error(400, "no such table"),
+        );
+        let client = mock.connect().await.unwrap();
+        let before = mock.queries().len();
+
+        let result = client
+            .with_transaction(|tx| {
+                Box::pin(async move {
+                    tx.query("INSERT INTO t VALUES (1)").await?;
+                    tx.query("INSERT INTO missing VALUES (1)").await
+                })
+            })
+            .await;
+        assert!(matches!(result, Err(FireboltError::Query(_))));
+
+        let rows = client
+            .with_transaction(|tx| Box::pin(async move { tx.query("SELECT 1").await }))
+            .await
+            .unwrap();
+        assert!(rows.rows.is_empty());
+
+        let statements: Vec<String> = mock.queries()[before..]
+            .iter()
+            .map(|query| query.sql.clone())
+            .collect();
+        assert_eq!(
+            statements,
+            [
+                "BEGIN TRANSACTION",
+                "INSERT INTO t VALUES (1)",
+                "INSERT INTO missing VALUES (1)",
+                "ROLLBACK",
+                "BEGIN TRANSACTION",
+                "SELECT 1",
+                "COMMIT",
+            ]
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_drop_rolls_back() {
+        let mock = MockFirebolt::new();
+        let client = mock.connect().await.unwrap();
+        let before = mock.queries().len();
+
+        drop(client.begin().await.unwrap());
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let statements: Vec<String> = mock.queries()[before..]
+            .iter()
+            .map(|query| query.sql.clone())
+            .collect();
+        assert_eq!(statements, ["BEGIN TRANSACTION", "ROLLBACK"]);
+    }
+}
@@ -0,0 +1,1244 @@
+use crate::error::FireboltError;
+use crate::hooks::Hooks;
+use crate::retry::{RetryCause, RetryEvent, RetryStrategy};
+#[cfg(feature = "reqwest")]
+use secrecy::ExposeSecret;
+use secrecy::SecretString;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+#[cfg(any(feature = "reqwest", feature = "hyper"))]
+use std::time::Instant;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use url::Url;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+    // Whether sending the request twice has the same effect as sending it
+    // once. Only idempotent requests are retried after a network error.
+    pub idempotent: bool,
+}
+
+impl HttpRequest {
+    pub fn new(method: Method, url: String) -> Self {
+        Self {
+            method,
+            url,
+            headers: Vec::new(),
+            body: None,
+            idempotent: method == Method::Get,
+        }
+    }
+
+    pub fn idempotent(mut self, idempotent: bool) -> Self {
+        self.idempotent = idempotent;
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    // Set by the built-in transports; `None` for responses from other transports.
+    pub timing: Option<TransferTiming>,
+}
+
+// Where the time of one HTTP exchange went, as measured by the client.
+// `time_to_first_byte` runs from sending the request until the response
+// headers arrive, and includes `dns` and `connect` when a new connection was
+// opened for the request. `dns` is only measured for lookups made by the
+// SDK's resolver, which the reqwest transport uses only when DNS options are
+// set. `download` is the time spent reading the body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransferTiming {
+    pub dns: Option<Duration>,
+    pub connect: Option<Duration>,
+    pub time_to_first_byte: Duration,
+    pub download: Duration,
+}
+
+impl HttpResponse {
+    pub fn new(status: u16, headers: Vec<(String, String)>, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            headers,
+            body,
+            timing: None,
+        }
+    }
+
+    pub fn with_timing(mut self, timing: TransferTiming) -> Self {
+        self.timing = Some(timing);
+        self
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn has_header(&self, name: &str) -> bool {
+        self.header(name).is_some()
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn is_server_error(&self) -> bool {
+        (500..600).contains(&self.status)
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+pub trait Transport: Send + Sync {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, FireboltError>>;
+
+    // Writes the body of a successful response to `sink` and returns the
+    // response with an empty body. Other responses are returned whole so their
+    // errors can be read. The default buffers the body before writing it;
+    // the built-in transports stream it as it arrives, waiting for `sink` to
+    // take each chunk before reading the next, so a slow sink slows the
+    // download instead of filling memory.
+    fn send_to<'a>(
+        &'a self,
+        request: HttpRequest,
+        sink: &'a mut (dyn AsyncWrite + Unpin + Send),
+    ) -> BoxFuture<'a, Result<HttpResponse, FireboltError>> {
+        Box::pin(async move {
+            let mut response = self.send(request).await?;
+            if response.is_success() {
+                sink.write_all(&response.body).await.map_err(sink_error)?;
+                response.body.clear();
+            }
+            Ok(response)
+        })
+    }
+}
+
+// Runs one exchange of a built-in transport, which returns the response with
+// the moment its headers arrived, and attaches the timing to the response.
+#[cfg(any(feature = "reqwest", feature = "hyper"))]
+async fn timed(
+    exchange: impl Future<Output = Result<(HttpResponse, Instant), FireboltError>>,
+) -> Result<HttpResponse, FireboltError> {
+    let started = Instant::now();
+    let (result, connection) = crate::timing::measure(exchange).await;
+    let (response, first_byte) = result?;
+    Ok(response.with_timing(TransferTiming {
+        dns: connection.dns,
+        connect: connection.connect,
+        time_to_first_byte: first_byte - started,
+        download: first_byte.elapsed(),
+    }))
+}
+
+fn sink_error(e: std::io::Error) -> FireboltError {
+    FireboltError::Serialization(format!("Failed to write response body: {e}"))
+}
+
+#[cfg(feature = "reqwest")]
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "reqwest")]
+impl Default for ReqwestTransport {
+    fn default() -> Self {
+        let client = client_builder()
+            .build()
+            .expect("Failed to build default HTTP client");
+        Self { client }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_client(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+
+    pub(crate) fn from_options(options: &HttpOptions) -> Result<Self, FireboltError> {
+        let mut builder = client_builder();
+        if let Some(connect_timeout) = options.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(proxy) = &options.proxy {
+            builder = builder.proxy(proxy.to_reqwest()?);
+        }
+        if !options.dns.is_default() {
+            builder = builder.dns_resolver(Arc::new(crate::dns::DnsResolver::new(&options.dns)));
+        }
+        let client = builder.build().map_err(|e| {
+            FireboltError::Configuration(format!("Failed to build HTTP client: {e}"))
+        })?;
+        Ok(Self { client })
+    }
+}
+
+// When both TLS features are enabled, the explicit rustls opt-in wins over the default.
+#[cfg(feature = "reqwest")]
+fn client_builder() -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder().connector_layer(crate::timing::TimedConnectorLayer);
+    #[cfg(feature = "rustls")]
+    let builder = builder.use_rustls_tls();
+    builder
+}
+
+// A slim transport built directly on hyper and rustls, for builds that leave
+// out reqwest. It trusts the bundled webpki roots and does not support proxies.
+#[cfg(feature = "hyper")]
+#[derive(Debug, Clone)]
+pub struct HyperTransport {
+    client: hyper_util::client::legacy::Client<
+        crate::timing::TimedConnector<
+            hyper_rustls::HttpsConnector<
+                hyper_util::client::legacy::connect::HttpConnector<crate::dns::DnsResolver>,
+            >,
+        >,
+        http_body_util::Full<hyper::body::Bytes>,
+    >,
+}
+
+#[cfg(feature = "hyper")]
+impl Default for HyperTransport {
+    fn default() -> Self {
+        Self::from_options(&HttpOptions::default()).expect("Failed to build default HTTP client")
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl HyperTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn from_options(options: &HttpOptions) -> Result<Self, FireboltError> {
+        use hyper_rustls::ConfigBuilderExt;
+
+        if options.proxy.is_some() {
+            return Err(FireboltError::Configuration(
+                "Proxies are only supported by the reqwest transport".to_string(),
+            ));
+        }
+        let tls = rustls::ClientConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| FireboltError::Configuration(format!("Failed to configure TLS: {e}")))?
+        .with_webpki_roots()
+        .with_no_client_auth();
+
+        let mut http = hyper_util::client::legacy::connect::HttpConnector::new_with_resolver(
+            crate::dns::DnsResolver::new(&options.dns),
+        );
+        http.enforce_http(false);
+        http.set_connect_timeout(options.connect_timeout);
+        let connector = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_tls_config(tls)
+            .https_or_http()
+            .enable_http1()
+            .enable_http2()
+            .wrap_connector(http);
+        let client =
+            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
+                .build(crate::timing::TimedConnector(connector));
+        Ok(Self { client })
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl HyperTransport {
+    async fn start(
+        &self,
+        request: HttpRequest,
+    ) -> Result<(hyper::body::Incoming, u16, Vec<(String, String)>), FireboltError> {
+        let method = match request.method {
+            Method::Get => hyper::Method::GET,
+            Method::Post => hyper::Method::POST,
+        };
+        let mut builder = hyper::Request::builder().method(method).uri(&request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        let body = http_body_util::Full::new(request.body.unwrap_or_default().into());
+        let request = builder
+            .body(body)
+            .map_err(|e| FireboltError::Network(format!("Invalid request: {e}")))?;
+
+        let response = self
+            .client
+            .request(request)
+            .await
+            .map_err(|e| FireboltError::Network(e.to_string()))?;
+
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect();
+        Ok((response.into_body(), status, headers))
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl Transport for HyperTransport {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+        use http_body_util::BodyExt;
+
+        Box::pin(timed(async move {
+            let (body, status, headers) = self.start(request).await?;
+            let first_byte = Instant::now();
+            let body = body.collect().await.map_err(hyper_body_error)?;
+
+            Ok((
+                HttpResponse::new(status, headers, body.to_bytes().to_vec()),
+                first_byte,
+            ))
+        }))
+    }
+
+    fn send_to<'a>(
+        &'a self,
+        request: HttpRequest,
+        sink: &'a mut (dyn AsyncWrite + Unpin + Send),
+    ) -> BoxFuture<'a, Result<HttpResponse, FireboltError>> {
+        use http_body_util::BodyExt;
+
+        Box::pin(timed(async move {
+            let (mut body, status, headers) = self.start(request).await?;
+            let first_byte = Instant::now();
+            if !(200..300).contains(&status) {
+                let body = body.collect().await.map_err(hyper_body_error)?;
+                return Ok((
+                    HttpResponse::new(status, headers, body.to_bytes().to_vec()),
+                    first_byte,
+                ));
+            }
+            while let Some(frame) = body.frame().await {
+                if let Some(data) = frame.map_err(hyper_body_error)?.data_ref() {
+                    sink.write_all(data).await.map_err(sink_error)?;
+                }
+            }
+
+            Ok((HttpResponse::new(status, headers, Vec::new()), first_byte))
+        }))
+    }
+}
+
+#[cfg(feature = "hyper")]
+fn hyper_body_error(e: hyper::Error) -> FireboltError {
+    FireboltError::Network(format!("Failed to read response body: {e}"))
+}
+
+// Builds the transport used when none is passed to the client builder.
+// reqwest is preferred when both built-in transports are enabled.
+#[cfg(feature = "reqwest")]
+pub(crate) fn default_transport(
+    options: &HttpOptions,
+) -> Result<Arc<dyn Transport>, FireboltError> {
+    Ok(Arc::new(ReqwestTransport::from_options(options)?))
+}
+
+#[cfg(all(feature = "hyper", not(feature = "reqwest")))]
+pub(crate) fn default_transport(
+    options: &HttpOptions,
+) -> Result<Arc<dyn Transport>, FireboltError> {
+    Ok(Arc::new(HyperTransport::from_options(options)?))
+}
+
+#[cfg(not(any(feature = "reqwest", feature = "hyper")))]
+pub(crate) fn default_transport(
+    _options: &HttpOptions,
+) -> Result<Arc<dyn Transport>, FireboltError> {
+    Err(FireboltError::Configuration(
+        "No HTTP transport configured: enable the `reqwest` or `hyper` feature or pass one with `with_transport`"
+            .to_string(),
+    ))
+}
+
+// The checks `default_transport` makes on the options, without building a
+// client.
+#[cfg(feature = "reqwest")]
+pub(crate) fn validate_options(options: &HttpOptions) -> Result<(), FireboltError> {
+    match &options.proxy {
+        Some(proxy) => proxy.to_reqwest().map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+#[cfg(all(feature = "hyper", not(feature = "reqwest")))]
+pub(crate) fn validate_options(options: &HttpOptions) -> Result<(), FireboltError> {
+    match &options.proxy {
+        Some(_) => Err(FireboltError::Configuration(
+            "Proxies are only supported by the reqwest transport".to_string(),
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(any(feature = "reqwest", feature = "hyper")))]
+pub(crate) fn validate_options(options: &HttpOptions) -> Result<(), FireboltError> {
+    default_transport(options).map(|_| ())
+}
+
+#[cfg_attr(not(any(feature = "reqwest", feature = "hyper")), allow(dead_code))]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HttpOptions {
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) proxy: Option<ProxyOptions>,
+    pub(crate) dns: crate::dns::DnsOptions,
+}
+
+#[cfg_attr(not(feature = "reqwest"), allow(dead_code))]
+#[derive(Clone)]
+pub(crate) struct ProxyOptions {
+    pub(crate) url: String,
+    pub(crate) credentials: Option<(String, SecretString)>,
+}
+
+impl std::fmt::Debug for ProxyOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyOptions")
+            .field("url", &self.url)
+            .field(
+                "credentials",
+                &self
+                    .credentials
+                    .as_ref()
+                    .map(|(username, _)| (username, crate::telemetry::REDACTED)),
+            )
+            .finish()
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl ProxyOptions {
+    fn to_reqwest(&self) -> Result<reqwest::Proxy, FireboltError> {
+        let invalid = |e: &dyn std::fmt::Display| {
+            FireboltError::Configuration(format!("Invalid proxy URL: {e}"))
+        };
+
+        let mut url = url::Url::parse(&self.url).map_err(|e| invalid(&e))?;
+        if !["http", "https", "socks5", "socks5h"].contains(&url.scheme()) {
+            return Err(FireboltError::Configuration(format!(
+                "Unsupported proxy scheme '{}', expected http, https, socks5 or socks5h",
+                url.scheme()
+            )));
+        }
+
+        match &self.credentials {
+            // SOCKS proxies take credentials from the URL rather than a header.
+            Some((username, password)) if url.scheme().starts_with("socks") => {
+                url.set_username(username)
+                    .map_err(|_| invalid(&"cannot set username"))?;
+                url.set_password(Some(password.expose_secret()))
+                    .map_err(|_| invalid(&"cannot set password"))?;
+                reqwest::Proxy::all(url.as_str()).map_err(|e| invalid(&e))
+            }
+            Some((username, password)) => Ok(reqwest::Proxy::all(url.as_str())
+                .map_err(|e| invalid(&e))?
+                .basic_auth(username, password.expose_secret())),
+            None => reqwest::Proxy::all(url.as_str()).map_err(|e| invalid(&e)),
+        }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestTransport {
+    async fn start(
+        &self,
+        request: HttpRequest,
+    ) -> Result<(reqwest::Response, u16, Vec<(String, String)>), FireboltError> {
+        let mut builder = match request.method {
+            Method::Get => self.client.get(&request.url),
+            Method::Post => self.client.post(&request.url),
+        };
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+
+        let response = builder.send().await.map_err(reqwest_error)?;
+
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect();
+        Ok((response, status, headers))
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl Transport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+        Box::pin(timed(async move {
+            let (response, status, headers) = self.start(request).await?;
+            let first_byte = Instant::now();
+            let body = response.bytes().await.map_err(body_error)?;
+
+            Ok((
+                HttpResponse::new(status, headers, body.to_vec()),
+                first_byte,
+            ))
+        }))
+    }
+
+    fn send_to<'a>(
+        &'a self,
+        request: HttpRequest,
+        sink: &'a mut (dyn AsyncWrite + Unpin + Send),
+    ) -> BoxFuture<'a, Result<HttpResponse, FireboltError>> {
+        Box::pin(timed(async move {
+            let (mut response, status, headers) = self.start(request).await?;
+            let first_byte = Instant::now();
+            if !response.status().is_success() {
+                let body = response.bytes().await.map_err(body_error)?;
+                return Ok((
+                    HttpResponse::new(status, headers, body.to_vec()),
+                    first_byte,
+                ));
+            }
+            while let Some(chunk) = response.chunk().await.map_err(body_error)? {
+                sink.write_all(&chunk).await.map_err(sink_error)?;
+            }
+
+            Ok((HttpResponse::new(status, headers, Vec::new()), first_byte))
+        }))
+    }
+}
+
+#[cfg(feature = "reqwest")]
+fn body_error(e: reqwest::Error) -> FireboltError {
+    match reqwest_error(e) {
+        FireboltError::Network(msg) => {
+            FireboltError::Network(format!("Failed to read response body: {msg}"))
+        }
+        other => other,
+    }
+}
+
+#[cfg(feature = "reqwest")]
+fn reqwest_error(e: reqwest::Error) -> FireboltError {
+    if e.is_timeout() {
+        FireboltError::Timeout(e.to_string())
+    } else {
+        FireboltError::Network(e.to_string())
+    }
+}
+
+pub(crate) struct TimeoutTransport {
+    inner: Arc<dyn Transport>,
+    timeout: Duration,
+}
+
+impl TimeoutTransport {
+    pub(crate) fn new(inner: Arc<dyn Transport>, timeout: Duration) -> Self {
+        Self { inner, timeout }
+    }
+}
+
+impl TimeoutTransport {
+    async fn with_timeout(
+        &self,
+        url: String,
+        future: BoxFuture<'_, Result<HttpResponse, FireboltError>>,
+    ) -> Result<HttpResponse, FireboltError> {
+        crate::runtime::timeout(self.timeout, future)
+            .await
+            .map_err(|_| {
+                FireboltError::Timeout(format!(
+                    "Request to {url} did not complete within {:?}",
+                    self.timeout
+                ))
+            })?
+    }
+}
+
+impl Transport for TimeoutTransport {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+        let url = request.url.clone();
+        Box::pin(self.with_timeout(url, self.inner.send(request)))
+    }
+
+    fn send_to<'a>(
+        &'a self,
+        request: HttpRequest,
+        sink: &'a mut (dyn AsyncWrite + Unpin + Send),
+    ) -> BoxFuture<'a, Result<HttpResponse, FireboltError>> {
+        let url = request.url.clone();
+        Box::pin(self.with_timeout(url, self.inner.send_to(request, sink)))
+    }
+}
+
+// Sends requests for aliased hosts to another base URL, such as a VPC
+// endpoint or reverse proxy, keeping the path and query. A base URL with a
+// path is used as a prefix. The request goes to the alias host, so it is the
+// one sent in the `Host` header and for TLS server name indication.
+pub(crate) struct AliasTransport {
+    inner: Arc<dyn Transport>,
+    aliases: HashMap<String, Url>,
+}
+
+impl AliasTransport {
+    pub(crate) fn new(inner: Arc<dyn Transport>, aliases: &HashMap<String, String>) -> Self {
+        let aliases = aliases
+            .iter()
+            .filter_map(|(host, target)| {
+                let target = Url::parse(target).ok()?;
+                Some((host.to_ascii_lowercase(), target))
+            })
+            .collect();
+        Self { inner, aliases }
+    }
+
+    fn rewrite(&self, mut request: HttpRequest) -> HttpRequest {
+        let Ok(url) = Url::parse(&request.url) else {
+            return request;
+        };
+        let Some(target) = url.host_str().and_then(|host| self.aliases.get(host)) else {
+            return request;
+        };
+        let mut rewritten = target.clone();
+        rewritten.set_path(&format!(
+            "{}{}",
+            target.path().trim_end_matches('/'),
+            url.path()
+        ));
+        rewritten.set_query(url.query());
+        request.url = rewritten.to_string();
+        request
+    }
+}
+
+impl Transport for AliasTransport {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+        self.inner.send(self.rewrite(request))
+    }
+
+    fn send_to<'a>(
+        &'a self,
+        request: HttpRequest,
+        sink: &'a mut (dyn AsyncWrite + Unpin + Send),
+    ) -> BoxFuture<'a, Result<HttpResponse, FireboltError>> {
+        self.inner.send_to(self.rewrite(request), sink)
+    }
+}
+
+// A host alias maps a host name to an http or https base URL without a query
+// or fragment.
+pub(crate) fn validate_alias(host: &str, target: &str) -> Result<(), FireboltError> {
+    if host.is_empty() || host.contains(['/', ':', '@']) {
+        return Err(FireboltError::Configuration(format!(
+            "Invalid host alias '{host}': it must be a host name without a scheme or port"
+        )));
+    }
+    let valid = Url::parse(target).is_ok_and(|url| {
+        matches!(url.scheme(), "http" | "https")
+            && url.host_str().is_some_and(|host| !host.is_empty())
+            && url.query().is_none()
+            && url.fragment().is_none()
+    });
+    if !valid {
+        return Err(FireboltError::Configuration(format!(
+            "Invalid target '{target}' for host alias '{host}': it must be an http or https URL without a query or fragment"
+        )));
+    }
+    Ok(())
+}
+
+// Retries responses that signal the server is temporarily unable to handle
+// the request; those are safe to resend because the request was not processed.
+// Network errors are retried only for idempotent requests, since the server
+// may have executed the request before the connection failed.
+pub(crate) struct RetryTransport {
+    inner: Arc<dyn Transport>,
+    strategy: Arc<dyn RetryStrategy>,
+    hooks: Hooks,
+}
+
+impl RetryTransport {
+    pub(crate) fn new(
+        inner: Arc<dyn Transport>,
+        strategy: Arc<dyn RetryStrategy>,
+        hooks: Hooks,
+    ) -> Self {
+        Self {
+            inner,
+            strategy,
+            hooks,
+        }
+    }
+}
+
+impl RetryTransport {
+    // A network error is only retried for a streamed body if nothing has been
+    // written to the sink yet.
+    async fn send_with_retries(
+        &self,
+        request: HttpRequest,
+        sink: Option<&mut CountingWriter<'_>>,
+    ) -> Result<HttpResponse, FireboltError> {
+        let mut attempt = 0;
+        let result = self.send_retrying(request, sink, &mut attempt).await;
+        crate::retry::emit_outcome(attempt, result.as_ref().is_ok_and(HttpResponse::is_success));
+        result
+    }
+
+    async fn send_retrying(
+        &self,
+        request: HttpRequest,
+        mut sink: Option<&mut CountingWriter<'_>>,
+        attempt: &mut u32,
+    ) -> Result<HttpResponse, FireboltError> {
+        loop {
+            let sent = match sink.as_deref_mut() {
+                Some(sink) => self.inner.send_to(request.clone(), sink).await,
+                None => self.inner.send(request.clone()).await,
+            };
+            let response = match sent {
+                Err(FireboltError::Network(message))
+                    if request.idempotent && sink.as_ref().is_none_or(|sink| sink.written == 0) =>
+                {
+                    let error = FireboltError::Network(message.clone());
+                    let Some(delay) = self.strategy.next_delay(*attempt + 1, &error) else {
+                        return Err(error);
+                    };
+                    *attempt += 1;
+                    self.hooks.retry(RetryEvent::new(
+                        *attempt,
+                        RetryCause::Network(message),
+                        delay,
+                    ));
+                    crate::runtime::sleep(delay).await;
+                    continue;
+                }
+                result => result?,
+            };
+            if !matches!(response.status, 429 | 503) {
+                return Ok(response);
+            }
+            let error = crate::parser::parse_error_response(response.status, response.text());
+            let retry_after = response
+                .header("Retry-After")
+                .and_then(|value| value.trim().parse().ok())
+                .map(Duration::from_secs);
+            let next = match retry_after {
+                Some(retry_after) => {
+                    self.strategy
+                        .next_delay_after(*attempt + 1, &error, retry_after)
+                }
+                None => self.strategy.next_delay(*attempt + 1, &error),
+            };
+            let Some(delay) = next else {
+                return Ok(response);
+            };
+            *attempt += 1;
+            self.hooks.retry(RetryEvent::new(
+                *attempt,
+                RetryCause::Status(response.status),
+                delay,
+            ));
+            crate::runtime::sleep(delay).await;
+        }
+    }
+}
+
+impl Transport for RetryTransport {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+        Box::pin(self.send_with_retries(request, None))
+    }
+
+    fn send_to<'a>(
+        &'a self,
+        request: HttpRequest,
+        sink: &'a mut (dyn AsyncWrite + Unpin + Send),
+    ) -> BoxFuture<'a, Result<HttpResponse, FireboltError>> {
+        Box::pin(async move {
+            let mut sink = CountingWriter::new(sink);
+            self.send_with_retries(request, Some(&mut sink)).await
+        })
+    }
+}
+
+pub(crate) struct CountingWriter<'a> {
+    inner: &'a mut (dyn AsyncWrite + Unpin + Send),
+    pub(crate) written: u64,
+}
+
+impl<'a> CountingWriter<'a> {
+    pub(crate) fn new(inner: &'a mut (dyn AsyncWrite + Unpin + Send)) -> Self {
+        Self { inner, written: 0 }
+    }
+}
+
+impl AsyncWrite for CountingWriter<'_> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let polled = Pin::new(&mut *self.inner).poll_write(cx, buf);
+        if let std::task::Poll::Ready(Ok(written)) = polled {
+            self.written += written as u64;
+        }
+        polled
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        Pin::new(&mut *self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        Pin::new(&mut *self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retry::RetryPolicy;
+
+    #[test]
+    fn test_response_header_lookup_is_case_insensitive() {
+        let response = HttpResponse::new(
+            200,
+            vec![("firebolt-reset-session".to_string(), "".to_string())],
+            b"ok".to_vec(),
+        );
+        assert!(response.has_header("Firebolt-Reset-Session"));
+        assert_eq!(response.header("FIREBOLT-RESET-SESSION"), Some(""));
+        assert_eq!(response.header("Firebolt-Update-Endpoint"), None);
+        assert!(response.is_success());
+        assert!(!response.is_server_error());
+        assert_eq!(response.text(), "ok");
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_reqwest_transport_send() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/path")
+            .match_header("x-test", "1")
+            .match_body("payload")
+            .with_status(201)
+            .with_header("x-reply", "yes")
+            .with_body("done")
+            .create_async()
+            .await;
+
+        let request = HttpRequest::new(Method::Post, format!("{}/path", server.url()))
+            .header("X-Test", "1")
+            .body("payload");
+        let response = ReqwestTransport::new().send(request).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(response.status, 201);
+        assert_eq!(response.header("X-Reply"), Some("yes"));
+        assert_eq!(response.text(), "done");
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_transfer_timing() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/")
+            .with_body("ok")
+            .create_async()
+            .await;
+
+        let timing = ReqwestTransport::new()
+            .send(HttpRequest::new(Method::Post, server.url()))
+            .await
+            .unwrap()
+            .timing
+            .unwrap();
+        assert!(timing.time_to_first_byte >= timing.connect.unwrap());
+        assert_eq!(timing.dns, None);
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_send_to_waits_for_sink() {
+        use tokio::io::AsyncReadExt;
+
+        let body = vec![b'x'; 4 << 20];
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/")
+            .with_body(&body)
+            .create_async()
+            .await;
+
+        let (mut sink, mut reader) = tokio::io::duplex(1024);
+        let transport = ReqwestTransport::new();
+        let mut sending =
+            transport.send_to(HttpRequest::new(Method::Post, server.url()), &mut sink);
+        // The sink holds 1 KiB, so the body cannot be read to the end until
+        // something drains it.
+        assert!(
+            tokio::time::timeout(Duration::from_millis(200), &mut sending)
+                .await
+                .is_err()
+        );
+
+        let mut received = Vec::new();
+        let (response, _) = tokio::join!(sending, async {
+            let mut chunk = [0; 8192];
+            while received.len() < body.len() {
+                let read = reader.read(&mut chunk).await.unwrap();
+                received.extend_from_slice(&chunk[..read]);
+            }
+        });
+        assert!(response.unwrap().body.is_empty());
+        assert_eq!(received.len(), body.len());
+    }
+
+    struct SlowTransport;
+
+    impl Transport for SlowTransport {
+        fn send(
+            &self,
+            _request: HttpRequest,
+        ) -> BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(HttpResponse::new(200, Vec::new(), Vec::new()))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout_transport() {
+        let transport = TimeoutTransport::new(Arc::new(SlowTransport), Duration::from_millis(10));
+        let result = transport
+            .send(HttpRequest::new(
+                Method::Get,
+                "http://localhost/".to_string(),
+            ))
+            .await;
+        assert!(matches!(result, Err(FireboltError::Timeout(_))));
+    }
+
+    struct BusyTransport {
+        calls: std::sync::atomic::AtomicU32,
+        busy_responses: u32,
+    }
+
+    impl Transport for BusyTransport {
+        fn send(
+            &self,
+            _request: HttpRequest,
+        ) -> BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+            let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let status = if call < self.busy_responses { 503 } else { 200 };
+            Box::pin(async move { Ok(HttpResponse::new(status, Vec::new(), Vec::new())) })
+        }
+    }
+
+    struct EchoTransport;
+
+    impl Transport for EchoTransport {
+        fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+            Box::pin(async move { Ok(HttpResponse::new(200, Vec::new(), request.url.into())) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_alias_transport() {
+        let aliases = HashMap::from([
+            (
+                "engine.firebolt.io".to_string(),
+                "https://proxy.internal:8443/firebolt/".to_string(),
+            ),
+            ("api.firebolt.io".to_string(), "http://10.0.0.7".to_string()),
+        ]);
+        let transport = AliasTransport::new(Arc::new(EchoTransport), &aliases);
+        for (url, expected) in [
+            (
+                "https://ENGINE.firebolt.io/?database=db",
+                "https://proxy.internal:8443/firebolt/?database=db",
+            ),
+            (
+                "https://api.firebolt.io/web/v3/account/a/engineUrl",
+                "http://10.0.0.7/web/v3/account/a/engineUrl",
+            ),
+            ("https://other.firebolt.io/", "https://other.firebolt.io/"),
+        ] {
+            let response = transport
+                .send(HttpRequest::new(Method::Post, url.to_string()))
+                .await
+                .unwrap();
+            assert_eq!(response.text(), expected);
+        }
+
+        assert!(validate_alias("engine.firebolt.io", "https://proxy.internal:8443").is_ok());
+        assert!(validate_alias("engine.firebolt.io:443", "https://proxy.internal").is_err());
+        assert!(validate_alias("engine.firebolt.io", "proxy.internal").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_retry_transport_retries_busy_responses() {
+        let policy = RetryPolicy::new()
+            .with_max_retries(2)
+            .with_initial_backoff(Duration::from_millis(1));
+        let request = HttpRequest::new(Method::Get, "http://localhost/".to_string());
+
+        let inner = Arc::new(BusyTransport {
+            calls: Default::default(),
+            busy_responses: 2,
+        });
+        let response = RetryTransport::new(inner.clone(), Arc::new(policy), Hooks::default())
+            .send(request.clone())
+            .await
+            .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(inner.calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+        let inner = Arc::new(BusyTransport {
+            calls: Default::default(),
+            busy_responses: 5,
+        });
+        let response = RetryTransport::new(inner.clone(), Arc::new(policy), Hooks::default())
+            .send(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status, 503);
+        assert_eq!(inner.calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    // Retries busy responses without waiting, but never network errors.
+    struct BusyOnly;
+
+    impl RetryStrategy for BusyOnly {
+        fn next_delay(&self, attempt: u32, error: &FireboltError) -> Option<Duration> {
+            (attempt <= 4 && matches!(error, FireboltError::Unavailable(_)))
+                .then_some(Duration::ZERO)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_transport_uses_strategy() {
+        let request = HttpRequest::new(Method::Get, "http://localhost/".to_string());
+
+        let inner = Arc::new(BusyTransport {
+            calls: Default::default(),
+            busy_responses: 4,
+        });
+        let response = RetryTransport::new(inner.clone(), Arc::new(BusyOnly), Hooks::default())
+            .send(request.clone())
+            .await
+            .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(inner.calls.load(std::sync::atomic::Ordering::SeqCst), 5);
+
+        let inner = Arc::new(FlakyTransport {
+            calls: Default::default(),
+        });
+        let result = RetryTransport::new(inner.clone(), Arc::new(BusyOnly), Hooks::default())
+            .send(request)
+            .await;
+        assert!(matches!(result, Err(FireboltError::Network(_))));
+        assert_eq!(inner.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    struct FlakyTransport {
+        calls: std::sync::atomic::AtomicU32,
+    }
+
+    impl Transport for FlakyTransport {
+        fn send(
+            &self,
+            _request: HttpRequest,
+        ) -> BoxFuture<'_, Result<HttpResponse, FireboltError>> {
+            let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Box::pin(async move {
+                if call == 0 {
+                    Err(FireboltError::Network("connection reset".to_string()))
+                } else {
+                    Ok(HttpResponse::new(200, Vec::new(), Vec::new()))
+                }
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_transport_retries_idempotent_network_errors() {
+        let policy = RetryPolicy::new().with_initial_backoff(Duration::from_millis(1));
+        let send = |request: HttpRequest| async {
+            let inner = Arc::new(FlakyTransport {
+                calls: Default::default(),
+            });
+            let result = RetryTransport::new(inner.clone(), Arc::new(policy), Hooks::default())
+                .send(request)
+                .await;
+            (
+                result,
+                inner.calls.load(std::sync::atomic::Ordering::SeqCst),
+            )
+        };
+
+        let get = HttpRequest::new(Method::Get, "http://localhost/".to_string());
+        let (result, calls) = send(get).await;
+        assert_eq!(result.unwrap().status, 200);
+        assert_eq!(calls, 2);
+
+        let insert = HttpRequest::new(Method::Post, "http://localhost/".to_string());
+        let (result, calls) = send(insert).await;
+        assert!(matches!(result, Err(FireboltError::Network(_))));
+        assert_eq!(calls, 1);
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_proxy_options() {
+        let proxy = |url: &str| HttpOptions {
+            proxy: Some(ProxyOptions {
+                url: url.to_string(),
+                credentials: Some(("user".to_string(), SecretString::from("pass"))),
+            }),
+            ..HttpOptions::default()
+        };
+
+        assert!(ReqwestTransport::from_options(&proxy("http://proxy.local:3128")).is_ok());
+        assert!(ReqwestTransport::from_options(&proxy("socks5://proxy.local:1080")).is_ok());
+        assert!(matches!(
+            ReqwestTransport::from_options(&proxy("ftp://proxy.local")),
+            Err(FireboltError::Configuration(_))
+        ));
+        assert!(matches!(
+            ReqwestTransport::from_options(&proxy("not a url")),
+            Err(FireboltError::Configuration(_))
+        ));
+        assert!(!format!("{:?}", proxy("http://proxy.local").proxy).contains("pass"));
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_requests_go_through_proxy() {
+        let mut proxy = mockito::Server::new_async().await;
+        let mock = proxy
+            .mock("GET", mockito::Matcher::Any)
+            .match_header("proxy-authorization", "Basic dXNlcjpwYXNz")
+            .with_status(200)
+            .with_body("proxied")
+            .create_async()
+            .await;
+
+        let options = HttpOptions {
+            proxy: Some(ProxyOptions {
+                url: proxy.url(),
+                credentials: Some(("user".to_string(), SecretString::from("pass"))),
+            }),
+            ..HttpOptions::default()
+        };
+        let response = ReqwestTransport::from_options(&options)
+            .unwrap()
+            .send(HttpRequest::new(
+                Method::Get,
+                "http://api.test.firebolt.io/web/v3/account/a/engineUrl".to_string(),
+            ))
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(response.text(), "proxied");
+    }
+
+    #[cfg(feature = "hyper")]
+    #[tokio::test]
+    async fn test_hyper_transport_send() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/path")
+            .match_header("x-test", "1")
+            .match_body("payload")
+            .with_status(201)
+            .with_header("x-reply", "yes")
+            .with_body("done")
+            .create_async()
+            .await;
+
+        let request = HttpRequest::new(Method::Post, format!("{}/path", server.url()))
+            .header("X-Test", "1")
+            .body("payload");
+        let response = HyperTransport::new().send(request).await.unwrap();
+
+        mock.assert_async().await;
+        assert!(response.timing.unwrap().connect.is_some());
+        assert_eq!(response.status, 201);
+        assert_eq!(response.header("X-Reply"), Some("yes"));
+        assert_eq!(response.text(), "done");
+
+        let options = HttpOptions {
+            proxy: Some(ProxyOptions {
+                url: "http://proxy.local:3128".to_string(),
+                credentials: None,
+            }),
+            ..HttpOptions::default()
+        };
+        assert!(matches!(
+            HyperTransport::from_options(&options),
+            Err(FireboltError::Configuration(_))
+        ));
+    }
+}
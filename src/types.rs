@@ -16,6 +16,90 @@ pub enum Type {
     Struct,
     Geography,
     Bytes,
+    Json,
+    Interval,
+}
+
+// The Firebolt SQL name of the type. `Type` does not carry decimal precision
+// and scale or nullability; use `Column`'s `Display` to render those.
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(crate::sql::sql_type_name(self))
+    }
+}
+
+impl Type {
+    // Parses Firebolt type syntax such as `bigint`, `decimal(38, 30)` or
+    // `text null`, keeping only the type. Use `Column::from_sql_type` to keep
+    // precision, scale and nullability as well.
+    pub fn from_sql_str(type_str: &str) -> Result<Self, crate::error::FireboltError> {
+        crate::parser::parse_sql_type(type_str).map(|(r#type, ..)| r#type)
+    }
+}
+
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+pub(crate) fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// Text forms of bytea values: `\x` followed by hex digits, or the escape
+// format where `\\` is a backslash and `\ooo` an octal byte. Anything else is
+// taken as the raw bytes of the text.
+pub(crate) fn decode_bytea(text: &str) -> Result<Vec<u8>, crate::error::FireboltError> {
+    if let Some(hex) = text.strip_prefix("\\x") {
+        return decode_hex(hex).ok_or_else(|| {
+            crate::error::FireboltError::Serialization("Failed to decode hex string".to_string())
+        });
+    }
+    if text.contains('\\') {
+        if let Some(bytes) = decode_escaped_octets(text) {
+            return Ok(bytes);
+        }
+    }
+    Ok(text.as_bytes().to_vec())
+}
+
+fn decode_escaped_octets(text: &str) -> Option<Vec<u8>> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'\\' {
+            decoded.push(bytes[i]);
+            i += 1;
+        } else if bytes.get(i + 1) == Some(&b'\\') {
+            decoded.push(b'\\');
+            i += 2;
+        } else {
+            let octal = std::str::from_utf8(bytes.get(i + 1..i + 4)?).ok()?;
+            if !octal.bytes().all(|b| (b'0'..=b'7').contains(&b)) {
+                return None;
+            }
+            decoded.push(u8::from_str_radix(octal, 8).ok()?);
+            i += 4;
+        }
+    }
+    Some(decoded)
+}
+
+// How `bytea` values are encoded in query results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BytesEncoding {
+    // `\x` hex, the escape format, or otherwise the raw text, told apart by
+    // their shape.
+    #[default]
+    Auto,
+    // Base64, which cannot be told apart from raw text and so must be chosen.
+    Base64,
 }
 
 pub trait TypeConversion {
@@ -25,6 +109,19 @@ pub trait TypeConversion {
     ) -> Result<Self, crate::error::FireboltError>
     where
         Self: Sized;
+
+    // Converts a value read from `column`, whose precision and scale are
+    // known, under the given conversion mode. Used by `Row::get` and friends.
+    fn convert_from_column(
+        value: &serde_json::Value,
+        column: &Column,
+        mode: ConversionMode,
+    ) -> Result<Self, crate::error::FireboltError>
+    where
+        Self: Sized,
+    {
+        convert_value(value, &column.r#type, mode)
+    }
 }
 
 impl TypeConversion for i32 {
@@ -82,13 +179,70 @@ impl TypeConversion for Option<i32> {
     }
 }
 
+// `bigint` values may arrive as JSON strings to preserve precision.
+fn json_to_i64(value: &serde_json::Value) -> Option<i64> {
+    value
+        .as_i64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+impl TypeConversion for i64 {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        match column_type {
+            Type::Int | Type::Long => {
+                if value.is_null() {
+                    return Err(crate::error::FireboltError::Serialization(
+                        "Cannot convert null to non-nullable type".to_string(),
+                    ));
+                }
+                json_to_i64(value).ok_or_else(|| {
+                    crate::error::FireboltError::Serialization(
+                        "Failed to convert to i64".to_string(),
+                    )
+                })
+            }
+            _ => Err(crate::error::FireboltError::Serialization(format!(
+                "Cannot convert {column_type:?} to i64"
+            ))),
+        }
+    }
+}
+
+impl TypeConversion for Option<i64> {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        match column_type {
+            Type::Int | Type::Long => {
+                let val = json_to_i64(value).ok_or_else(|| {
+                    crate::error::FireboltError::Serialization(
+                        "Failed to convert to i64".to_string(),
+                    )
+                })?;
+                Ok(Some(val))
+            }
+            _ => Err(crate::error::FireboltError::Serialization(format!(
+                "Cannot convert {column_type:?} to Option<i64>"
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "bigint")]
 impl TypeConversion for num_bigint::BigInt {
     fn convert_from_json(
         value: &serde_json::Value,
         column_type: &Type,
     ) -> Result<Self, crate::error::FireboltError> {
         match column_type {
-            Type::Long => {
+            Type::Long | Type::Int => {
                 if value.is_null() {
                     return Err(crate::error::FireboltError::Serialization(
                         "Cannot convert null to non-nullable type".to_string(),
@@ -115,6 +269,7 @@ impl TypeConversion for num_bigint::BigInt {
     }
 }
 
+#[cfg(feature = "bigint")]
 impl TypeConversion for Option<num_bigint::BigInt> {
     fn convert_from_json(
         value: &serde_json::Value,
@@ -124,7 +279,7 @@ impl TypeConversion for Option<num_bigint::BigInt> {
             return Ok(None);
         }
         match column_type {
-            Type::Long => {
+            Type::Long | Type::Int => {
                 let val = if let Some(v) = value.as_i64() {
                     num_bigint::BigInt::from(v)
                 } else if let Some(s) = value.as_str() {
@@ -202,7 +357,8 @@ impl TypeConversion for f64 {
         column_type: &Type,
     ) -> Result<Self, crate::error::FireboltError> {
         match column_type {
-            Type::Double => {
+            // Every `float` and `int` value is exactly representable as f64.
+            Type::Double | Type::Float | Type::Int => {
                 if value.is_null() {
                     return Err(crate::error::FireboltError::Serialization(
                         "Cannot convert null to non-nullable type".to_string(),
@@ -230,7 +386,7 @@ impl TypeConversion for Option<f64> {
             return Ok(None);
         }
         match column_type {
-            Type::Double => {
+            Type::Double | Type::Float | Type::Int => {
                 let val = value.as_f64().ok_or_else(|| {
                     crate::error::FireboltError::Serialization(
                         "Failed to convert to f64".to_string(),
@@ -245,6 +401,7 @@ impl TypeConversion for Option<f64> {
     }
 }
 
+#[cfg(feature = "decimal")]
 impl TypeConversion for rust_decimal::Decimal {
     fn convert_from_json(
         value: &serde_json::Value,
@@ -258,11 +415,7 @@ impl TypeConversion for rust_decimal::Decimal {
                     ));
                 }
                 if let Some(s) = value.as_str() {
-                    s.parse().map_err(|_| {
-                        crate::error::FireboltError::Serialization(
-                            "Failed to parse Decimal from string".to_string(),
-                        )
-                    })
+                    parse_decimal(s, false)
                 } else if let Some(f) = value.as_f64() {
                     rust_decimal::Decimal::try_from(f).map_err(|_| {
                         crate::error::FireboltError::Serialization(
@@ -280,8 +433,31 @@ impl TypeConversion for rust_decimal::Decimal {
             ))),
         }
     }
+
+    // Lenient mode rounds values with more fractional digits than Decimal's
+    // 28 instead of failing. The result is padded to the column's scale.
+    fn convert_from_column(
+        value: &serde_json::Value,
+        column: &Column,
+        mode: ConversionMode,
+    ) -> Result<Self, crate::error::FireboltError> {
+        let mut decimal = match (&column.r#type, value) {
+            (Type::Decimal, serde_json::Value::String(s)) if mode == ConversionMode::Lenient => {
+                parse_decimal(s, true)?
+            }
+            _ => convert_value(value, &column.r#type, mode)?,
+        };
+        if let (Type::Decimal, Some(scale)) = (&column.r#type, column.scale) {
+            let scale = u32::try_from(scale).unwrap_or(0).min(28);
+            if decimal.scale() < scale {
+                decimal.rescale(scale);
+            }
+        }
+        Ok(decimal)
+    }
 }
 
+#[cfg(feature = "decimal")]
 impl TypeConversion for Option<rust_decimal::Decimal> {
     fn convert_from_json(
         value: &serde_json::Value,
@@ -293,11 +469,7 @@ impl TypeConversion for Option<rust_decimal::Decimal> {
         match column_type {
             Type::Decimal => {
                 let val = if let Some(s) = value.as_str() {
-                    s.parse().map_err(|_| {
-                        crate::error::FireboltError::Serialization(
-                            "Failed to parse Decimal from string".to_string(),
-                        )
-                    })?
+                    parse_decimal(s, false)?
                 } else if let Some(f) = value.as_f64() {
                     rust_decimal::Decimal::try_from(f).map_err(|_| {
                         crate::error::FireboltError::Serialization(
@@ -316,6 +488,51 @@ impl TypeConversion for Option<rust_decimal::Decimal> {
             ))),
         }
     }
+
+    fn convert_from_column(
+        value: &serde_json::Value,
+        column: &Column,
+        mode: ConversionMode,
+    ) -> Result<Self, crate::error::FireboltError> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        rust_decimal::Decimal::convert_from_column(value, column, mode).map(Some)
+    }
+}
+
+// Decimal holds at most 28 fractional digits, fewer than Firebolt's
+// decimal(38, 30). Trailing zeros are dropped first since they lose nothing;
+// other digits past the limit are an error unless `round` is set.
+#[cfg(feature = "decimal")]
+fn parse_decimal(
+    text: &str,
+    round: bool,
+) -> Result<rust_decimal::Decimal, crate::error::FireboltError> {
+    use std::str::FromStr;
+
+    let trimmed = if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        text
+    };
+    if round {
+        return rust_decimal::Decimal::from_str(trimmed).map_err(|e| {
+            crate::error::FireboltError::Serialization(format!(
+                "Failed to parse Decimal from '{text}': {e}"
+            ))
+        });
+    }
+    rust_decimal::Decimal::from_str_exact(trimmed).map_err(|e| {
+        let message = match rust_decimal::Decimal::from_str(trimmed) {
+            Ok(_) => format!(
+                "Decimal value '{text}' has more fractional digits than Decimal can hold; \
+                 use ConversionMode::Lenient to round it"
+            ),
+            Err(_) => format!("Failed to parse Decimal from '{text}': {e}"),
+        };
+        crate::error::FireboltError::Serialization(message)
+    })
 }
 
 impl TypeConversion for String {
@@ -336,6 +553,14 @@ impl TypeConversion for String {
                     )
                 })
             }
+            Type::Decimal | Type::Json | Type::Interval => {
+                if value.is_null() {
+                    return Err(crate::error::FireboltError::Serialization(
+                        "Cannot convert null to non-nullable type".to_string(),
+                    ));
+                }
+                Ok(raw_text(value))
+            }
             _ => Err(crate::error::FireboltError::Serialization(format!(
                 "Cannot convert {column_type:?} to String"
             ))),
@@ -343,6 +568,14 @@ impl TypeConversion for String {
     }
 }
 
+// Decimals, intervals and JSON documents arrive as strings or as JSON values depending
+// on the output format; either way the text keeps what the server sent.
+fn raw_text(value: &serde_json::Value) -> String {
+    value
+        .as_str()
+        .map_or_else(|| value.to_string(), str::to_string)
+}
+
 impl TypeConversion for Option<String> {
     fn convert_from_json(
         value: &serde_json::Value,
@@ -360,6 +593,7 @@ impl TypeConversion for Option<String> {
                 })?;
                 Ok(Some(val))
             }
+            Type::Decimal | Type::Json | Type::Interval => Ok(Some(raw_text(value))),
             _ => Err(crate::error::FireboltError::Serialization(format!(
                 "Cannot convert {column_type:?} to Option<String>"
             ))),
@@ -429,15 +663,7 @@ impl TypeConversion for Vec<u8> {
                     ));
                 }
                 if let Some(s) = value.as_str() {
-                    if let Some(stripped) = s.strip_prefix("\\x") {
-                        hex::decode(stripped).map_err(|_| {
-                            crate::error::FireboltError::Serialization(
-                                "Failed to decode hex string".to_string(),
-                            )
-                        })
-                    } else {
-                        Ok(s.as_bytes().to_vec())
-                    }
+                    decode_bytea(s)
                 } else {
                     Err(crate::error::FireboltError::Serialization(
                         "Failed to convert to Vec<u8>".to_string(),
@@ -462,15 +688,7 @@ impl TypeConversion for Option<Vec<u8>> {
         match column_type {
             Type::Bytes => {
                 let val = if let Some(s) = value.as_str() {
-                    if let Some(stripped) = s.strip_prefix("\\x") {
-                        hex::decode(stripped).map_err(|_| {
-                            crate::error::FireboltError::Serialization(
-                                "Failed to decode hex string".to_string(),
-                            )
-                        })?
-                    } else {
-                        s.as_bytes().to_vec()
-                    }
+                    decode_bytea(s)?
                 } else {
                     return Err(crate::error::FireboltError::Serialization(
                         "Failed to convert to Vec<u8>".to_string(),
@@ -485,12 +703,576 @@ impl TypeConversion for Option<Vec<u8>> {
     }
 }
 
+impl TypeConversion for chrono::Duration {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        match column_type {
+            Type::Interval => {
+                if value.is_null() {
+                    return Err(crate::error::FireboltError::Serialization(
+                        "Cannot convert null to non-nullable type".to_string(),
+                    ));
+                }
+                interval_from_json(value)
+            }
+            _ => Err(crate::error::FireboltError::Serialization(format!(
+                "Cannot convert {column_type:?} to chrono::Duration"
+            ))),
+        }
+    }
+}
+
+impl TypeConversion for Option<chrono::Duration> {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        match column_type {
+            Type::Interval => Ok(Some(interval_from_json(value)?)),
+            _ => Err(crate::error::FireboltError::Serialization(format!(
+                "Cannot convert {column_type:?} to Option<chrono::Duration>"
+            ))),
+        }
+    }
+}
+
+impl TypeConversion for std::time::Duration {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        chrono::Duration::convert_from_json(value, column_type)?
+            .to_std()
+            .map_err(|_| {
+                crate::error::FireboltError::Serialization(
+                    "Cannot convert negative interval to std::time::Duration".to_string(),
+                )
+            })
+    }
+}
+
+impl TypeConversion for Option<std::time::Duration> {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        std::time::Duration::convert_from_json(value, column_type).map(Some)
+    }
+}
+
+fn interval_from_json(
+    value: &serde_json::Value,
+) -> Result<chrono::Duration, crate::error::FireboltError> {
+    let text = value.as_str().ok_or_else(|| {
+        crate::error::FireboltError::Serialization("Failed to convert to interval".to_string())
+    })?;
+    parse_interval(text)
+}
+
+// Parses the server's interval text, such as "3 days 04:05:06.5" or
+// "-1 days +02:00:00". Months and years have no fixed length, so intervals
+// that use them cannot become a duration.
+fn parse_interval(text: &str) -> Result<chrono::Duration, crate::error::FireboltError> {
+    let invalid =
+        || crate::error::FireboltError::Serialization(format!("Failed to parse interval '{text}'"));
+    let mut micros: i64 = 0;
+    let mut tokens = text.split_whitespace();
+    while let Some(token) = tokens.next() {
+        let part = if token.contains(':') {
+            parse_clock(token).ok_or_else(invalid)?
+        } else {
+            let amount: i64 = token.parse().map_err(|_| invalid())?;
+            let unit = tokens.next().ok_or_else(invalid)?;
+            let unit_micros: i64 = match unit.to_ascii_lowercase().trim_end_matches('s') {
+                "year" | "mon" | "month" => {
+                    return Err(crate::error::FireboltError::Serialization(format!(
+                        "Cannot convert interval '{text}' with months or years to a duration"
+                    )))
+                }
+                "week" => 7 * 86_400_000_000,
+                "day" => 86_400_000_000,
+                "hour" => 3_600_000_000,
+                "minute" | "min" => 60_000_000,
+                "second" | "sec" => 1_000_000,
+                "millisecond" | "msec" => 1_000,
+                "microsecond" | "usec" => 1,
+                _ => return Err(invalid()),
+            };
+            amount.checked_mul(unit_micros).ok_or_else(invalid)?
+        };
+        micros = micros.checked_add(part).ok_or_else(invalid)?;
+    }
+    Ok(chrono::Duration::microseconds(micros))
+}
+
+// "[+-]HH:MM:SS[.ffffff]" in microseconds.
+fn parse_clock(token: &str) -> Option<i64> {
+    let (negative, clock) = match token.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, token.strip_prefix('+').unwrap_or(token)),
+    };
+    let mut fields = clock.split(':');
+    let hours: i64 = fields.next()?.parse().ok()?;
+    let minutes: i64 = fields.next()?.parse().ok()?;
+    let (whole, fraction) = match fields.next() {
+        Some(seconds) => seconds.split_once('.').unwrap_or((seconds, "")),
+        None => ("0", ""),
+    };
+    if fields.next().is_some()
+        || fraction.len() > 6
+        || !fraction.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let seconds: i64 = whole.parse().ok()?;
+    let fraction: i64 = format!("{fraction:0<6}").parse().ok()?;
+    let micros = hours
+        .checked_mul(3_600)?
+        .checked_add(minutes.checked_mul(60)?.checked_add(seconds)?)?
+        .checked_mul(1_000_000)?
+        .checked_add(fraction)?;
+    Some(if negative { -micros } else { micros })
+}
+
+impl TypeConversion for chrono::DateTime<chrono::FixedOffset> {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        match column_type {
+            Type::TimestampTZ => {
+                if value.is_null() {
+                    return Err(crate::error::FireboltError::Serialization(
+                        "Cannot convert null to non-nullable type".to_string(),
+                    ));
+                }
+                timestamptz_from_json(value)
+            }
+            _ => Err(crate::error::FireboltError::Serialization(format!(
+                "Cannot convert {column_type:?} to chrono::DateTime<FixedOffset>"
+            ))),
+        }
+    }
+}
+
+impl TypeConversion for Option<chrono::DateTime<chrono::FixedOffset>> {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        match column_type {
+            Type::TimestampTZ => Ok(Some(timestamptz_from_json(value)?)),
+            _ => Err(crate::error::FireboltError::Serialization(format!(
+                "Cannot convert {column_type:?} to Option<chrono::DateTime<FixedOffset>>"
+            ))),
+        }
+    }
+}
+
+// A `timestamp` has no time zone, so reading it as `DateTime<Utc>` takes its
+// wall-clock value to be UTC. A `timestamptz` is converted to the same instant.
+impl TypeConversion for chrono::DateTime<chrono::Utc> {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        match column_type {
+            Type::Timestamp => {
+                chrono::NaiveDateTime::convert_from_json(value, column_type).map(|ts| ts.and_utc())
+            }
+            _ => chrono::DateTime::<chrono::FixedOffset>::convert_from_json(value, column_type)
+                .map(|ts| ts.to_utc()),
+        }
+    }
+}
+
+impl TypeConversion for Option<chrono::DateTime<chrono::Utc>> {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        chrono::DateTime::<chrono::Utc>::convert_from_json(value, column_type).map(Some)
+    }
+}
+
+// Only `timestamp` columns convert to `NaiveDateTime`. Dropping the offset of a
+// `timestamptz` would silently give the wall clock of whatever `time_zone` the
+// session used, so those must be read as `DateTime<Utc>` or
+// `DateTime<FixedOffset>`.
+impl TypeConversion for chrono::NaiveDateTime {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        match column_type {
+            Type::Timestamp => {
+                if value.is_null() {
+                    return Err(crate::error::FireboltError::Serialization(
+                        "Cannot convert null to non-nullable type".to_string(),
+                    ));
+                }
+                let text = value.as_str().ok_or_else(|| {
+                    crate::error::FireboltError::Serialization(
+                        "Failed to convert to timestamp".to_string(),
+                    )
+                })?;
+                parse_timestamp(text)
+            }
+            Type::TimestampTZ => Err(crate::error::FireboltError::Serialization(
+                "Cannot convert TimestampTZ to chrono::NaiveDateTime; read it as \
+                 chrono::DateTime<Utc> or chrono::DateTime<FixedOffset>"
+                    .to_string(),
+            )),
+            _ => Err(crate::error::FireboltError::Serialization(format!(
+                "Cannot convert {column_type:?} to chrono::NaiveDateTime"
+            ))),
+        }
+    }
+}
+
+impl TypeConversion for Option<chrono::NaiveDateTime> {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        chrono::NaiveDateTime::convert_from_json(value, column_type).map(Some)
+    }
+}
+
+impl TypeConversion for chrono::NaiveDate {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        match column_type {
+            Type::Date => {
+                if value.is_null() {
+                    return Err(crate::error::FireboltError::Serialization(
+                        "Cannot convert null to non-nullable type".to_string(),
+                    ));
+                }
+                let text = value.as_str().ok_or_else(|| {
+                    crate::error::FireboltError::Serialization(
+                        "Failed to convert to date".to_string(),
+                    )
+                })?;
+                chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d").map_err(|_| {
+                    crate::error::FireboltError::Serialization(format!(
+                        "Failed to parse date '{text}'"
+                    ))
+                })
+            }
+            _ => Err(crate::error::FireboltError::Serialization(format!(
+                "Cannot convert {column_type:?} to chrono::NaiveDate"
+            ))),
+        }
+    }
+}
+
+impl TypeConversion for Option<chrono::NaiveDate> {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        chrono::NaiveDate::convert_from_json(value, column_type).map(Some)
+    }
+}
+
+fn parse_timestamp(text: &str) -> Result<chrono::NaiveDateTime, crate::error::FireboltError> {
+    chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f").map_err(|_| {
+        crate::error::FireboltError::Serialization(format!("Failed to parse timestamp '{text}'"))
+    })
+}
+
+fn timestamptz_from_json(
+    value: &serde_json::Value,
+) -> Result<chrono::DateTime<chrono::FixedOffset>, crate::error::FireboltError> {
+    let text = value.as_str().ok_or_else(|| {
+        crate::error::FireboltError::Serialization("Failed to convert to timestamptz".to_string())
+    })?;
+    parse_timestamptz(text)
+}
+
+// Parses the server's timestamptz text, such as "2024-01-31 12:30:00.000500+00"
+// or "1111-01-05 17:04:42.123456+00:53:28", keeping the offset it was rendered
+// in. Historical time zones can have offsets with seconds, which chrono's
+// format strings do not accept, so the offset is parsed here.
+pub(crate) fn parse_timestamptz(
+    text: &str,
+) -> Result<chrono::DateTime<chrono::FixedOffset>, crate::error::FireboltError> {
+    let invalid = || {
+        crate::error::FireboltError::Serialization(format!("Failed to parse timestamptz '{text}'"))
+    };
+    let out_of_range = || {
+        crate::error::FireboltError::Serialization(format!(
+            "Timestamptz '{text}' is outside the range supported by chrono"
+        ))
+    };
+    // The date also contains '-', so the offset sign is searched for after it.
+    let sign_at = text
+        .char_indices()
+        .skip(10)
+        .find(|(_, c)| *c == '+' || *c == '-')
+        .map(|(i, _)| i)
+        .ok_or_else(invalid)?;
+    let (local, offset) = text.split_at(sign_at);
+    let local = chrono::NaiveDateTime::parse_from_str(local.trim(), "%Y-%m-%d %H:%M:%S%.f")
+        .map_err(|_| {
+            let year = local.trim_start().split('-').next().unwrap_or_default();
+            match year.parse::<i64>() {
+                Ok(year) if year > chrono::Datelike::year(&chrono::NaiveDate::MAX).into() => {
+                    out_of_range()
+                }
+                _ => invalid(),
+            }
+        })?;
+    let offset = parse_utc_offset(offset).ok_or_else(invalid)?;
+    // Only the extremes of chrono's range can fail here.
+    local
+        .checked_sub_offset(offset)
+        .map(|utc| chrono::DateTime::from_naive_utc_and_offset(utc, offset))
+        .ok_or_else(out_of_range)
+}
+
+// "[+-]HH[:MM[:SS]]" or "[+-]HHMM".
+fn parse_utc_offset(text: &str) -> Option<chrono::FixedOffset> {
+    let (sign, rest) = match text.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, text.strip_prefix('+')?),
+    };
+    let fields: Vec<&str> = if rest.contains(':') {
+        rest.split(':').collect()
+    } else if rest.len() == 4 {
+        vec![&rest[..2], &rest[2..]]
+    } else {
+        vec![rest]
+    };
+    if fields.len() > 3
+        || fields
+            .iter()
+            .any(|f| f.len() != 2 || !f.chars().all(|c| c.is_ascii_digit()))
+    {
+        return None;
+    }
+    let seconds = fields
+        .iter()
+        .zip([3_600, 60, 1])
+        .map(|(field, unit)| field.parse::<i32>().map(|n| n * unit))
+        .sum::<Result<i32, _>>()
+        .ok()?;
+    chrono::FixedOffset::east_opt(sign * seconds)
+}
+
+#[cfg(feature = "uuid")]
+impl TypeConversion for uuid::Uuid {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        match column_type {
+            Type::Text => {
+                if value.is_null() {
+                    return Err(crate::error::FireboltError::Serialization(
+                        "Cannot convert null to non-nullable type".to_string(),
+                    ));
+                }
+                parse_uuid(value)
+            }
+            _ => Err(crate::error::FireboltError::Serialization(format!(
+                "Cannot convert {column_type:?} to Uuid"
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl TypeConversion for Option<uuid::Uuid> {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        match column_type {
+            Type::Text => Ok(Some(parse_uuid(value)?)),
+            _ => Err(crate::error::FireboltError::Serialization(format!(
+                "Cannot convert {column_type:?} to Option<Uuid>"
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "uuid")]
+fn parse_uuid(value: &serde_json::Value) -> Result<uuid::Uuid, crate::error::FireboltError> {
+    let text = value.as_str().ok_or_else(|| {
+        crate::error::FireboltError::Serialization("Failed to convert to Uuid".to_string())
+    })?;
+    uuid::Uuid::parse_str(text).map_err(|e| {
+        crate::error::FireboltError::Serialization(format!(
+            "Failed to parse Uuid from '{text}': {e}"
+        ))
+    })
+}
+
+// JSON columns arrive as their document text, which is parsed here; other
+// columns are returned as sent.
 impl TypeConversion for serde_json::Value {
     fn convert_from_json(
         value: &serde_json::Value,
-        _column_type: &Type,
+        column_type: &Type,
+    ) -> Result<Self, crate::error::FireboltError> {
+        match (column_type, value.as_str()) {
+            (Type::Json, Some(text)) => serde_json::from_str(text).map_err(|e| {
+                crate::error::FireboltError::Serialization(format!("Failed to parse JSON: {e}"))
+            }),
+            _ => Ok(value.clone()),
+        }
+    }
+}
+
+// How `Row::get` and `ResultSet::column_values` treat a value whose column
+// type does not match the requested Rust type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConversionMode {
+    // Only the exact column types are accepted.
+    #[default]
+    Strict,
+    // Compatible values are coerced as well: numeric text to numbers, any
+    // number to a wider or floating-point type, and scalars to text.
+    Lenient,
+}
+
+pub(crate) fn convert_value<T: TypeConversion>(
+    value: &serde_json::Value,
+    column_type: &Type,
+    mode: ConversionMode,
+) -> Result<T, crate::error::FireboltError> {
+    let strict = T::convert_from_json(value, column_type);
+    if strict.is_ok() || mode == ConversionMode::Strict {
+        return strict;
+    }
+    lenient_candidates(value)
+        .into_iter()
+        .find_map(|(value, column_type)| T::convert_from_json(&value, &column_type).ok())
+        .map_or(strict, Ok)
+}
+
+// The same value re-read as each column type it could be coerced to.
+fn lenient_candidates(value: &serde_json::Value) -> Vec<(serde_json::Value, Type)> {
+    use serde_json::Value;
+
+    let numeric = |number: &serde_json::Number| {
+        let value = Value::Number(number.clone());
+        vec![
+            (value.clone(), Type::Int),
+            (value.clone(), Type::Long),
+            (value.clone(), Type::Double),
+            (value.clone(), Type::Float),
+            (value, Type::Decimal),
+            (Value::String(number.to_string()), Type::Text),
+        ]
+    };
+    match value {
+        Value::Number(number) => numeric(number),
+        Value::String(text) => {
+            let trimmed = text.trim();
+            let mut candidates = match trimmed.parse::<i64>() {
+                Ok(int) => numeric(&int.into()),
+                Err(_) => trimmed
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(serde_json::Number::from_f64)
+                    .map(|number| numeric(&number))
+                    .unwrap_or_default(),
+            };
+            candidates.push((Value::String(trimmed.to_string()), Type::Decimal));
+            candidates.push((Value::String(trimmed.to_string()), Type::Long));
+            candidates.push((value.clone(), Type::Text));
+            candidates
+        }
+        Value::Bool(flag) => vec![
+            (value.clone(), Type::Boolean),
+            (Value::String(flag.to_string()), Type::Text),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+// A single value whose Rust type follows the column type, for code that
+// walks rows without knowing the schema in advance. Dates, timestamps and
+// geography keep the server's text form, as do intervals and decimals so
+// nothing is lost; arrays and structs stay JSON, and JSON documents are parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FireboltValue {
+    Null,
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    Decimal(String),
+    Text(String),
+    Date(String),
+    Timestamp(String),
+    TimestampTZ(String),
+    Boolean(bool),
+    Array(serde_json::Value),
+    Struct(serde_json::Value),
+    Geography(String),
+    Bytes(Vec<u8>),
+    Json(serde_json::Value),
+    Interval(String),
+}
+
+impl TypeConversion for FireboltValue {
+    fn convert_from_json(
+        value: &serde_json::Value,
+        column_type: &Type,
     ) -> Result<Self, crate::error::FireboltError> {
-        Ok(value.clone())
+        if value.is_null() {
+            return Ok(FireboltValue::Null);
+        }
+        let text = || String::convert_from_json(value, &Type::Text);
+        Ok(match column_type {
+            Type::Int => FireboltValue::Int(i32::convert_from_json(value, column_type)?),
+            Type::Long => FireboltValue::Long(i64::convert_from_json(value, column_type)?),
+            Type::Float => FireboltValue::Float(f32::convert_from_json(value, column_type)?),
+            Type::Double => FireboltValue::Double(f64::convert_from_json(value, column_type)?),
+            Type::Decimal => FireboltValue::Decimal(raw_text(value)),
+            Type::Text => FireboltValue::Text(text()?),
+            Type::Date => FireboltValue::Date(text()?),
+            Type::Timestamp => FireboltValue::Timestamp(text()?),
+            Type::TimestampTZ => FireboltValue::TimestampTZ(text()?),
+            Type::Boolean => FireboltValue::Boolean(bool::convert_from_json(value, column_type)?),
+            Type::Array => FireboltValue::Array(value.clone()),
+            Type::Struct => FireboltValue::Struct(value.clone()),
+            Type::Geography => FireboltValue::Geography(text()?),
+            Type::Bytes => FireboltValue::Bytes(Vec::<u8>::convert_from_json(value, column_type)?),
+            Type::Json => {
+                FireboltValue::Json(serde_json::Value::convert_from_json(value, column_type)?)
+            }
+            Type::Interval => FireboltValue::Interval(raw_text(value)),
+        })
     }
 }
 
@@ -503,6 +1285,39 @@ pub struct Column {
     pub is_nullable: bool,
 }
 
+// Renders the column's type in Firebolt SQL syntax, such as
+// `decimal(38, 30) null`, for generating DDL.
+impl std::fmt::Display for Column {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.r#type, self.precision, self.scale) {
+            (Type::Decimal, Some(precision), Some(scale)) => {
+                write!(f, "decimal({precision}, {scale})")?
+            }
+            (r#type, _, _) => write!(f, "{type}")?,
+        }
+        if self.is_nullable {
+            f.write_str(" null")?;
+        }
+        Ok(())
+    }
+}
+
+impl Column {
+    pub fn from_sql_type(
+        name: impl Into<String>,
+        type_str: &str,
+    ) -> Result<Self, crate::error::FireboltError> {
+        let (r#type, is_nullable, precision, scale) = crate::parser::parse_sql_type(type_str)?;
+        Ok(Self {
+            name: name.into(),
+            r#type,
+            precision,
+            scale,
+            is_nullable,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ColumnRef {
     Index(usize),
@@ -4,3 +4,9 @@ pub const PROTOCOL_VERSION: &str = "2.4";
 pub fn user_agent() -> String {
     format!("Rust SDK {VERSION}")
 }
+
+// Appends the calling application in the `name/version` form other Firebolt
+// drivers use, so query history shows which service sent a query.
+pub fn user_agent_with_application(name: &str, version: &str) -> String {
+    format!("{} {name}/{version}", user_agent())
+}
@@ -1,7 +1,7 @@
 mod common;
 
 use common::TestConfig;
-use firebolt::{FireboltClient, FireboltError};
+use firebolt::{ExposeSecret, FireboltClient, FireboltError};
 
 #[tokio::test]
 async fn test_client_factory_build_integration_happy_path() {
@@ -30,7 +30,7 @@ async fn test_client_factory_build_integration_happy_path() {
     match result {
         Ok(client) => {
             assert!(!client.client_id().is_empty());
-            assert!(!client.client_secret().is_empty());
+            assert!(!client.client_secret().expose_secret().is_empty());
             assert!(!client.engine_url().is_empty());
             assert!(!client.api_endpoint().is_empty());
         }
@@ -59,7 +59,7 @@ async fn test_client_factory_build_integration_no_database_no_engine() {
     match result {
         Ok(client) => {
             assert!(!client.client_id().is_empty());
-            assert!(!client.client_secret().is_empty());
+            assert!(!client.client_secret().expose_secret().is_empty());
             assert!(!client.engine_url().is_empty());
             assert!(!client.api_endpoint().is_empty());
         }
@@ -120,3 +120,21 @@ async fn test_client_factory_build_integration_missing_required_params() {
         FireboltError::Configuration(_)
     ));
 }
+
+#[tokio::test]
+async fn test_client_factory_from_env_integration() {
+    if let Err(e) = TestConfig::from_env() {
+        println!("Skipping integration test due to setup failure: {e}");
+        return;
+    }
+
+    let result = firebolt::FireboltClientFactory::from_env()
+        .expect("Factory should be created from the environment")
+        .build()
+        .await;
+
+    match result {
+        Ok(client) => assert!(!client.engine_url().is_empty()),
+        Err(e) => panic!("Integration test failed: {e:?}"),
+    }
+}
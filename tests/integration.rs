@@ -1,7 +1,9 @@
 mod common;
 
 use common::{validate_environment, TestConfig};
+use firebolt::management::EngineStatus;
 use firebolt::FireboltClient;
+use std::time::Duration;
 
 #[allow(dead_code)]
 fn setup() -> Result<TestConfig, String> {
@@ -26,7 +28,7 @@ async fn create_client_from_config(
 #[tokio::test]
 async fn test_use_engine_functionality() -> Result<(), Box<dyn std::error::Error>> {
     let config = setup()?;
-    let mut client = create_client_from_config(&config).await?;
+    let client = create_client_from_config(&config).await?;
 
     let current_engine_result = client.query("SELECT CURRENT_ENGINE()").await?;
     let current_engine = current_engine_result
@@ -65,8 +67,14 @@ async fn test_use_engine_functionality() -> Result<(), Box<dyn std::error::Error
     client
         .query(&format!("USE ENGINE {}", config.engine))
         .await?;
+    client.stop_engine(&new_engine_name).await?;
     client
-        .query(&format!("STOP ENGINE {new_engine_name}"))
+        .wait_for_engine_status(
+            &new_engine_name,
+            EngineStatus::Stopped,
+            Duration::from_secs(300),
+            Duration::from_secs(5),
+        )
         .await?;
     client
         .query(&format!("DROP ENGINE {new_engine_name}"))
@@ -78,7 +86,7 @@ async fn test_use_engine_functionality() -> Result<(), Box<dyn std::error::Error
 #[tokio::test]
 async fn test_use_database_functionality() -> Result<(), Box<dyn std::error::Error>> {
     let config = setup()?;
-    let mut client = create_client_from_config(&config).await?;
+    let client = create_client_from_config(&config).await?;
 
     let current_database_result = client.query("SELECT CURRENT_DATABASE()").await?;
     let current_database = current_database_result
@@ -131,7 +139,7 @@ async fn test_use_database_functionality() -> Result<(), Box<dyn std::error::Err
 #[tokio::test]
 async fn test_all_data_types_parsing() -> Result<(), Box<dyn std::error::Error>> {
     let config = setup()?;
-    let mut client = create_client_from_config(&config).await?;
+    let client = create_client_from_config(&config).await?;
 
     let query = r#"
         select  1                                                         as col_int,
@@ -199,6 +207,19 @@ async fn test_all_data_types_parsing() -> Result<(), Box<dyn std::error::Error>>
     let col_text_null: Option<String> = row.get("col_text_null")?;
     assert_eq!(col_text_null, None);
 
+    let col_timestamptz: chrono::DateTime<chrono::FixedOffset> = row.get("col_timestamptz")?;
+    assert_eq!(
+        col_timestamptz.to_utc().naive_utc(),
+        chrono::NaiveDate::from_ymd_opt(1111, 1, 5)
+            .unwrap()
+            .and_hms_micro_opt(17, 4, 42, 123456)
+            .unwrap()
+    );
+
+    let col_timestamptz_null: Option<chrono::DateTime<chrono::Utc>> =
+        row.get("col_timestamptz_null")?;
+    assert_eq!(col_timestamptz_null, None);
+
     let col_boolean: bool = row.get("col_boolean")?;
     assert!(col_boolean);
 
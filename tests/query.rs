@@ -12,7 +12,7 @@ async fn test_query_integration() {
         }
     };
 
-    let mut client = FireboltClient::builder()
+    let client = FireboltClient::builder()
         .with_credentials(config.client_id, config.client_secret)
         .with_database(config.database)
         .with_engine(config.engine)
@@ -47,7 +47,7 @@ async fn test_query_invalid_sql() {
         }
     };
 
-    let mut client = FireboltClient::builder()
+    let client = FireboltClient::builder()
         .with_credentials(config.client_id, config.client_secret)
         .with_database(config.database)
         .with_engine(config.engine)
@@ -77,7 +77,7 @@ async fn test_query_with_invalid_token() {
         }
     };
 
-    let mut client = FireboltClient::builder()
+    let client = FireboltClient::builder()
         .with_credentials(config.client_id, config.client_secret)
         .with_database(config.database)
         .with_engine(config.engine)
@@ -119,7 +119,7 @@ async fn test_query_with_invalid_credentials() {
         .await;
 
     match client_result {
-        Ok(mut client) => {
+        Ok(client) => {
             let result = client.query("SELECT 1").await;
             match result {
                 Ok(_) => panic!("Expected authentication error"),